@@ -1,21 +1,27 @@
+use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, Command};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
 use tokio::sync::{mpsc, oneshot, Mutex};
-use tokio::time::timeout;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
 
 use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol::{
+    decode_response, is_unsupported_method, Request, ServerNotification, ThreadResponse,
+};
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
 use crate::codex::args::parse_codex_args;
-use crate::types::WorkspaceEntry;
+use crate::types::{AppSettings, WorkspaceEntry};
 
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
@@ -39,6 +45,30 @@ fn extract_thread_id(value: &Value) -> Option<String> {
         })
 }
 
+fn push_bounded(lines: &mut VecDeque<String>, line: &str, limit: usize) {
+    while lines.len() >= limit {
+        lines.pop_front();
+    }
+    lines.push_back(line.to_string());
+}
+
+/// Delay before restart attempt `attempt` (1-based): 1s, 2s, 4s, … capped at 30s.
+fn restart_backoff(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(5);
+    Duration::from_secs((1u64 << exponent).min(30))
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
 fn build_initialize_params(client_version: &str) -> Value {
     json!({
         "clientInfo": {
@@ -52,17 +82,158 @@ fn build_initialize_params(client_version: &str) -> Value {
     })
 }
 
+/// Timeout for listing and account reads, which should answer quickly.
+const READ_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Key in `codexRequestTimeouts` that opts every other method into a timeout.
+const ANY_METHOD_TIMEOUT_KEY: &str = "*";
+/// Number of trailing stderr lines reported when the app-server exits.
+const STDERR_TAIL_LINES: usize = 20;
+/// Restart attempts after an unexpected exit before the supervisor gives up.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// An app-server that stayed up this long is considered healthy again, so the
+/// next crash starts the backoff from scratch.
+const RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+
+/// Everything needed to (re)start a workspace's app-server process.
+struct SessionLaunch {
+    codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+    options: SessionOptions,
+}
+
+/// Per-session behaviour taken from the app settings when the session starts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SessionOptions {
+    pub(crate) auto_restart: bool,
+    /// Per-method timeout overrides in seconds; 0 disables the timeout and
+    /// `"*"` applies to methods without a timeout of their own.
+    pub(crate) request_timeouts: HashMap<String, u64>,
+}
+
+impl SessionOptions {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            auto_restart: settings.codex_auto_restart,
+            request_timeouts: settings.codex_request_timeouts.clone(),
+        }
+    }
+
+    /// How long to wait for a response to `method`. Listing and account reads
+    /// should answer quickly; everything else waits until it is answered or
+    /// cancelled unless a timeout is configured for it.
+    pub(crate) fn request_timeout(&self, method: &str) -> Option<Duration> {
+        let configured = |key: &str| {
+            self.request_timeouts
+                .get(key)
+                .map(|seconds| (*seconds > 0).then_some(Duration::from_secs(*seconds)))
+        };
+        if let Some(limit) = configured(method) {
+            return limit;
+        }
+        match method {
+            "thread/list"
+            | "thread/loaded/list"
+            | "model/list"
+            | "skills/list"
+            | "app/list"
+            | "collaborationMode/list"
+            | "mcpServerStatus/list"
+            | "account/read"
+            | "account/rateLimits/read"
+            | "config/read" => Some(READ_REQUEST_TIMEOUT),
+            // `command/exec` carries its own timeout.
+            "command/exec" => None,
+            _ => configured(ANY_METHOD_TIMEOUT_KEY).flatten(),
+        }
+    }
+}
+
+/// A request waiting for its response.
+pub(crate) struct PendingRequest {
+    method: String,
+    tx: oneshot::Sender<Value>,
+}
+
+/// Cancels its request when the caller stops waiting for it, whether it gave
+/// up, timed out or its future was dropped, so the pending entry can't leak.
+struct PendingGuard<'a> {
+    session: &'a WorkspaceSession,
+    id: u64,
+}
+
+impl PendingGuard<'_> {
+    /// The response arrived; nothing is left to cancel.
+    fn complete(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        // Dropping can't await, so the session's cancel task does the work.
+        let _ = self.session.cancel_tx.send(self.id);
+    }
+}
+
 pub(crate) struct WorkspaceSession {
     pub(crate) entry: WorkspaceEntry,
     pub(crate) child: Mutex<Child>,
     pub(crate) stdin: Mutex<ChildStdin>,
-    pub(crate) pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    pub(crate) pending: Mutex<HashMap<u64, PendingRequest>>,
     pub(crate) next_id: AtomicU64,
+    /// Ids of abandoned requests, drained by the task spawned in `spawn_cancel_task`.
+    cancel_tx: mpsc::UnboundedSender<u64>,
     /// Callbacks for background threads - events for these threadIds are sent through the channel
     pub(crate) background_thread_callbacks: Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    /// Turns currently running, keyed by threadId, so they can be interrupted on shutdown
+    pub(crate) active_turns: Mutex<HashMap<String, String>>,
+    /// Threads started or resumed on this session, resumed again after a restart
+    open_threads: Mutex<HashSet<String>>,
+    stderr_tail: std::sync::Mutex<VecDeque<String>>,
+    connected: AtomicBool,
+    /// Pid of the current app-server, 0 once it exited. Kept outside `child`
+    /// so status polls don't wait on the supervisor reaping the process.
+    pid: AtomicU32,
+    exit_code: std::sync::Mutex<Option<i32>>,
+    /// Set once the session is being torn down on purpose, so its exit is not
+    /// reported as a crash or restarted.
+    closing: AtomicBool,
+    launch: SessionLaunch,
 }
 
 impl WorkspaceSession {
+    /// Whether the app-server process is up; false after it exited, while a restart is pending.
+    pub(crate) fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Pid of the running app-server; `None` after it exited.
+    pub(crate) fn pid(&self) -> Option<u32> {
+        match self.pid.load(Ordering::SeqCst) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Exit code of the last app-server that exited, if it exited with one.
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        self.exit_code.lock().ok().and_then(|code| *code)
+    }
+
+    fn record_exit(&self, status: Option<ExitStatus>) {
+        self.pid.store(0, Ordering::SeqCst);
+        if let Ok(mut code) = self.exit_code.lock() {
+            *code = status.and_then(|status| status.code());
+        }
+    }
+
+    /// The CODEX_HOME override the app-server was started with; `None` means the default.
+    pub(crate) fn codex_home(&self) -> Option<&Path> {
+        self.launch.codex_home.as_deref()
+    }
+
     async fn write_message(&self, value: Value) -> Result<(), String> {
         let mut stdin = self.stdin.lock().await;
         let mut line = serde_json::to_string(&value).map_err(|e| e.to_string())?;
@@ -73,13 +244,135 @@ impl WorkspaceSession {
             .map_err(|e| e.to_string())
     }
 
+    /// Sends a request and waits for its response, giving up after the method's
+    /// timeout (see [`SessionOptions::request_timeout`]).
     pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let limit = self.launch.options.request_timeout(method);
+        self.send_request_with_timeout(method, params, limit).await
+    }
+
+    /// Sends a typed request and returns the raw response envelope, for callers
+    /// that hand the result to the frontend unchanged. A successful result that
+    /// doesn't match `R::Response` is an error; error envelopes pass through.
+    pub(crate) async fn send<R: Request>(&self, request: &R) -> Result<Value, String> {
+        let params = serde_json::to_value(request).map_err(|e| e.to_string())?;
+        let response = self.send_request(R::METHOD, params).await?;
+        if response.get("error").is_none() {
+            decode_response::<R::Response>(R::METHOD, response.clone())?;
+        }
+        Ok(response)
+    }
+
+    /// Sends a typed request and decodes its result.
+    pub(crate) async fn call<R: Request>(&self, request: &R) -> Result<R::Response, String> {
+        let params = serde_json::to_value(request).map_err(|e| e.to_string())?;
+        let response = self.send_request(R::METHOD, params).await?;
+        decode_response(R::METHOD, response)
+    }
+
+    /// Like `call`, but `Ok(None)` when the app-server doesn't know the method,
+    /// so callers can fall back for older Codex versions.
+    pub(crate) async fn call_if_supported<R: Request>(
+        &self,
+        request: &R,
+    ) -> Result<Option<R::Response>, String> {
+        let params = serde_json::to_value(request).map_err(|e| e.to_string())?;
+        let response = self.send_request(R::METHOD, params).await?;
+        if is_unsupported_method(&response) {
+            return Ok(None);
+        }
+        decode_response(R::METHOD, response).map(Some)
+    }
+
+    /// Like `send_request` with an explicit timeout; `None` waits until the
+    /// response arrives or the request is cancelled. A request that times out,
+    /// or whose caller drops this future, is cancelled on the app-server.
+    pub(crate) async fn send_request_with_timeout(
+        &self,
+        method: &str,
+        params: Value,
+        limit: Option<Duration>,
+    ) -> Result<Value, String> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
-        self.pending.lock().await.insert(id, tx);
-        self.write_message(json!({ "id": id, "method": method, "params": params }))
-            .await?;
-        rx.await.map_err(|_| "request canceled".to_string())
+        {
+            // Checked under the pending lock so a request can't slip in after
+            // `mark_disconnected` drained the map and then wait forever.
+            let mut pending = self.pending.lock().await;
+            if !self.is_connected() {
+                return Err("codex app-server is not running".to_string());
+            }
+            let method = method.to_string();
+            pending.insert(id, PendingRequest { method, tx });
+        }
+        let guard = PendingGuard { session: self, id };
+        let thread_id = params
+            .get("threadId")
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string());
+        if let Err(error) = self
+            .write_message(json!({ "id": id, "method": method, "params": params }))
+            .await
+        {
+            guard.complete();
+            self.pending.lock().await.remove(&id);
+            return Err(error);
+        }
+        let received = match limit {
+            Some(limit) => match timeout(limit, rx).await {
+                Ok(received) => received,
+                Err(_) => {
+                    guard.complete();
+                    self.cancel_request(id).await;
+                    return Err(format!("{method} timed out after {}s", limit.as_secs_f32()));
+                }
+            },
+            None => rx.await,
+        };
+        guard.complete();
+        let response = received.map_err(|_| {
+            if self.is_connected() {
+                "request canceled".to_string()
+            } else {
+                "codex app-server exited before responding".to_string()
+            }
+        })?;
+        self.track_thread(method, thread_id.as_deref(), &response)
+            .await;
+        Ok(response)
+    }
+
+    /// Abandons an in-flight request: its waiter gets a "request canceled" error
+    /// and the app-server is told via `$/cancelRequest` that the result is no
+    /// longer wanted. Returns false if the request already completed.
+    pub(crate) async fn cancel_request(&self, id: u64) -> bool {
+        if self.pending.lock().await.remove(&id).is_none() {
+            return false;
+        }
+        let _ = self
+            .send_notification("$/cancelRequest", Some(json!({ "id": id })))
+            .await;
+        true
+    }
+
+    /// Cancels every in-flight request for `method`, e.g. a `thread/list` that
+    /// stopped answering, and returns their ids.
+    pub(crate) async fn cancel_requests(&self, method: &str) -> Vec<u64> {
+        let ids: Vec<u64> = self
+            .pending
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, request)| request.method == method)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut cancelled = Vec::new();
+        for id in ids {
+            if self.cancel_request(id).await {
+                cancelled.push(id);
+            }
+        }
+        cancelled
     }
 
     pub(crate) async fn send_notification(
@@ -99,6 +392,83 @@ impl WorkspaceSession {
         self.write_message(json!({ "id": id, "result": result }))
             .await
     }
+
+    async fn track_turn(&self, notification: &ServerNotification) {
+        match notification {
+            ServerNotification::TurnStarted(turn) => {
+                if let Some(turn_id) = turn.turn_id() {
+                    self.active_turns
+                        .lock()
+                        .await
+                        .insert(turn.thread_id.clone(), turn_id.to_string());
+                }
+            }
+            ServerNotification::TurnCompleted(turn) => {
+                self.active_turns.lock().await.remove(&turn.thread_id);
+            }
+            _ => {}
+        }
+    }
+
+    async fn track_thread(&self, method: &str, thread_id: Option<&str>, response: &Value) {
+        if response.get("error").is_some() {
+            return;
+        }
+        match method {
+            "thread/start" | "thread/resume" => {
+                let decoded = response
+                    .get("result")
+                    .and_then(|result| ThreadResponse::deserialize(result).ok());
+                let thread_id = decoded
+                    .as_ref()
+                    .and_then(|decoded| decoded.thread_id().ok())
+                    .or(thread_id);
+                if let Some(thread_id) = thread_id {
+                    self.open_threads.lock().await.insert(thread_id.to_string());
+                }
+            }
+            "thread/archive" => {
+                if let Some(thread_id) = thread_id {
+                    self.open_threads.lock().await.remove(thread_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_stderr_line(&self, line: &str) {
+        if let Ok(mut tail) = self.stderr_tail.lock() {
+            push_bounded(&mut tail, line, STDERR_TAIL_LINES);
+        }
+    }
+
+    /// Fails every in-flight request; their callers get an error instead of hanging.
+    async fn mark_disconnected(&self) {
+        self.connected.store(false, Ordering::SeqCst);
+        self.pending.lock().await.clear();
+        self.active_turns.lock().await.clear();
+    }
+
+    /// Kills the app-server on purpose; the supervisor stays quiet and does not restart it.
+    pub(crate) async fn kill(&self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let mut child = self.child.lock().await;
+        kill_child_process_tree(&mut child).await;
+    }
+
+    /// Interrupts running turns (so they end up recorded as interrupted rather than
+    /// cut off mid-stream) and then kills the app-server process tree.
+    pub(crate) async fn shutdown(&self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let turns: Vec<(String, String)> = self.active_turns.lock().await.drain().collect();
+        for (thread_id, turn_id) in turns {
+            let params = json!({ "threadId": thread_id, "turnId": turn_id });
+            let _ = self
+                .send_request_with_timeout("turn/interrupt", params, Some(Duration::from_secs(2)))
+                .await;
+        }
+        self.kill().await;
+    }
 }
 
 pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
@@ -282,52 +652,41 @@ pub(crate) async fn check_codex_installation(
     Ok(if version.is_empty() { None } else { Some(version) })
 }
 
-pub(crate) async fn spawn_workspace_session<E: EventSink>(
-    entry: WorkspaceEntry,
-    default_codex_bin: Option<String>,
-    codex_args: Option<String>,
-    codex_home: Option<PathBuf>,
-    client_version: String,
-    event_sink: E,
-) -> Result<Arc<WorkspaceSession>, String> {
-    let codex_bin = entry
-        .codex_bin
-        .clone()
-        .filter(|value| !value.trim().is_empty())
-        .or(default_codex_bin);
-    let _ = check_codex_installation(codex_bin.clone()).await?;
-
+fn spawn_app_server(entry: &WorkspaceEntry, launch: &SessionLaunch) -> Result<Child, String> {
     let mut command = build_codex_command_with_bin(
-        codex_bin,
-        codex_args.as_deref(),
+        launch.codex_bin.clone(),
+        launch.codex_args.as_deref(),
         vec!["app-server".to_string()],
     )?;
     command.current_dir(&entry.path);
-    if let Some(codex_home) = codex_home {
+    if let Some(codex_home) = &launch.codex_home {
         command.env("CODEX_HOME", codex_home);
     }
     command.stdin(std::process::Stdio::piped());
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
+    command.spawn().map_err(|e| e.to_string())
+}
 
-    let mut child = command.spawn().map_err(|e| e.to_string())?;
+fn take_pipes(child: &mut Child) -> Result<(ChildStdin, ChildStdout, ChildStderr), String> {
     let stdin = child.stdin.take().ok_or("missing stdin")?;
     let stdout = child.stdout.take().ok_or("missing stdout")?;
     let stderr = child.stderr.take().ok_or("missing stderr")?;
+    Ok((stdin, stdout, stderr))
+}
 
-    let session = Arc::new(WorkspaceSession {
-        entry: entry.clone(),
-        child: Mutex::new(child),
-        stdin: Mutex::new(stdin),
-        pending: Mutex::new(HashMap::new()),
-        next_id: AtomicU64::new(1),
-        background_thread_callbacks: Mutex::new(HashMap::new()),
-    });
-
-    let session_clone = Arc::clone(&session);
-    let workspace_id = entry.id.clone();
+/// Spawns the stdout/stderr reader tasks. The returned handle completes once
+/// stdout closes, i.e. when the app-server went away.
+fn spawn_readers<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+    event_sink: E,
+) -> JoinHandle<()> {
+    let session_clone = Arc::clone(session);
+    let workspace_id = session.entry.id.clone();
     let event_sink_clone = event_sink.clone();
-    tokio::spawn(async move {
+    let stdout_task = tokio::spawn(async move {
         let mut lines = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
@@ -354,11 +713,28 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
 
             // Check if this event is for a background thread
             let thread_id = extract_thread_id(&value);
+            if has_method && !has_result_or_error {
+                match ServerNotification::decode(&value) {
+                    Ok(Some(notification)) => session_clone.track_turn(&notification).await,
+                    Ok(None) => {}
+                    Err(error) => {
+                        // Still forwarded below; this only flags the schema drift.
+                        let payload = AppServerEvent {
+                            workspace_id: workspace_id.clone(),
+                            message: json!({
+                                "method": "codex/protocolError",
+                                "params": { "error": error, "message": value.clone() },
+                            }),
+                        };
+                        event_sink_clone.emit_app_server_event(payload);
+                    }
+                }
+            }
 
             if let Some(id) = maybe_id {
                 if has_result_or_error {
-                    if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                        let _ = tx.send(value);
+                    if let Some(request) = session_clone.pending.lock().await.remove(&id) {
+                        let _ = request.tx.send(value);
                     }
                 } else if has_method {
                     // Check for background thread callback
@@ -378,8 +754,8 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                         };
                         event_sink_clone.emit_app_server_event(payload);
                     }
-                } else if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
-                    let _ = tx.send(value);
+                } else if let Some(request) = session_clone.pending.lock().await.remove(&id) {
+                    let _ = request.tx.send(value);
                 }
             } else if has_method {
                 // Check for background thread callback
@@ -401,16 +777,18 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 }
             }
         }
+        session_clone.mark_disconnected().await;
     });
 
-    let workspace_id = entry.id.clone();
-    let event_sink_clone = event_sink.clone();
+    let session_clone = Arc::clone(session);
+    let workspace_id = session.entry.id.clone();
     tokio::spawn(async move {
         let mut lines = BufReader::new(stderr).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
             }
+            session_clone.push_stderr_line(&line);
             let payload = AppServerEvent {
                 workspace_id: workspace_id.clone(),
                 message: json!({
@@ -418,14 +796,32 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                     "params": { "message": line },
                 }),
             };
-            event_sink_clone.emit_app_server_event(payload);
+            event_sink.emit_app_server_event(payload);
         }
     });
 
-    let init_params = build_initialize_params(&client_version);
+    stdout_task
+}
+
+/// Cancels requests whose `PendingGuard` was dropped. Holds the session weakly
+/// so it ends once the session is gone.
+fn spawn_cancel_task(session: &Arc<WorkspaceSession>, mut cancel_rx: mpsc::UnboundedReceiver<u64>) {
+    let session = Arc::downgrade(session);
+    tokio::spawn(async move {
+        while let Some(id) = cancel_rx.recv().await {
+            let Some(session) = session.upgrade() else {
+                return;
+            };
+            session.cancel_request(id).await;
+        }
+    });
+}
+
+async fn initialize_session(session: &WorkspaceSession) -> Result<(), String> {
+    let init_params = build_initialize_params(&session.launch.client_version);
     let init_result = timeout(
         Duration::from_secs(15),
-        session.send_request("initialize", init_params),
+        session.send_request_with_timeout("initialize", init_params, None),
     )
     .await;
     let init_response = match init_result {
@@ -440,7 +836,58 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         }
     };
     init_response?;
-    session.send_notification("initialized", None).await?;
+    session.send_notification("initialized", None).await
+}
+
+pub(crate) async fn spawn_workspace_session<E: EventSink>(
+    entry: WorkspaceEntry,
+    default_codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+    options: SessionOptions,
+    event_sink: E,
+) -> Result<Arc<WorkspaceSession>, String> {
+    let codex_bin = entry
+        .codex_bin
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .or(default_codex_bin);
+    let _ = check_codex_installation(codex_bin.clone()).await?;
+
+    let launch = SessionLaunch {
+        codex_bin,
+        codex_args,
+        codex_home,
+        client_version,
+        options,
+    };
+    let mut child = spawn_app_server(&entry, &launch)?;
+    let (stdin, stdout, stderr) = take_pipes(&mut child)?;
+    let pid = child.id().unwrap_or(0);
+    let (cancel_tx, cancel_rx) = mpsc::unbounded_channel();
+
+    let session = Arc::new(WorkspaceSession {
+        entry: entry.clone(),
+        child: Mutex::new(child),
+        stdin: Mutex::new(stdin),
+        pending: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+        cancel_tx,
+        background_thread_callbacks: Mutex::new(HashMap::new()),
+        active_turns: Mutex::new(HashMap::new()),
+        open_threads: Mutex::new(HashSet::new()),
+        stderr_tail: std::sync::Mutex::new(VecDeque::new()),
+        connected: AtomicBool::new(true),
+        pid: AtomicU32::new(pid),
+        exit_code: std::sync::Mutex::new(None),
+        closing: AtomicBool::new(false),
+        launch,
+    });
+
+    spawn_cancel_task(&session, cancel_rx);
+    let stdout_task = spawn_readers(&session, stdout, stderr, event_sink.clone());
+    initialize_session(&session).await?;
 
     let payload = AppServerEvent {
         workspace_id: entry.id.clone(),
@@ -451,13 +898,203 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     };
     event_sink.emit_app_server_event(payload);
 
+    tokio::spawn(supervise_session(
+        Arc::clone(&session),
+        stdout_task,
+        event_sink,
+    ));
+
     Ok(session)
 }
 
+/// Reaps the app-server once its stdout closed. Gives it a moment to exit on
+/// its own before killing whatever is left.
+async fn wait_for_exit(session: &WorkspaceSession) -> Option<ExitStatus> {
+    let mut child = session.child.lock().await;
+    match timeout(Duration::from_secs(5), child.wait()).await {
+        Ok(Ok(status)) => Some(status),
+        _ => {
+            kill_child_process_tree(&mut child).await;
+            child.try_wait().ok().flatten()
+        }
+    }
+}
+
+fn emit_disconnected<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    status: Option<ExitStatus>,
+    restarting: bool,
+    error: Option<String>,
+) {
+    let stderr: Vec<String> = session
+        .stderr_tail
+        .lock()
+        .map(|tail| tail.iter().cloned().collect())
+        .unwrap_or_default();
+    let payload = AppServerEvent {
+        workspace_id: session.entry.id.clone(),
+        message: json!({
+            "method": "codex/disconnected",
+            "params": {
+                "workspaceId": session.entry.id.clone(),
+                "exitCode": status.and_then(|status| status.code()),
+                "signal": status.as_ref().and_then(exit_signal),
+                "stderr": stderr,
+                "restarting": restarting,
+                "error": error,
+            }
+        }),
+    };
+    event_sink.emit_app_server_event(payload);
+}
+
+/// Starts a fresh app-server for an existing session and resumes the threads
+/// that were open on the previous one.
+async fn restart_session<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &E,
+) -> Result<JoinHandle<()>, String> {
+    let mut child = spawn_app_server(&session.entry, &session.launch)?;
+    let (stdin, stdout, stderr) = take_pipes(&mut child)?;
+    let pid = child.id().unwrap_or(0);
+    *session.stdin.lock().await = stdin;
+    {
+        let mut current = session.child.lock().await;
+        *current = child;
+        // `kill` raises `closing` before taking this lock, so either it sees the
+        // new child or we see the flag here.
+        if session.closing.load(Ordering::SeqCst) {
+            kill_child_process_tree(&mut current).await;
+            return Err("workspace session closed".to_string());
+        }
+    }
+    if let Ok(mut tail) = session.stderr_tail.lock() {
+        tail.clear();
+    }
+    if let Ok(mut code) = session.exit_code.lock() {
+        *code = None;
+    }
+    session.pid.store(pid, Ordering::SeqCst);
+    session.connected.store(true, Ordering::SeqCst);
+    let stdout_task = spawn_readers(session, stdout, stderr, event_sink.clone());
+    if let Err(error) = initialize_session(session).await {
+        let mut child = session.child.lock().await;
+        kill_child_process_tree(&mut child).await;
+        return Err(error);
+    }
+
+    let threads: Vec<String> = {
+        let open_threads = session.open_threads.lock().await;
+        let background = session.background_thread_callbacks.lock().await;
+        open_threads
+            .iter()
+            .filter(|thread_id| !background.contains_key(*thread_id))
+            .cloned()
+            .collect()
+    };
+    let mut resumed = Vec::new();
+    for thread_id in threads {
+        let response = session
+            .send_request_with_timeout(
+                "thread/resume",
+                json!({ "threadId": thread_id }),
+                Some(Duration::from_secs(15)),
+            )
+            .await;
+        match response {
+            Ok(response) if response.get("error").is_none() => resumed.push(thread_id),
+            _ => {
+                session.open_threads.lock().await.remove(&thread_id);
+            }
+        }
+    }
+
+    let payload = AppServerEvent {
+        workspace_id: session.entry.id.clone(),
+        message: json!({
+            "method": "codex/connected",
+            "params": {
+                "workspaceId": session.entry.id.clone(),
+                "restarted": true,
+                "resumedThreads": resumed,
+            }
+        }),
+    };
+    event_sink.emit_app_server_event(payload);
+    Ok(stdout_task)
+}
+
+/// Watches a session for its app-server exiting. Unless the exit was requested,
+/// reports it as `codex/disconnected` and, when enabled, restarts the process
+/// with exponential backoff.
+async fn supervise_session<E: EventSink>(
+    session: Arc<WorkspaceSession>,
+    mut stdout_task: JoinHandle<()>,
+    event_sink: E,
+) {
+    let mut attempts = 0;
+    let mut started_at = Instant::now();
+    loop {
+        let _ = stdout_task.await;
+        if session.closing.load(Ordering::SeqCst) {
+            return;
+        }
+        let status = wait_for_exit(&session).await;
+        session.record_exit(status);
+        if session.closing.load(Ordering::SeqCst) {
+            return;
+        }
+        if started_at.elapsed() >= RESTART_RESET_AFTER {
+            attempts = 0;
+        }
+        let restarting = session.launch.options.auto_restart && attempts < MAX_RESTART_ATTEMPTS;
+        emit_disconnected(&session, &event_sink, status, restarting, None);
+        if !restarting {
+            return;
+        }
+        loop {
+            attempts += 1;
+            sleep(restart_backoff(attempts)).await;
+            if session.closing.load(Ordering::SeqCst) {
+                return;
+            }
+            match restart_session(&session, &event_sink).await {
+                Ok(next_task) => {
+                    stdout_task = next_task;
+                    started_at = Instant::now();
+                    break;
+                }
+                Err(error) => {
+                    if session.closing.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    let retrying = attempts < MAX_RESTART_ATTEMPTS;
+                    emit_disconnected(&session, &event_sink, None, retrying, Some(error));
+                    if !retrying {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{build_initialize_params, extract_thread_id};
+    use super::{
+        build_initialize_params, extract_thread_id, push_bounded, restart_backoff,
+        spawn_workspace_session, SessionOptions, WorkspaceSession, READ_REQUEST_TIMEOUT,
+    };
+    use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+    use crate::backend::protocol::{ConfigEdit, ConfigValueWriteParams, MergeStrategy};
+    use crate::types::WorkspaceEntry;
     use serde_json::json;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::{sleep, timeout};
 
     #[test]
     fn extract_thread_id_reads_camel_case() {
@@ -477,6 +1114,229 @@ mod tests {
         assert_eq!(extract_thread_id(&value), None);
     }
 
+    #[test]
+    fn push_bounded_keeps_the_latest_lines() {
+        let mut lines = VecDeque::new();
+        for index in 0..5 {
+            push_bounded(&mut lines, &format!("line {index}"), 3);
+        }
+        assert_eq!(lines, ["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_cap() {
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(2), Duration::from_secs(2));
+        assert_eq!(restart_backoff(4), Duration::from_secs(8));
+        assert_eq!(restart_backoff(10), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn request_timeout_uses_defaults_and_overrides() {
+        let mut options = SessionOptions::default();
+        assert_eq!(
+            options.request_timeout("thread/list"),
+            Some(READ_REQUEST_TIMEOUT)
+        );
+        assert_eq!(options.request_timeout("turn/start"), None);
+        assert_eq!(options.request_timeout("command/exec"), None);
+
+        options.request_timeouts.insert("*".to_string(), 90);
+        assert_eq!(
+            options.request_timeout("turn/start"),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(options.request_timeout("command/exec"), None);
+        assert_eq!(
+            options.request_timeout("thread/list"),
+            Some(READ_REQUEST_TIMEOUT)
+        );
+
+        options
+            .request_timeouts
+            .insert("thread/list".to_string(), 5);
+        options.request_timeouts.insert("turn/start".to_string(), 0);
+        assert_eq!(
+            options.request_timeout("thread/list"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(options.request_timeout("turn/start"), None);
+    }
+
+    #[derive(Clone)]
+    struct NullSink;
+
+    impl EventSink for NullSink {
+        fn emit_app_server_event(&self, _event: AppServerEvent) {}
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    }
+
+    /// A stand-in `codex` that answers `initialize`, rejects `config/value/write`
+    /// as an unknown method, never answers anything else, and logs every
+    /// message it receives to `requests.log`.
+    #[cfg(unix)]
+    const FAKE_CODEX: &str = r#"#!/bin/sh
+if [ "$1" = "--version" ]; then echo "codex 0.0.0"; exit 0; fi
+while IFS= read -r line; do
+  printf '%s\n' "$line" >> requests.log
+  case "$line" in
+    *'"method":"initialize"'*)
+      id=${line#*\"id\":}
+      printf '{"id":%s,"result":{}}\n' "${id%%,*}"
+      ;;
+    *'"method":"config/value/write"'*)
+      id=${line#*\"id\":}
+      printf '{"id":%s,"error":{"code":-32600,"message":"unknown variant `config/value/write`"}}\n' "${id%%,*}"
+      ;;
+    *'"method":"test/exit"'*)
+      exit 3
+      ;;
+  esac
+done
+"#;
+
+    /// A workspace in a fresh temp dir whose `codex` is `FAKE_CODEX`.
+    #[cfg(unix)]
+    fn fake_codex_workspace() -> (PathBuf, WorkspaceEntry) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let codex = dir.join("codex");
+        std::fs::write(&codex, FAKE_CODEX).expect("write fake codex");
+        std::fs::set_permissions(&codex, std::fs::Permissions::from_mode(0o755))
+            .expect("chmod fake codex");
+        let entry = WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "repo".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            codex_bin: Some(codex.to_string_lossy().to_string()),
+            kind: Default::default(),
+            parent_id: None,
+            worktree: None,
+            settings: Default::default(),
+        };
+        (dir, entry)
+    }
+
+    #[cfg(unix)]
+    async fn spawn_fake_session(entry: WorkspaceEntry) -> Arc<WorkspaceSession> {
+        spawn_workspace_session(
+            entry,
+            None,
+            None,
+            None,
+            "test".to_string(),
+            SessionOptions::default(),
+            NullSink,
+        )
+        .await
+        .expect("spawn session")
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dropped_and_cancelled_requests_leave_nothing_pending() {
+        let (dir, entry) = fake_codex_workspace();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let session = spawn_fake_session(entry).await;
+
+            // The caller gives up by dropping the future.
+            let abandoned = timeout(
+                Duration::from_millis(100),
+                session.send_request("test/hang", json!({})),
+            )
+            .await;
+            assert!(abandoned.is_err());
+            let log = dir.join("requests.log");
+            let mut cancelled = false;
+            for _ in 0..200 {
+                let logged = std::fs::read_to_string(&log).unwrap_or_default();
+                if session.pending.lock().await.is_empty() && logged.contains("$/cancelRequest") {
+                    cancelled = true;
+                    break;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+            assert!(cancelled, "dropped request was not cancelled");
+
+            let waiting = Arc::clone(&session);
+            let request =
+                tokio::spawn(async move { waiting.send_request("test/hang", json!({})).await });
+            while session.pending.lock().await.is_empty() {
+                sleep(Duration::from_millis(10)).await;
+            }
+            assert!(session.cancel_requests("thread/list").await.is_empty());
+            assert_eq!(session.cancel_requests("test/hang").await.len(), 1);
+            assert_eq!(
+                request.await.expect("join").unwrap_err(),
+                "request canceled"
+            );
+            assert!(session.pending.lock().await.is_empty());
+
+            session.kill().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_is_recorded_without_the_child_lock() {
+        let (dir, entry) = fake_codex_workspace();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let session = spawn_fake_session(entry).await;
+            assert!(session.pid().is_some());
+            assert_eq!(session.exit_code(), None);
+
+            let _ = session.send_request("test/exit", json!({})).await;
+            let mut recorded = false;
+            for _ in 0..200 {
+                if !session.is_connected() && session.exit_code() == Some(3) {
+                    recorded = true;
+                    break;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+            assert!(recorded, "exit code was not recorded");
+            assert_eq!(session.pid(), None);
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn call_if_supported_returns_none_for_unknown_methods() {
+        let (dir, entry) = fake_codex_workspace();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let session = spawn_fake_session(entry).await;
+            let request = ConfigValueWriteParams {
+                edit: ConfigEdit {
+                    key_path: "features.steer".to_string(),
+                    value: json!(true),
+                    merge_strategy: MergeStrategy::Replace,
+                },
+                expected_version: None,
+            };
+            assert_eq!(session.call_if_supported(&request).await, Ok(None));
+            assert!(session.call(&request).await.is_err());
+            session.kill().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn build_initialize_params_enables_experimental_api() {
         let params = build_initialize_params("1.2.3");
@@ -0,0 +1,946 @@
+//! Typed messages for the `codex app-server` JSON-RPC protocol.
+//!
+//! Requests serialize to the params the app-server expects. Responses and
+//! notifications are decoded leniently: unknown fields are ignored and the
+//! snake_case / flattened shapes older Codex builds used are still accepted,
+//! but a missing required field is reported as an error naming the method
+//! instead of being silently treated as absent.
+//!
+//! Results the app hands to the frontend unchanged are typed as `Value`; only
+//! the fields the backend itself reads get a response struct.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A request the app-server answers, tied to its method name and response type.
+pub(crate) trait Request: Serialize {
+    const METHOD: &'static str;
+    type Response: DeserializeOwned;
+}
+
+/// Unwraps a JSON-RPC response envelope and decodes its `result`.
+///
+/// Error responses become `Err` with the server's message. Responses without a
+/// `result` key are decoded as-is, which is how some older builds replied.
+pub(crate) fn decode_response<T: DeserializeOwned>(
+    method: &str,
+    response: Value,
+) -> Result<T, String> {
+    if let Some(error) = response.get("error") {
+        return Err(error_message(error)
+            .unwrap_or_else(|| format!("{method} failed"))
+            .to_string());
+    }
+    let result = match response {
+        Value::Object(mut map) if map.contains_key("result") => {
+            map.remove("result").unwrap_or(Value::Null)
+        }
+        other => other,
+    };
+    serde_json::from_value(result)
+        .map_err(|err| format!("Unexpected {method} response from Codex: {err}"))
+}
+
+/// Whether `response` is the app-server refusing a method it doesn't know.
+/// Codex answers those with an invalid-request error naming an "unknown
+/// variant" rather than the JSON-RPC method-not-found code.
+pub(crate) fn is_unsupported_method(response: &Value) -> bool {
+    let Some(error) = response.get("error") else {
+        return false;
+    };
+    error.get("code").and_then(Value::as_i64) == Some(-32601)
+        || error_message(error).is_some_and(|message| {
+            let message = message.to_ascii_lowercase();
+            message.contains("unknown variant") || message.contains("method not found")
+        })
+}
+
+fn error_message(error: &Value) -> Option<String> {
+    error
+        .as_str()
+        .or_else(|| error.get("message").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct ThreadRef {
+    pub(crate) id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct TurnRef {
+    pub(crate) id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadStartParams {
+    pub(crate) cwd: String,
+    pub(crate) approval_policy: ApprovalPolicy,
+}
+
+impl Request for ThreadStartParams {
+    const METHOD: &'static str = "thread/start";
+    type Response = ThreadResponse;
+}
+
+/// Result of `thread/start`, `thread/resume` and `thread/fork`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadResponse {
+    #[serde(default)]
+    thread: Option<ThreadRef>,
+    #[serde(default, alias = "thread_id")]
+    thread_id: Option<String>,
+}
+
+impl ThreadResponse {
+    pub(crate) fn thread_id(&self) -> Result<&str, String> {
+        self.thread
+            .as_ref()
+            .map(|thread| thread.id.as_str())
+            .or(self.thread_id.as_deref())
+            .ok_or_else(|| "Codex thread response is missing thread.id".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadResumeParams {
+    pub(crate) thread_id: String,
+}
+
+impl Request for ThreadResumeParams {
+    const METHOD: &'static str = "thread/resume";
+    type Response = ThreadResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadForkParams {
+    pub(crate) thread_id: String,
+}
+
+impl Request for ThreadForkParams {
+    const METHOD: &'static str = "thread/fork";
+    type Response = ThreadResponse;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadArchiveParams {
+    pub(crate) thread_id: String,
+}
+
+impl Request for ThreadArchiveParams {
+    const METHOD: &'static str = "thread/archive";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadUnarchiveParams {
+    pub(crate) thread_id: String,
+}
+
+impl Request for ThreadUnarchiveParams {
+    const METHOD: &'static str = "thread/unarchive";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadReadParams {
+    pub(crate) thread_id: String,
+    pub(crate) include_turns: bool,
+}
+
+impl Request for ThreadReadParams {
+    const METHOD: &'static str = "thread/read";
+    type Response = Value;
+}
+
+/// Drops the last `num_turns` turns from the thread history. Files the agent
+/// already changed on disk are left as they are.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadRollbackParams {
+    pub(crate) thread_id: String,
+    pub(crate) num_turns: u32,
+}
+
+impl Request for ThreadRollbackParams {
+    const METHOD: &'static str = "thread/rollback";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadLoadedListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) limit: Option<u32>,
+}
+
+impl Request for ThreadLoadedListParams {
+    const METHOD: &'static str = "thread/loaded/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadCompactParams {
+    pub(crate) thread_id: String,
+}
+
+impl Request for ThreadCompactParams {
+    const METHOD: &'static str = "thread/compact/start";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadSetNameParams {
+    pub(crate) thread_id: String,
+    pub(crate) name: String,
+}
+
+impl Request for ThreadSetNameParams {
+    const METHOD: &'static str = "thread/name/set";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sort_key: Option<String>,
+}
+
+impl Request for ThreadListParams {
+    const METHOD: &'static str = "thread/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ApprovalPolicy {
+    Never,
+    OnRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum SandboxPolicy {
+    DangerFullAccess,
+    ReadOnly,
+    #[serde(rename_all = "camelCase")]
+    WorkspaceWrite {
+        writable_roots: Vec<String>,
+        network_access: bool,
+    },
+}
+
+impl SandboxPolicy {
+    /// Maps the UI access modes (`read-only`, `current`, `full-access`) to a
+    /// sandbox policy; anything unrecognized gets workspace write access.
+    pub(crate) fn for_access_mode(access_mode: &str, workspace_path: &str) -> Self {
+        match access_mode {
+            "full-access" => Self::DangerFullAccess,
+            "read-only" => Self::ReadOnly,
+            _ => Self::WorkspaceWrite {
+                writable_roots: vec![workspace_path.to_string()],
+                network_access: true,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum UserInput {
+    Text { text: String },
+    Image { url: String },
+    LocalImage { path: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnStartParams {
+    pub(crate) thread_id: String,
+    pub(crate) input: Vec<UserInput>,
+    pub(crate) cwd: String,
+    pub(crate) approval_policy: ApprovalPolicy,
+    pub(crate) sandbox_policy: SandboxPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) effort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) collaboration_mode: Option<Value>,
+}
+
+impl Request for TurnStartParams {
+    const METHOD: &'static str = "turn/start";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnInterruptParams {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+}
+
+impl Request for TurnInterruptParams {
+    const METHOD: &'static str = "turn/interrupt";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewStartParams {
+    pub(crate) thread_id: String,
+    pub(crate) target: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) delivery: Option<String>,
+}
+
+impl Request for ReviewStartParams {
+    const METHOD: &'static str = "review/start";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct ModelListParams {}
+
+impl Request for ModelListParams {
+    const METHOD: &'static str = "model/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub(crate) struct CollaborationModeListParams {}
+
+impl Request for CollaborationModeListParams {
+    const METHOD: &'static str = "collaborationMode/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SkillsListParams {
+    pub(crate) cwd: String,
+}
+
+impl Request for SkillsListParams {
+    const METHOD: &'static str = "skills/list";
+    type Response = Value;
+}
+
+/// `account/read` takes no params; the unit struct serializes to `null`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AccountReadParams;
+
+impl Request for AccountReadParams {
+    const METHOD: &'static str = "account/read";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AccountRateLimitsReadParams;
+
+impl Request for AccountRateLimitsReadParams {
+    const METHOD: &'static str = "account/rateLimits/read";
+    type Response = Value;
+}
+
+/// Codex deletes the stored credentials and sends `account/updated`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AccountLogoutParams;
+
+impl Request for AccountLogoutParams {
+    const METHOD: &'static str = "account/logout";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum LoginStartParams {
+    Chatgpt,
+}
+
+impl Request for LoginStartParams {
+    const METHOD: &'static str = "account/login/start";
+    type Response = LoginStartResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoginStartResponse {
+    #[serde(alias = "login_id")]
+    pub(crate) login_id: String,
+    #[serde(alias = "auth_url")]
+    pub(crate) auth_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoginCancelParams {
+    pub(crate) login_id: String,
+}
+
+impl Request for LoginCancelParams {
+    const METHOD: &'static str = "account/login/cancel";
+    type Response = LoginCancelResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct LoginCancelResponse {
+    #[serde(default)]
+    pub(crate) status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigReadParams {
+    pub(crate) include_layers: bool,
+    /// Workspace directory, so project-level config layers are included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cwd: Option<String>,
+}
+
+impl Request for ConfigReadParams {
+    const METHOD: &'static str = "config/read";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MergeStrategy {
+    #[default]
+    Replace,
+    Upsert,
+}
+
+/// One change to `config.toml`: `key_path` is dotted (`features.steer`) and a
+/// `null` value removes the key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigEdit {
+    pub(crate) key_path: String,
+    pub(crate) value: Value,
+    #[serde(default)]
+    pub(crate) merge_strategy: MergeStrategy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigValueWriteParams {
+    #[serde(flatten)]
+    pub(crate) edit: ConfigEdit,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expected_version: Option<String>,
+}
+
+impl Request for ConfigValueWriteParams {
+    const METHOD: &'static str = "config/value/write";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigBatchWriteParams {
+    pub(crate) edits: Vec<ConfigEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expected_version: Option<String>,
+}
+
+impl Request for ConfigBatchWriteParams {
+    const METHOD: &'static str = "config/batchWrite";
+    type Response = Value;
+}
+
+/// Makes a running app-server pick up edited `mcp_servers` tables.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct McpServerReloadParams;
+
+impl Request for McpServerReloadParams {
+    const METHOD: &'static str = "config/mcpServer/reload";
+    type Response = Value;
+}
+
+/// Starts an OAuth login for a configured MCP server. Completion arrives as
+/// `mcpServer/oauthLogin/completed` with the server `name`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerOauthLoginParams {
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) scopes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout_secs: Option<u64>,
+}
+
+impl Request for McpServerOauthLoginParams {
+    const METHOD: &'static str = "mcpServer/oauth/login";
+    type Response = McpServerOauthLoginResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerOauthLoginResponse {
+    #[serde(alias = "authorization_url")]
+    pub(crate) authorization_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServerStatusListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) limit: Option<u32>,
+}
+
+impl Request for McpServerStatusListParams {
+    const METHOD: &'static str = "mcpServerStatus/list";
+    type Response = Value;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppsListParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) limit: Option<u32>,
+}
+
+impl Request for AppsListParams {
+    const METHOD: &'static str = "app/list";
+    type Response = Value;
+}
+
+/// Runs a command outside any thread. Output is buffered and returned once the
+/// command exits or `timeout_ms` elapses.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommandExecParams {
+    pub(crate) command: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sandbox_policy: Option<SandboxPolicy>,
+}
+
+impl Request for CommandExecParams {
+    const METHOD: &'static str = "command/exec";
+    type Response = CommandExecResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommandExecResponse {
+    #[serde(alias = "exit_code")]
+    pub(crate) exit_code: i32,
+    #[serde(default)]
+    pub(crate) stdout: String,
+    #[serde(default)]
+    pub(crate) stderr: String,
+}
+
+/// Error payloads arrive either as a bare string or as `{ message, … }`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ErrorDetail {
+    Message(String),
+    Detailed { message: String },
+}
+
+impl ErrorDetail {
+    pub(crate) fn message(&self) -> &str {
+        match self {
+            ErrorDetail::Message(message) => message,
+            ErrorDetail::Detailed { message } => message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnNotification {
+    #[serde(alias = "thread_id")]
+    pub(crate) thread_id: String,
+    #[serde(default)]
+    turn: Option<TurnRef>,
+    #[serde(default, alias = "turn_id")]
+    turn_id: Option<String>,
+}
+
+impl TurnNotification {
+    pub(crate) fn turn_id(&self) -> Option<&str> {
+        self.turn
+            .as_ref()
+            .map(|turn| turn.id.as_str())
+            .or(self.turn_id.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnErrorNotification {
+    #[serde(default)]
+    pub(crate) error: Option<ErrorDetail>,
+    #[serde(default)]
+    pub(crate) will_retry: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentMessageDeltaNotification {
+    pub(crate) delta: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ItemNotification {
+    pub(crate) item: Item,
+}
+
+/// A thread item; only its type is typed, the rest varies by kind.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct Item {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    #[serde(flatten)]
+    pub(crate) fields: Map<String, Value>,
+}
+
+/// Notifications the backend itself reacts to. Everything else decodes as
+/// `Other` and is only forwarded to the frontend.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ServerNotification {
+    TurnStarted(TurnNotification),
+    TurnCompleted(TurnNotification),
+    TurnError(TurnErrorNotification),
+    AgentMessageDelta(AgentMessageDeltaNotification),
+    ItemCompleted(ItemNotification),
+    Other(String),
+}
+
+impl ServerNotification {
+    /// Decodes a notification message. Returns `Ok(None)` for responses and
+    /// server requests (anything with an `id`), and `Err` when a known method
+    /// carries params this build does not understand.
+    pub(crate) fn decode(message: &Value) -> Result<Option<Self>, String> {
+        if message.get("id").is_some() {
+            return Ok(None);
+        }
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+        let params = message.get("params").unwrap_or(&Value::Null);
+        let notification = match method {
+            "turn/started" => Self::TurnStarted(decode_params(method, params)?),
+            "turn/completed" => Self::TurnCompleted(decode_params(method, params)?),
+            "turn/error" | "error" => Self::TurnError(decode_params(method, params)?),
+            "item/agentMessage/delta" => Self::AgentMessageDelta(decode_params(method, params)?),
+            "item/completed" => Self::ItemCompleted(decode_params(method, params)?),
+            other => Self::Other(other.to_string()),
+        };
+        Ok(Some(notification))
+    }
+}
+
+fn decode_params<T: DeserializeOwned>(method: &str, params: &Value) -> Result<T, String> {
+    T::deserialize(params)
+        .map_err(|err| format!("Unexpected {method} notification from Codex: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn decode_response_unwraps_result_and_errors() {
+        let response = json!({ "id": 1, "result": { "thread": { "id": "thread-1" } } });
+        let decoded: ThreadResponse = decode_response("thread/start", response).unwrap();
+        assert_eq!(decoded.thread_id(), Ok("thread-1"));
+
+        let error = json!({ "id": 2, "error": { "message": "boom" } });
+        let result: Result<ThreadResponse, String> = decode_response("thread/start", error);
+        assert_eq!(result.unwrap_err(), "boom");
+    }
+
+    #[test]
+    fn is_unsupported_method_tells_unknown_methods_from_failures() {
+        let unknown = json!({
+            "id": 1,
+            "error": {
+                "code": -32600,
+                "message": "Invalid request: unknown variant `config/batchWrite`, expected one of `initialize`"
+            }
+        });
+        assert!(is_unsupported_method(&unknown));
+        let not_found = json!({ "id": 2, "error": { "code": -32601, "message": "nope" } });
+        assert!(is_unsupported_method(&not_found));
+
+        let rejected = json!({
+            "id": 3,
+            "error": { "code": -32600, "message": "Invalid value for features.steer" }
+        });
+        assert!(!is_unsupported_method(&rejected));
+        assert!(!is_unsupported_method(&json!({ "id": 4, "result": {} })));
+    }
+
+    #[test]
+    fn thread_response_accepts_flat_thread_id() {
+        let decoded: ThreadResponse =
+            decode_response("thread/start", json!({ "threadId": "thread-2" })).unwrap();
+        assert_eq!(decoded.thread_id(), Ok("thread-2"));
+
+        let missing: ThreadResponse =
+            decode_response("thread/start", json!({ "result": {} })).unwrap();
+        assert!(missing.thread_id().is_err());
+    }
+
+    #[test]
+    fn login_start_response_reports_missing_fields() {
+        let decoded: LoginStartResponse = decode_response(
+            "account/login/start",
+            json!({ "result": { "login_id": "l1", "auth_url": "https://example.com" } }),
+        )
+        .unwrap();
+        assert_eq!(decoded.login_id, "l1");
+
+        let error = decode_response::<LoginStartResponse>(
+            "account/login/start",
+            json!({ "result": { "loginId": "l1" } }),
+        )
+        .unwrap_err();
+        assert!(error.contains("account/login/start"));
+        assert!(error.contains("authUrl"));
+    }
+
+    #[test]
+    fn turn_start_params_serialize_wire_shape() {
+        let params = TurnStartParams {
+            thread_id: "thread-1".to_string(),
+            input: vec![
+                UserInput::Text {
+                    text: "hi".to_string(),
+                },
+                UserInput::LocalImage {
+                    path: "/tmp/a.png".to_string(),
+                },
+            ],
+            cwd: "/repo".to_string(),
+            approval_policy: ApprovalPolicy::OnRequest,
+            sandbox_policy: SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec!["/repo".to_string()],
+                network_access: true,
+            },
+            model: None,
+            effort: Some("high".to_string()),
+            collaboration_mode: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            json!({
+                "threadId": "thread-1",
+                "input": [
+                    { "type": "text", "text": "hi" },
+                    { "type": "localImage", "path": "/tmp/a.png" }
+                ],
+                "cwd": "/repo",
+                "approvalPolicy": "on-request",
+                "sandboxPolicy": {
+                    "type": "workspaceWrite",
+                    "writableRoots": ["/repo"],
+                    "networkAccess": true
+                },
+                "effort": "high"
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(AccountReadParams).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            serde_json::to_value(LoginStartParams::Chatgpt).unwrap(),
+            json!({ "type": "chatgpt" })
+        );
+    }
+
+    #[test]
+    fn thread_history_params_serialize_wire_shape() {
+        let read = ThreadReadParams {
+            thread_id: "thread-1".to_string(),
+            include_turns: true,
+        };
+        assert_eq!(
+            serde_json::to_value(read).unwrap(),
+            json!({ "threadId": "thread-1", "includeTurns": true })
+        );
+        let rollback = ThreadRollbackParams {
+            thread_id: "thread-1".to_string(),
+            num_turns: 2,
+        };
+        assert_eq!(
+            serde_json::to_value(rollback).unwrap(),
+            json!({ "threadId": "thread-1", "numTurns": 2 })
+        );
+        let loaded = ThreadLoadedListParams {
+            cursor: None,
+            limit: Some(10),
+        };
+        assert_eq!(
+            serde_json::to_value(loaded).unwrap(),
+            json!({ "limit": 10 })
+        );
+    }
+
+    #[test]
+    fn config_write_params_serialize_wire_shape() {
+        let edit: ConfigEdit =
+            serde_json::from_value(json!({ "keyPath": "features.steer", "value": true })).unwrap();
+        assert_eq!(edit.merge_strategy, MergeStrategy::Replace);
+        let params = ConfigValueWriteParams {
+            edit,
+            expected_version: None,
+        };
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            json!({ "keyPath": "features.steer", "value": true, "mergeStrategy": "replace" })
+        );
+    }
+
+    #[test]
+    fn mcp_server_oauth_login_wire_shape() {
+        let params = McpServerOauthLoginParams {
+            name: "linear".to_string(),
+            scopes: None,
+            timeout_secs: Some(120),
+        };
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            json!({ "name": "linear", "timeoutSecs": 120 })
+        );
+        let decoded: McpServerOauthLoginResponse = decode_response(
+            "mcpServer/oauth/login",
+            json!({ "result": { "authorizationUrl": "https://example.com/auth" } }),
+        )
+        .unwrap();
+        assert_eq!(decoded.authorization_url, "https://example.com/auth");
+    }
+
+    #[test]
+    fn command_exec_wire_shape() {
+        let params = CommandExecParams {
+            command: vec!["/bin/sh".to_string(), "-lc".to_string(), "make".to_string()],
+            timeout_ms: Some(5_000),
+            cwd: Some("/tmp/repo".to_string()),
+            sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+        };
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            json!({
+                "command": ["/bin/sh", "-lc", "make"],
+                "timeoutMs": 5000,
+                "cwd": "/tmp/repo",
+                "sandboxPolicy": { "type": "dangerFullAccess" }
+            })
+        );
+        let decoded: CommandExecResponse = decode_response(
+            "command/exec",
+            json!({ "result": { "exit_code": 2, "stderr": "boom" } }),
+        )
+        .unwrap();
+        assert_eq!(decoded.exit_code, 2);
+        assert_eq!(decoded.stdout, "");
+        assert_eq!(decoded.stderr, "boom");
+    }
+
+    #[test]
+    fn list_params_omit_unset_cursor_and_limit() {
+        let params = McpServerStatusListParams {
+            cursor: None,
+            limit: Some(50),
+        };
+        assert_eq!(
+            serde_json::to_value(params).unwrap(),
+            json!({ "limit": 50 })
+        );
+        assert_eq!(
+            serde_json::to_value(AppsListParams::default()).unwrap(),
+            json!({})
+        );
+    }
+
+    #[test]
+    fn decode_notification_handles_nested_and_flat_turn_ids() {
+        let nested = json!({
+            "method": "turn/started",
+            "params": { "threadId": "thread-1", "turn": { "id": "turn-9" } }
+        });
+        let Some(ServerNotification::TurnStarted(turn)) =
+            ServerNotification::decode(&nested).unwrap()
+        else {
+            panic!("expected turn/started");
+        };
+        assert_eq!(turn.turn_id(), Some("turn-9"));
+
+        let flat = json!({
+            "method": "turn/completed",
+            "params": { "thread_id": "thread-1", "turnId": "turn-3" }
+        });
+        let Some(ServerNotification::TurnCompleted(turn)) =
+            ServerNotification::decode(&flat).unwrap()
+        else {
+            panic!("expected turn/completed");
+        };
+        assert_eq!(turn.thread_id, "thread-1");
+        assert_eq!(turn.turn_id(), Some("turn-3"));
+    }
+
+    #[test]
+    fn decode_notification_tolerates_unknown_methods_and_flags_drift() {
+        let unknown = json!({ "method": "thread/somethingNew", "params": { "x": 1 } });
+        assert_eq!(
+            ServerNotification::decode(&unknown).unwrap(),
+            Some(ServerNotification::Other("thread/somethingNew".to_string()))
+        );
+
+        let request = json!({ "id": 5, "method": "item/tool/requestUserInput", "params": {} });
+        assert_eq!(ServerNotification::decode(&request).unwrap(), None);
+
+        let drifted = json!({ "method": "item/agentMessage/delta", "params": { "text": "hi" } });
+        let error = ServerNotification::decode(&drifted).unwrap_err();
+        assert!(error.contains("item/agentMessage/delta"));
+        assert!(error.contains("delta"));
+
+        let turn_error = json!({
+            "method": "error",
+            "params": { "error": { "message": "rate limited" }, "willRetry": true }
+        });
+        let Some(ServerNotification::TurnError(error)) =
+            ServerNotification::decode(&turn_error).unwrap()
+        else {
+            panic!("expected error notification");
+        };
+        assert!(error.will_retry);
+        assert_eq!(
+            error.error.as_ref().map(ErrorDetail::message),
+            Some("rate limited")
+        );
+    }
+}
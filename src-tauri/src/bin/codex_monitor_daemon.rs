@@ -3,16 +3,22 @@
 mod backend;
 #[path = "../codex/args.rs"]
 mod codex_args;
+#[path = "../codex/background.rs"]
+mod codex_background;
 #[path = "../codex/home.rs"]
 mod codex_home;
 #[path = "../codex/config.rs"]
 mod codex_config;
+#[path = "../codex/mcp.rs"]
+mod codex_mcp;
 #[path = "../files/io.rs"]
 mod file_io;
 #[path = "../files/ops.rs"]
 mod file_ops;
 #[path = "../files/policy.rs"]
 mod file_policy;
+#[path = "../git_utils.rs"]
+mod git_utils;
 #[path = "../rules.rs"]
 mod rules;
 #[path = "../storage.rs"]
@@ -32,12 +38,18 @@ mod codex {
     pub(crate) mod args {
         pub(crate) use crate::codex_args::*;
     }
+    pub(crate) mod background {
+        pub(crate) use crate::codex_background::*;
+    }
     pub(crate) mod config {
         pub(crate) use crate::codex_config::*;
     }
     pub(crate) mod home {
         pub(crate) use crate::codex_home::*;
     }
+    pub(crate) mod mcp {
+        pub(crate) use crate::codex_mcp::*;
+    }
 }
 
 mod files {
@@ -54,94 +66,563 @@ mod files {
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use futures_util::{SinkExt, StreamExt};
 use ignore::WalkBuilder;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_rustls::rustls;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::Message;
 
 use backend::app_server::{
-    spawn_workspace_session, WorkspaceSession,
+    spawn_workspace_session, SessionOptions, WorkspaceSession,
 };
 use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::protocol::{ConfigEdit, MergeStrategy};
 use storage::{read_settings, read_workspaces};
-use shared::{codex_core, files_core, git_core, settings_core, workspaces_core, worktree_core};
+use shared::{
+    codex_core, files_core, git_core, git_ui_core, script_core, settings_core, terminal_core,
+    workspaces_core, worktree_core,
+};
+use shared::terminal_core::{TerminalSession, TerminalSessionInfo};
+use codex::background::BackgroundPromptOptions;
+use codex::mcp::McpServerConfig;
 use shared::codex_core::CodexLoginCancelState;
 use workspace_settings::apply_workspace_settings_update;
 use types::{
-    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
+    AppSettings, GitBlameHunk, GitCommitDiff, GitConflictFile, GitConflictStrategy, GitFileDiff,
+    GitFileHistoryEntry, GitHubIssuesResponse, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestsResponse, GitLogResponse, GitRefDiffResponse, GitStashEntry, WorkspaceEntry,
+    WorkspaceInfo, WorkspaceScript, WorkspaceScriptRun, WorkspaceSettings, WorktreeSetupStatus,
 };
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
+/// Number of recent events kept for `events_since` and for catching up lagged clients.
+const EVENT_BUFFER_CAPACITY: usize = 10_000;
 
-fn spawn_with_client(
+async fn spawn_with_client(
     event_sink: DaemonEventSink,
     client_version: String,
+    app_settings: &Mutex<AppSettings>,
     entry: WorkspaceEntry,
     default_bin: Option<String>,
     codex_args: Option<String>,
     codex_home: Option<PathBuf>,
-) -> impl std::future::Future<Output = Result<Arc<WorkspaceSession>, String>> {
+) -> Result<Arc<WorkspaceSession>, String> {
+    let options = SessionOptions::from_settings(&*app_settings.lock().await);
     spawn_workspace_session(
         entry,
         default_bin,
         codex_args,
         codex_home,
         client_version,
+        options,
         event_sink,
     )
+    .await
 }
 
 #[derive(Clone)]
 struct DaemonEventSink {
-    tx: broadcast::Sender<DaemonEvent>,
+    tx: broadcast::Sender<SequencedEvent>,
+    log: Arc<std::sync::Mutex<EventLog>>,
+}
+
+#[derive(Clone)]
+struct SequencedEvent {
+    seq: u64,
+    event: DaemonEvent,
+}
+
+#[derive(Default)]
+struct EventLog {
+    last_seq: u64,
+    buffer: VecDeque<SequencedEvent>,
+}
+
+struct EventsSince {
+    events: Vec<SequencedEvent>,
+    latest_seq: u64,
+    /// Set when events after the requested seq were already evicted from the buffer.
+    truncated: bool,
 }
 
 #[derive(Clone)]
 enum DaemonEvent {
     AppServer(AppServerEvent),
-    #[allow(dead_code)]
     TerminalOutput(TerminalOutput),
-    #[allow(dead_code)]
     TerminalExit(TerminalExit),
 }
 
+impl DaemonEvent {
+    fn workspace_id(&self) -> &str {
+        match self {
+            DaemonEvent::AppServer(event) => &event.workspace_id,
+            DaemonEvent::TerminalOutput(event) => &event.workspace_id,
+            DaemonEvent::TerminalExit(event) => &event.workspace_id,
+        }
+    }
+}
+
+impl DaemonEventSink {
+    fn new(tx: broadcast::Sender<SequencedEvent>) -> Self {
+        Self {
+            tx,
+            log: Arc::new(std::sync::Mutex::new(EventLog::default())),
+        }
+    }
+
+    fn publish(&self, event: DaemonEvent) {
+        // Sequencing, buffering and broadcasting under one lock keeps the buffer and
+        // every subscriber in the same order.
+        let mut log = self.log.lock().unwrap_or_else(|err| err.into_inner());
+        log.last_seq += 1;
+        let entry = SequencedEvent {
+            seq: log.last_seq,
+            event,
+        };
+        if log.buffer.len() >= EVENT_BUFFER_CAPACITY {
+            log.buffer.pop_front();
+        }
+        log.buffer.push_back(entry.clone());
+        let _ = self.tx.send(entry);
+    }
+
+    /// Subscribes to live events, returning the seq of the last event the
+    /// receiver will not see.
+    fn subscribe(&self) -> (broadcast::Receiver<SequencedEvent>, u64) {
+        let log = self.log.lock().unwrap_or_else(|err| err.into_inner());
+        (self.tx.subscribe(), log.last_seq)
+    }
+
+    fn stats(&self) -> EventStats {
+        let log = self.log.lock().unwrap_or_else(|err| err.into_inner());
+        EventStats {
+            latest_seq: log.last_seq,
+            buffered: log.buffer.len(),
+            subscribers: self.tx.receiver_count(),
+            lagged: 0,
+            lost_gaps: 0,
+        }
+    }
+
+    fn events_since(&self, seq: u64) -> EventsSince {
+        let log = self.log.lock().unwrap_or_else(|err| err.into_inner());
+        let oldest = log.buffer.front().map(|entry| entry.seq);
+        EventsSince {
+            events: log
+                .buffer
+                .iter()
+                .filter(|entry| entry.seq > seq)
+                .cloned()
+                .collect(),
+            latest_seq: log.last_seq,
+            truncated: oldest.is_some_and(|oldest| oldest > seq.saturating_add(1)),
+        }
+    }
+}
+
 impl EventSink for DaemonEventSink {
     fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.tx.send(DaemonEvent::AppServer(event));
+        self.publish(DaemonEvent::AppServer(event));
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
-        let _ = self.tx.send(DaemonEvent::TerminalOutput(event));
+        self.publish(DaemonEvent::TerminalOutput(event));
     }
 
     fn emit_terminal_exit(&self, event: TerminalExit) {
-        let _ = self.tx.send(DaemonEvent::TerminalExit(event));
+        self.publish(DaemonEvent::TerminalExit(event));
+    }
+}
+
+const UNIX_LISTEN_PREFIX: &str = "unix:";
+
+#[derive(Clone, Debug)]
+enum ListenAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    fn is_unix(&self) -> bool {
+        match self {
+            ListenAddr::Tcp(_) => false,
+            #[cfg(unix)]
+            ListenAddr::Unix(_) => true,
+        }
+    }
+}
+
+impl std::fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => write!(f, "{UNIX_LISTEN_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+fn parse_listen_addr(value: &str) -> Result<ListenAddr, String> {
+    if let Some(path) = value.strip_prefix(UNIX_LISTEN_PREFIX) {
+        #[cfg(unix)]
+        {
+            if path.trim().is_empty() {
+                return Err("--listen unix: requires a socket path".to_string());
+            }
+            return Ok(ListenAddr::Unix(PathBuf::from(path.trim())));
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            return Err("Unix socket listeners are not supported on this platform".to_string());
+        }
     }
+    value
+        .parse::<SocketAddr>()
+        .map(ListenAddr::Tcp)
+        .map_err(|err| err.to_string())
 }
 
 struct DaemonConfig {
-    listen: SocketAddr,
-    token: Option<String>,
+    listen: ListenAddr,
+    listen_ws: Option<SocketAddr>,
+    metrics_listen: Option<SocketAddr>,
+    /// Accepted client tokens; empty only with `--insecure-no-auth`.
+    tokens: Vec<ClientToken>,
     data_dir: PathBuf,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+}
+
+/// A named client credential. Tokens can be limited to a set of workspaces and/or
+/// made read-only so teammates can watch an agent without driving it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClientToken {
+    name: String,
+    token: String,
+    /// Workspace ids this token may touch; omitted means every workspace.
+    #[serde(default)]
+    workspaces: Option<HashSet<String>>,
+    #[serde(default)]
+    read_only: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenFile {
+    tokens: Vec<ClientToken>,
+}
+
+/// Methods a read-only token may call. This is an allowlist so that methods added
+/// later stay off-limits to observers until someone decides they are safe.
+const READ_ONLY_METHODS: &[&str] = &[
+    "ping",
+    "daemon_status",
+    "list_workspaces",
+    "is_workspace_path_dir",
+    "worktree_setup_status",
+    "list_workspace_files",
+    "read_workspace_file",
+    "file_read",
+    "get_git_status",
+    "list_git_roots",
+    "get_git_diffs",
+    "get_git_log",
+    "get_git_commit_diff",
+    "get_git_ref_diff",
+    "get_git_blame",
+    "get_git_file_history",
+    "list_git_stashes",
+    "get_git_conflicts",
+    "get_git_stash_diff",
+    "get_git_remote",
+    "get_commit_message_prompt",
+    "read_codex_config",
+    "get_github_issues",
+    "get_github_pull_requests",
+    "get_github_pull_request_diff",
+    "get_github_pull_request_comments",
+    "list_git_branches",
+    "events_since",
+    "list_threads",
+    "read_thread",
+    "list_loaded_threads",
+    "list_mcp_server_status",
+    "list_mcp_servers",
+    "model_list",
+    "collaboration_mode_list",
+    "account_rate_limits",
+    "account_read",
+    "skills_list",
+    "apps_list",
+];
+
+/// Methods a workspace-scoped token may call without naming a workspace. Their
+/// results are filtered down to the token's workspaces.
+const UNSCOPED_METHODS: &[&str] = &["ping", "daemon_status", "list_workspaces", "events_since"];
+
+impl ClientToken {
+    fn unrestricted(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            token: String::new(),
+            workspaces: None,
+            read_only: false,
+        }
+    }
+
+    fn allows_workspace(&self, workspace_id: &str) -> bool {
+        self.workspaces
+            .as_ref()
+            .is_none_or(|workspaces| workspaces.contains(workspace_id))
+    }
+
+    fn authorize(&self, method: &str, params: &Value) -> Result<(), String> {
+        if self.read_only && !READ_ONLY_METHODS.contains(&method) {
+            return Err(format!("token `{}` is read-only", self.name));
+        }
+        // The raw config.toml would bypass the secret redaction in `read_codex_config`.
+        if self.read_only
+            && method == "file_read"
+            && parse_optional_string(params, "kind").as_deref() == Some("config")
+        {
+            return Err(format!(
+                "token `{}` is read-only and cannot read config.toml",
+                self.name
+            ));
+        }
+        if self.workspaces.is_none() {
+            return Ok(());
+        }
+        if matches!(method, "file_read" | "file_write")
+            && parse_optional_string(params, "scope").as_deref() != Some("workspace")
+        {
+            return Err(format!("token `{}` cannot access global files", self.name));
+        }
+        match request_workspace_id(method, params) {
+            Some(workspace_id) if self.allows_workspace(&workspace_id) => Ok(()),
+            Some(workspace_id) => Err(format!(
+                "token `{}` is not allowed to access workspace {workspace_id}",
+                self.name
+            )),
+            None if UNSCOPED_METHODS.contains(&method) => Ok(()),
+            None => Err(format!(
+                "token `{}` is limited to specific workspaces",
+                self.name
+            )),
+        }
+    }
+}
+
+/// The workspace a request targets, for methods that name one.
+fn request_workspace_id(method: &str, params: &Value) -> Option<String> {
+    parse_optional_string(params, "workspaceId").or_else(|| match method {
+        "add_worktree" => parse_optional_string(params, "parentId"),
+        "connect_workspace"
+        | "remove_workspace"
+        | "remove_worktree"
+        | "rename_worktree"
+        | "rename_worktree_upstream"
+        | "update_workspace_settings"
+        | "update_workspace_codex_bin" => parse_optional_string(params, "id"),
+        _ => None,
+    })
+}
+
+fn load_token_file(path: &Path) -> Result<Vec<ClientToken>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read token file {}: {err}", path.display()))?;
+    let file: TokenFile = serde_json::from_str(&contents)
+        .map_err(|err| format!("Invalid token file {}: {err}", path.display()))?;
+    let mut names = HashSet::new();
+    for entry in &file.tokens {
+        if entry.name.trim().is_empty() || entry.token.trim().is_empty() {
+            return Err(format!(
+                "Invalid token file {}: every token needs a name and a value",
+                path.display()
+            ));
+        }
+        if !names.insert(entry.name.as_str()) {
+            return Err(format!(
+                "Invalid token file {}: duplicate token name `{}`",
+                path.display(),
+                entry.name
+            ));
+        }
+    }
+    Ok(file.tokens)
+}
+
+/// Finds the token matching `provided`. Every candidate is compared in full via
+/// fixed-size digests, so response timing reveals neither which token matched
+/// nor how long a matching prefix was.
+fn find_client_token<'a>(tokens: &'a [ClientToken], provided: &str) -> Option<&'a ClientToken> {
+    let provided = Sha256::digest(provided.as_bytes());
+    let mut found = None;
+    for entry in tokens {
+        let expected = Sha256::digest(entry.token.as_bytes());
+        if constant_time_eq(&expected, &provided) && found.is_none() {
+            found = Some(entry);
+        }
+    }
+    found
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0u8, |acc, (left, right)| acc | (left ^ right))
+        == 0
+}
+
+fn audit_call(client: &ClientToken, method: &str, workspace_id: Option<&str>, outcome: &str) {
+    let workspace = workspace_id.unwrap_or("-");
+    eprintln!(
+        "audit {} token={} method={method} workspace={workspace} outcome={outcome}",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        client.name
+    );
+}
+
+/// Counters surfaced through `daemon_status` and the metrics endpoint.
+#[derive(Default)]
+struct DaemonMetrics {
+    connected_clients: AtomicUsize,
+    total_connections: AtomicU64,
+    /// Events clients fell behind on and had to catch up on from the replay buffer.
+    lagged_events: AtomicU64,
+    /// Catch-ups where the replay buffer no longer covered the whole gap.
+    lost_event_gaps: AtomicU64,
+}
+
+/// Decrements the connected-client gauge when a connection handler exits.
+struct ClientGuard(Arc<DaemonMetrics>);
+
+impl ClientGuard {
+    fn new(metrics: &Arc<DaemonMetrics>) -> Self {
+        metrics.connected_clients.fetch_add(1, Ordering::Relaxed);
+        metrics.total_connections.fetch_add(1, Ordering::Relaxed);
+        Self(Arc::clone(metrics))
+    }
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        self.0.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DaemonStatus {
+    version: &'static str,
+    pid: u32,
+    uptime_secs: u64,
+    clients: ClientStats,
+    events: EventStats,
+    terminal_sessions: usize,
+    workspaces: Vec<WorkspaceStatus>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClientStats {
+    connected: usize,
+    total: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventStats {
+    latest_seq: u64,
+    buffered: usize,
+    subscribers: usize,
+    lagged: u64,
+    lost_gaps: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceStatus {
+    id: String,
+    name: String,
+    /// `disconnected`, `running` or `exited` (the app-server died under us).
+    state: &'static str,
+    pid: Option<u32>,
+    exit_code: Option<i32>,
+    pending_requests: usize,
+    background_threads: usize,
+}
+
+/// Workspaces with a live app-server, persisted so a restarted daemon picks up
+/// where it left off.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionState {
+    connected_workspaces: Vec<String>,
+}
+
+fn read_session_state(path: &Path) -> SessionState {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Connected workspaces plus those still waiting to be restored, so a transient
+/// failure at boot doesn't drop a workspace from sessions.json.
+fn session_ids_to_persist(connected: Vec<String>, pending: &[String]) -> Vec<String> {
+    let mut ids = connected;
+    ids.extend(pending.iter().cloned());
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+fn write_session_state(path: &Path, state: &SessionState) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(path, data).map_err(|e| e.to_string())
 }
 
 struct DaemonState {
     data_dir: PathBuf,
     workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    terminal_sessions: Mutex<HashMap<String, Arc<TerminalSession>>>,
     storage_path: PathBuf,
     settings_path: PathBuf,
+    session_state_path: PathBuf,
+    /// Workspaces that were connected when the daemon last stopped and have not
+    /// reconnected since. They stay in sessions.json until they do.
+    restore_workspaces: Mutex<Vec<String>>,
     app_settings: Mutex<AppSettings>,
     event_sink: DaemonEventSink,
     codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    started_at: Instant,
+    metrics: Arc<DaemonMetrics>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -154,17 +635,136 @@ impl DaemonState {
     fn load(config: &DaemonConfig, event_sink: DaemonEventSink) -> Self {
         let storage_path = config.data_dir.join("workspaces.json");
         let settings_path = config.data_dir.join("settings.json");
+        let session_state_path = config.data_dir.join("sessions.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
+        let restore_workspaces = read_session_state(&session_state_path)
+            .connected_workspaces
+            .into_iter()
+            .filter(|id| workspaces.contains_key(id))
+            .collect();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
         Self {
             data_dir: config.data_dir.clone(),
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
+            terminal_sessions: Mutex::new(HashMap::new()),
             storage_path,
             settings_path,
+            session_state_path,
+            restore_workspaces: Mutex::new(restore_workspaces),
             app_settings: Mutex::new(app_settings),
             event_sink,
             codex_login_cancels: Mutex::new(HashMap::new()),
+            started_at: Instant::now(),
+            metrics: Arc::new(DaemonMetrics::default()),
+        }
+    }
+
+    /// Reconnects the workspaces that had sessions when the daemon last stopped.
+    async fn restore_sessions(&self) {
+        let ids = self.restore_workspaces.lock().await.clone();
+        if ids.is_empty() {
+            return;
+        }
+        let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
+        for id in ids {
+            // A failed id stays in `restore_workspaces`, so the next boot tries again.
+            match self.connect_workspace(id.clone(), client_version.clone()).await {
+                Ok(()) => eprintln!("reconnected workspace {id}"),
+                Err(err) => eprintln!("failed to reconnect workspace {id}: {err}"),
+            }
+        }
+        self.persist_sessions().await;
+    }
+
+    async fn persist_sessions(&self) {
+        let connected: Vec<String> = self.sessions.lock().await.keys().cloned().collect();
+        let connected_workspaces = {
+            let workspaces = self.workspaces.lock().await;
+            let mut pending = self.restore_workspaces.lock().await;
+            pending.retain(|id| workspaces.contains_key(id) && !connected.contains(id));
+            session_ids_to_persist(connected, &pending)
+        };
+        let state = SessionState {
+            connected_workspaces,
+        };
+        if let Err(err) = write_session_state(&self.session_state_path, &state) {
+            eprintln!("failed to persist connected workspaces: {err}");
+        }
+    }
+
+    /// Records the connected workspaces for the next boot, then interrupts running
+    /// turns and kills every app-server and terminal so nothing is orphaned.
+    async fn shutdown(&self) {
+        self.persist_sessions().await;
+        let sessions: Vec<Arc<WorkspaceSession>> = self
+            .sessions
+            .lock()
+            .await
+            .drain()
+            .map(|(_, session)| session)
+            .collect();
+        futures_util::future::join_all(sessions.iter().map(|session| session.shutdown())).await;
+        let terminals: Vec<Arc<TerminalSession>> = self
+            .terminal_sessions
+            .lock()
+            .await
+            .drain()
+            .map(|(_, session)| session)
+            .collect();
+        for terminal in terminals {
+            terminal_core::kill_terminal_session(terminal).await;
+        }
+    }
+
+    async fn daemon_status(&self) -> DaemonStatus {
+        let entries: Vec<WorkspaceEntry> = {
+            let workspaces = self.workspaces.lock().await;
+            workspaces.values().cloned().collect()
+        };
+        let sessions: HashMap<String, Arc<WorkspaceSession>> = self.sessions.lock().await.clone();
+
+        let mut workspaces = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let mut status = WorkspaceStatus {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+                state: "disconnected",
+                pid: None,
+                exit_code: None,
+                pending_requests: 0,
+                background_threads: 0,
+            };
+            if let Some(session) = sessions.get(&entry.id) {
+                if session.is_connected() {
+                    status.state = "running";
+                    status.pid = session.pid();
+                } else {
+                    status.state = "exited";
+                    status.exit_code = session.exit_code();
+                }
+                status.pending_requests = session.pending.lock().await.len();
+                status.background_threads = session.background_thread_callbacks.lock().await.len();
+            }
+            workspaces.push(status);
+        }
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+        let mut events = self.event_sink.stats();
+        events.lagged = self.metrics.lagged_events.load(Ordering::Relaxed);
+        events.lost_gaps = self.metrics.lost_event_gaps.load(Ordering::Relaxed);
+
+        DaemonStatus {
+            version: env!("CARGO_PKG_VERSION"),
+            pid: std::process::id(),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            clients: ClientStats {
+                connected: self.metrics.connected_clients.load(Ordering::Relaxed),
+                total: self.metrics.total_connections.load(Ordering::Relaxed),
+            },
+            events,
+            terminal_sessions: self.terminal_sessions.lock().await.len(),
+            workspaces,
         }
     }
 
@@ -183,7 +783,7 @@ impl DaemonState {
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let client_version = client_version.clone();
-        workspaces_core::add_workspace_core(
+        let result = workspaces_core::add_workspace_core(
             path,
             codex_bin,
             &self.workspaces,
@@ -194,6 +794,7 @@ impl DaemonState {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
+                    &self.app_settings,
                     entry,
                     default_bin,
                     codex_args,
@@ -201,7 +802,9 @@ impl DaemonState {
                 )
             },
         )
-        .await
+        .await;
+        self.persist_sessions().await;
+        result
     }
 
     async fn add_worktree(
@@ -213,7 +816,7 @@ impl DaemonState {
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let client_version = client_version.clone();
-        workspaces_core::add_worktree_core(
+        let result = workspaces_core::add_worktree_core(
             parent_id,
             branch,
             name,
@@ -242,6 +845,7 @@ impl DaemonState {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
+                    &self.app_settings,
                     entry,
                     default_bin,
                     codex_args,
@@ -249,7 +853,9 @@ impl DaemonState {
                 )
             },
         )
-        .await
+        .await;
+        self.persist_sessions().await;
+        result
     }
 
     async fn worktree_setup_status(&self, workspace_id: String) -> Result<WorktreeSetupStatus, String> {
@@ -262,8 +868,30 @@ impl DaemonState {
             .await
     }
 
+    async fn run_workspace_script(
+        &self,
+        workspace_id: String,
+        script: WorkspaceScript,
+        run_id: Option<String>,
+        timeout_secs: Option<u64>,
+        use_command_exec: bool,
+    ) -> Result<WorkspaceScriptRun, String> {
+        script_core::run_workspace_script_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.event_sink,
+            &self.data_dir,
+            workspace_id,
+            script,
+            run_id,
+            timeout_secs,
+            use_command_exec,
+        )
+        .await
+    }
+
     async fn remove_workspace(&self, id: String) -> Result<(), String> {
-        workspaces_core::remove_workspace_core(
+        let result = workspaces_core::remove_workspace_core(
             id,
             &self.workspaces,
             &self.sessions,
@@ -279,11 +907,13 @@ impl DaemonState {
             true,
             true,
         )
-        .await
+        .await;
+        self.persist_sessions().await;
+        result
     }
 
     async fn remove_worktree(&self, id: String) -> Result<(), String> {
-        workspaces_core::remove_worktree_core(
+        let result = workspaces_core::remove_worktree_core(
             id,
             &self.workspaces,
             &self.sessions,
@@ -297,7 +927,9 @@ impl DaemonState {
                     .map_err(|err| format!("Failed to remove worktree folder: {err}"))
             },
         )
-        .await
+        .await;
+        self.persist_sessions().await;
+        result
     }
 
     async fn rename_worktree(
@@ -334,6 +966,7 @@ impl DaemonState {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
+                    &self.app_settings,
                     entry,
                     default_bin,
                     codex_args,
@@ -407,6 +1040,38 @@ impl DaemonState {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
+                    &self.app_settings,
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn switch_workspace_account(
+        &self,
+        workspace_id: String,
+        profile_id: Option<String>,
+        client_version: String,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::switch_workspace_account_core(
+            workspace_id,
+            profile_id,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            |workspaces, workspace_id, next_settings| {
+                apply_workspace_settings_update(workspaces, workspace_id, next_settings)
+            },
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    &self.app_settings,
                     entry,
                     default_bin,
                     codex_args,
@@ -435,13 +1100,13 @@ impl DaemonState {
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
         {
             let sessions = self.sessions.lock().await;
-            if sessions.contains_key(&id) {
+            if sessions.get(&id).is_some_and(|session| session.is_connected()) {
                 return Ok(());
             }
         }
 
         let client_version = client_version.clone();
-        workspaces_core::connect_workspace_core(
+        let result = workspaces_core::connect_workspace_core(
             id,
             &self.workspaces,
             &self.sessions,
@@ -450,6 +1115,7 @@ impl DaemonState {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
+                    &self.app_settings,
                     entry,
                     default_bin,
                     codex_args,
@@ -457,7 +1123,9 @@ impl DaemonState {
                 )
             },
         )
-        .await
+        .await;
+        self.persist_sessions().await;
+        result
     }
 
     async fn get_app_settings(&self) -> AppSettings {
@@ -465,8 +1133,13 @@ impl DaemonState {
     }
 
     async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
-        settings_core::update_app_settings_core(settings, &self.app_settings, &self.settings_path)
-            .await
+        settings_core::update_app_settings_core(
+            settings,
+            &self.app_settings,
+            &self.settings_path,
+            &self.sessions,
+        )
+        .await
     }
 
     async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
@@ -509,59 +1182,477 @@ impl DaemonState {
         files_core::file_write_core(&self.workspaces, scope, kind, workspace_id, content).await
     }
 
-    async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
-        codex_core::start_thread_core(&self.sessions, workspace_id).await
+    async fn get_git_status(&self, workspace_id: String) -> Result<Value, String> {
+        git_ui_core::get_git_status_core(&self.workspaces, workspace_id).await
     }
 
-    async fn resume_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
-        codex_core::resume_thread_core(&self.sessions, workspace_id, thread_id).await
+    async fn list_git_roots(
+        &self,
+        workspace_id: String,
+        depth: Option<usize>,
+    ) -> Result<Vec<String>, String> {
+        git_ui_core::list_git_roots_core(&self.workspaces, workspace_id, depth).await
     }
 
-    async fn fork_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
-        codex_core::fork_thread_core(&self.sessions, workspace_id, thread_id).await
+    async fn get_git_diffs(&self, workspace_id: String) -> Result<Vec<GitFileDiff>, String> {
+        git_ui_core::get_git_diffs_core(&self.workspaces, &self.app_settings, workspace_id).await
     }
 
-    async fn list_threads(
+    async fn get_git_log(
         &self,
         workspace_id: String,
-        cursor: Option<String>,
-        limit: Option<u32>,
-        sort_key: Option<String>,
-    ) -> Result<Value, String> {
-        codex_core::list_threads_core(&self.sessions, workspace_id, cursor, limit, sort_key).await
+        limit: Option<usize>,
+    ) -> Result<GitLogResponse, String> {
+        git_ui_core::get_git_log_core(&self.workspaces, workspace_id, limit).await
     }
 
-    async fn list_mcp_server_status(
+    async fn get_git_commit_diff(
         &self,
         workspace_id: String,
-        cursor: Option<String>,
-        limit: Option<u32>,
-    ) -> Result<Value, String> {
-        codex_core::list_mcp_server_status_core(&self.sessions, workspace_id, cursor, limit).await
+        sha: String,
+    ) -> Result<Vec<GitCommitDiff>, String> {
+        git_ui_core::get_git_commit_diff_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            sha,
+        )
+        .await
     }
 
-    async fn archive_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
-        codex_core::archive_thread_core(&self.sessions, workspace_id, thread_id).await
+    async fn get_git_ref_diff(
+        &self,
+        workspace_id: String,
+        base: String,
+        head: String,
+        merge_base: bool,
+    ) -> Result<GitRefDiffResponse, String> {
+        git_ui_core::get_git_ref_diff_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            base,
+            head,
+            merge_base,
+        )
+        .await
     }
 
-    async fn compact_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
-        codex_core::compact_thread_core(&self.sessions, workspace_id, thread_id).await
+    async fn get_git_remote(&self, workspace_id: String) -> Result<Option<String>, String> {
+        git_ui_core::get_git_remote_core(&self.workspaces, workspace_id).await
     }
 
-    async fn set_thread_name(
+    async fn stage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+        git_ui_core::stage_git_file_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn stage_git_all(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::stage_git_all_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn unstage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+        git_ui_core::unstage_git_file_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn revert_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+        git_ui_core::revert_git_file_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn stage_git_hunk(
         &self,
         workspace_id: String,
-        thread_id: String,
-        name: String,
-    ) -> Result<Value, String> {
-        codex_core::set_thread_name_core(&self.sessions, workspace_id, thread_id, name).await
+        path: String,
+        hunk: String,
+        lines: Option<Vec<usize>>,
+    ) -> Result<(), String> {
+        git_ui_core::stage_git_hunk_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            path,
+            hunk,
+            lines,
+        )
+        .await
     }
 
-    async fn send_user_message(
+    async fn unstage_git_hunk(
         &self,
         workspace_id: String,
-        thread_id: String,
-        text: String,
+        path: String,
+        hunk: String,
+        lines: Option<Vec<usize>>,
+    ) -> Result<(), String> {
+        git_ui_core::unstage_git_hunk_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            path,
+            hunk,
+            lines,
+        )
+        .await
+    }
+
+    async fn revert_git_hunk(
+        &self,
+        workspace_id: String,
+        path: String,
+        hunk: String,
+        lines: Option<Vec<usize>>,
+    ) -> Result<(), String> {
+        git_ui_core::revert_git_hunk_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            path,
+            hunk,
+            lines,
+        )
+        .await
+    }
+
+    async fn get_git_blame(
+        &self,
+        workspace_id: String,
+        path: String,
+    ) -> Result<Vec<GitBlameHunk>, String> {
+        git_ui_core::get_git_blame_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn get_git_file_history(
+        &self,
+        workspace_id: String,
+        path: String,
+        limit: Option<usize>,
+    ) -> Result<Vec<GitFileHistoryEntry>, String> {
+        git_ui_core::get_git_file_history_core(&self.workspaces, workspace_id, path, limit).await
+    }
+
+    async fn get_git_conflicts(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<GitConflictFile>, String> {
+        git_ui_core::get_git_conflicts_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn resolve_git_conflict(
+        &self,
+        workspace_id: String,
+        path: String,
+        strategy: GitConflictStrategy,
+        content: Option<String>,
+    ) -> Result<(), String> {
+        git_ui_core::resolve_git_conflict_core(
+            &self.workspaces,
+            workspace_id,
+            path,
+            strategy,
+            content,
+        )
+        .await
+    }
+
+    async fn list_git_stashes(&self, workspace_id: String) -> Result<Vec<GitStashEntry>, String> {
+        git_ui_core::list_git_stashes_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn create_git_stash(
+        &self,
+        workspace_id: String,
+        message: Option<String>,
+        include_untracked: bool,
+    ) -> Result<(), String> {
+        git_ui_core::create_git_stash_core(
+            &self.workspaces,
+            workspace_id,
+            message,
+            include_untracked,
+        )
+        .await
+    }
+
+    async fn apply_git_stash(&self, workspace_id: String, sha: String) -> Result<(), String> {
+        git_ui_core::apply_git_stash_core(&self.workspaces, workspace_id, sha).await
+    }
+
+    async fn pop_git_stash(&self, workspace_id: String, sha: String) -> Result<(), String> {
+        git_ui_core::pop_git_stash_core(&self.workspaces, workspace_id, sha).await
+    }
+
+    async fn drop_git_stash(&self, workspace_id: String, sha: String) -> Result<(), String> {
+        git_ui_core::drop_git_stash_core(&self.workspaces, workspace_id, sha).await
+    }
+
+    async fn get_git_stash_diff(
+        &self,
+        workspace_id: String,
+        sha: String,
+    ) -> Result<Vec<GitCommitDiff>, String> {
+        git_ui_core::get_git_stash_diff_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            sha,
+        )
+        .await
+    }
+
+    async fn revert_git_all(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::revert_git_all_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn commit_git(&self, workspace_id: String, message: String) -> Result<(), String> {
+        git_ui_core::commit_git_core(&self.workspaces, workspace_id, message).await
+    }
+
+    async fn push_git(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::push_git_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn pull_git(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::pull_git_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn fetch_git(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::fetch_git_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn sync_git(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::sync_git_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn get_github_issues(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitHubIssuesResponse, String> {
+        git_ui_core::get_github_issues_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn get_github_pull_requests(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitHubPullRequestsResponse, String> {
+        git_ui_core::get_github_pull_requests_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn get_github_pull_request_diff(
+        &self,
+        workspace_id: String,
+        pr_number: u64,
+    ) -> Result<Vec<GitHubPullRequestDiff>, String> {
+        git_ui_core::get_github_pull_request_diff_core(&self.workspaces, workspace_id, pr_number)
+            .await
+    }
+
+    async fn get_github_pull_request_comments(
+        &self,
+        workspace_id: String,
+        pr_number: u64,
+    ) -> Result<Vec<GitHubPullRequestComment>, String> {
+        git_ui_core::get_github_pull_request_comments_core(
+            &self.workspaces,
+            workspace_id,
+            pr_number,
+        )
+        .await
+    }
+
+    async fn list_git_branches(&self, workspace_id: String) -> Result<Value, String> {
+        git_ui_core::list_git_branches_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn checkout_git_branch(&self, workspace_id: String, name: String) -> Result<(), String> {
+        git_ui_core::checkout_git_branch_core(&self.workspaces, workspace_id, name).await
+    }
+
+    async fn create_git_branch(&self, workspace_id: String, name: String) -> Result<(), String> {
+        git_ui_core::create_git_branch_core(&self.workspaces, workspace_id, name).await
+    }
+
+    async fn terminal_open(
+        &self,
+        workspace_id: String,
+        terminal_id: String,
+        cols: u16,
+        rows: u16,
+    ) -> Result<TerminalSessionInfo, String> {
+        terminal_core::terminal_open_core(
+            &self.workspaces,
+            &self.terminal_sessions,
+            workspace_id,
+            terminal_id,
+            cols,
+            rows,
+            self.event_sink.clone(),
+        )
+        .await
+    }
+
+    async fn terminal_write(
+        &self,
+        workspace_id: String,
+        terminal_id: String,
+        data: String,
+    ) -> Result<(), String> {
+        terminal_core::terminal_write_core(&self.terminal_sessions, workspace_id, terminal_id, data)
+            .await
+    }
+
+    async fn terminal_resize(
+        &self,
+        workspace_id: String,
+        terminal_id: String,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        terminal_core::terminal_resize_core(
+            &self.terminal_sessions,
+            workspace_id,
+            terminal_id,
+            cols,
+            rows,
+        )
+        .await
+    }
+
+    async fn terminal_close(
+        &self,
+        workspace_id: String,
+        terminal_id: String,
+    ) -> Result<(), String> {
+        terminal_core::terminal_close_core(&self.terminal_sessions, workspace_id, terminal_id).await
+    }
+
+    async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
+        codex_core::start_thread_core(&self.sessions, workspace_id).await
+    }
+
+    async fn resume_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
+        codex_core::resume_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn fork_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
+        codex_core::fork_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn list_threads(
+        &self,
+        workspace_id: String,
+        cursor: Option<String>,
+        limit: Option<u32>,
+        sort_key: Option<String>,
+    ) -> Result<Value, String> {
+        codex_core::list_threads_core(&self.sessions, workspace_id, cursor, limit, sort_key).await
+    }
+
+    async fn list_mcp_server_status(
+        &self,
+        workspace_id: String,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Value, String> {
+        codex_core::list_mcp_server_status_core(&self.sessions, workspace_id, cursor, limit).await
+    }
+
+    async fn list_mcp_servers(&self, workspace_id: String) -> Result<Value, String> {
+        codex_core::list_mcp_servers_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn write_mcp_server(
+        &self,
+        workspace_id: String,
+        name: String,
+        config: Option<McpServerConfig>,
+    ) -> Result<Value, String> {
+        codex_core::write_mcp_server_core(
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            name,
+            config,
+        )
+        .await
+    }
+
+    async fn reload_mcp_servers(&self, workspace_id: String) -> Result<Value, String> {
+        codex_core::reload_mcp_servers_core(&self.sessions, workspace_id).await
+    }
+
+    async fn mcp_server_oauth_login(
+        &self,
+        workspace_id: String,
+        name: String,
+        scopes: Option<Vec<String>>,
+        timeout_secs: Option<u64>,
+    ) -> Result<Value, String> {
+        codex_core::mcp_server_oauth_login_core(
+            &self.sessions,
+            workspace_id,
+            name,
+            scopes,
+            timeout_secs,
+        )
+        .await
+    }
+
+    async fn archive_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
+        codex_core::archive_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn unarchive_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        codex_core::unarchive_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn read_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        include_turns: bool,
+    ) -> Result<Value, String> {
+        codex_core::read_thread_core(&self.sessions, workspace_id, thread_id, include_turns).await
+    }
+
+    async fn rollback_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        num_turns: u32,
+    ) -> Result<Value, String> {
+        codex_core::rollback_thread_core(&self.sessions, workspace_id, thread_id, num_turns).await
+    }
+
+    async fn list_loaded_threads(
+        &self,
+        workspace_id: String,
+        cursor: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Value, String> {
+        codex_core::list_loaded_threads_core(&self.sessions, workspace_id, cursor, limit).await
+    }
+
+    async fn compact_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
+        codex_core::compact_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn set_thread_name(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        name: String,
+    ) -> Result<Value, String> {
+        codex_core::set_thread_name_core(&self.sessions, workspace_id, thread_id, name).await
+    }
+
+    async fn send_user_message(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        text: String,
         model: Option<String>,
         effort: Option<String>,
         access_mode: Option<String>,
@@ -591,6 +1682,14 @@ impl DaemonState {
         codex_core::turn_interrupt_core(&self.sessions, workspace_id, thread_id, turn_id).await
     }
 
+    async fn cancel_app_server_request(
+        &self,
+        workspace_id: String,
+        method: String,
+    ) -> Result<Value, String> {
+        codex_core::cancel_app_server_request_core(&self.sessions, workspace_id, method).await
+    }
+
     async fn start_review(
         &self,
         workspace_id: String,
@@ -627,6 +1726,16 @@ impl DaemonState {
             .await
     }
 
+    async fn codex_logout(&self, workspace_id: String) -> Result<Value, String> {
+        codex_core::codex_logout_core(
+            &self.sessions,
+            &self.workspaces,
+            &self.codex_login_cancels,
+            workspace_id,
+        )
+        .await
+    }
+
     async fn skills_list(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::skills_list_core(&self.sessions, workspace_id).await
     }
@@ -640,6 +1749,36 @@ impl DaemonState {
         codex_core::apps_list_core(&self.sessions, workspace_id, cursor, limit).await
     }
 
+    async fn get_commit_message_prompt(&self, workspace_id: String) -> Result<String, String> {
+        codex_core::get_commit_message_prompt_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn generate_commit_message(&self, workspace_id: String) -> Result<String, String> {
+        codex_core::generate_commit_message_core(
+            &self.sessions,
+            &self.workspaces,
+            &self.event_sink,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn run_background_prompt(
+        &self,
+        workspace_id: String,
+        prompt: String,
+        options: BackgroundPromptOptions,
+    ) -> Result<Value, String> {
+        codex_core::run_background_prompt_core(
+            &self.sessions,
+            &self.event_sink,
+            workspace_id,
+            prompt,
+            options,
+        )
+        .await
+    }
+
     async fn respond_to_server_request(
         &self,
         workspace_id: String,
@@ -662,6 +1801,36 @@ impl DaemonState {
     async fn get_config_model(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::get_config_model_core(&self.workspaces, workspace_id).await
     }
+
+    async fn read_codex_config(
+        &self,
+        workspace_id: Option<String>,
+        include_layers: bool,
+    ) -> Result<Value, String> {
+        codex_core::read_codex_config_core(
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            include_layers,
+        )
+        .await
+    }
+
+    async fn write_codex_config(
+        &self,
+        workspace_id: Option<String>,
+        edits: Vec<ConfigEdit>,
+        expected_version: Option<String>,
+    ) -> Result<Value, String> {
+        codex_core::write_codex_config_core(
+            &self.sessions,
+            &self.workspaces,
+            workspace_id,
+            edits,
+            expected_version,
+        )
+        .await
+    }
 }
 
 fn should_skip_dir(name: &str) -> bool {
@@ -771,21 +1940,24 @@ fn default_data_dir() -> PathBuf {
 fn usage() -> String {
     format!(
         "\
-USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n\n\
-OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --insecure-no-auth      Disable auth (dev only)\n  -h, --help             Show this help\n"
+USAGE:\n  codex-monitor-daemon [--listen <addr|unix:path>] [--listen-ws <addr>] [--metrics-listen <addr>] [--data-dir <path>] [--tls-cert <path> --tls-key <path>] [--token <token> | --token-file <path> | --insecure-no-auth]\n\n\
+OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR}); unix:<path> for a Unix socket\n  --listen-ws <addr>     Also accept WebSocket clients on this address\n  --metrics-listen <addr> Serve Prometheus metrics over plain HTTP at /metrics (bearer token required when tokens are set)\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --tls-cert <path>      PEM certificate chain; serve TLS on all listeners\n  --tls-key <path>       PEM private key for --tls-cert\n  --token <token>        Shared token required by clients\n  --token-file <path>    JSON file of named tokens with workspace/read-only scopes\n  --insecure-no-auth      Disable auth (dev only)\n  -h, --help             Show this help\n"
     )
 }
 
 fn parse_args() -> Result<DaemonConfig, String> {
-    let mut listen = DEFAULT_LISTEN_ADDR
-        .parse::<SocketAddr>()
-        .map_err(|err| err.to_string())?;
+    let mut listen = parse_listen_addr(DEFAULT_LISTEN_ADDR)?;
     let mut token = env::var("CODEX_MONITOR_DAEMON_TOKEN")
         .ok()
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty());
+    let mut token_file: Option<PathBuf> = None;
+    let mut listen_ws: Option<SocketAddr> = None;
+    let mut metrics_listen: Option<SocketAddr> = None;
     let mut insecure_no_auth = false;
     let mut data_dir: Option<PathBuf> = None;
+    let mut tls_cert: Option<PathBuf> = None;
+    let mut tls_key: Option<PathBuf> = None;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -796,7 +1968,15 @@ fn parse_args() -> Result<DaemonConfig, String> {
             }
             "--listen" => {
                 let value = args.next().ok_or("--listen requires a value")?;
-                listen = value.parse::<SocketAddr>().map_err(|err| err.to_string())?;
+                listen = parse_listen_addr(&value)?;
+            }
+            "--listen-ws" => {
+                let value = args.next().ok_or("--listen-ws requires a value")?;
+                listen_ws = Some(value.parse::<SocketAddr>().map_err(|err| err.to_string())?);
+            }
+            "--metrics-listen" => {
+                let value = args.next().ok_or("--metrics-listen requires a value")?;
+                metrics_listen = Some(value.parse::<SocketAddr>().map_err(|err| err.to_string())?);
             }
             "--token" => {
                 let value = args.next().ok_or("--token requires a value")?;
@@ -806,6 +1986,10 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 }
                 token = Some(trimmed.to_string());
             }
+            "--token-file" => {
+                let value = args.next().ok_or("--token-file requires a value")?;
+                token_file = Some(PathBuf::from(value.trim()));
+            }
             "--data-dir" => {
                 let value = args.next().ok_or("--data-dir requires a value")?;
                 let trimmed = value.trim();
@@ -814,25 +1998,66 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 }
                 data_dir = Some(PathBuf::from(trimmed));
             }
+            "--tls-cert" => {
+                let value = args.next().ok_or("--tls-cert requires a value")?;
+                tls_cert = Some(PathBuf::from(value.trim()));
+            }
+            "--tls-key" => {
+                let value = args.next().ok_or("--tls-key requires a value")?;
+                tls_key = Some(PathBuf::from(value.trim()));
+            }
             "--insecure-no-auth" => {
                 insecure_no_auth = true;
                 token = None;
+                token_file = None;
             }
             _ => return Err(format!("Unknown argument: {arg}")),
         }
     }
 
-    if token.is_none() && !insecure_no_auth {
+    let mut tokens = match token_file {
+        Some(path) => load_token_file(&path)?,
+        None => Vec::new(),
+    };
+    if let Some(token) = token {
+        // The shared token keeps full access alongside any scoped ones.
+        tokens.push(ClientToken {
+            token,
+            ..ClientToken::unrestricted("default")
+        });
+    }
+
+    // A 0600 Unix socket already limits access to the current user, so a token is
+    // only required once something is reachable over the network.
+    let local_only = listen.is_unix() && listen_ws.is_none();
+    if tokens.is_empty() && !insecure_no_auth && !local_only {
         return Err(
-            "Missing --token (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
+            "Missing --token or --token-file (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
                 .to_string(),
         );
     }
 
+    // Metrics name every workspace; without tokens to check, keep them on loopback.
+    let metrics_exposed = metrics_listen.is_some_and(|addr| !addr.ip().is_loopback());
+    if metrics_exposed && tokens.is_empty() && !insecure_no_auth {
+        return Err(
+            "--metrics-listen on a non-loopback address requires --token or --token-file."
+                .to_string(),
+        );
+    }
+
+    if tls_cert.is_some() != tls_key.is_some() {
+        return Err("--tls-cert and --tls-key must be provided together".to_string());
+    }
+
     Ok(DaemonConfig {
         listen,
-        token,
+        listen_ws,
+        metrics_listen,
+        tokens,
         data_dir: data_dir.unwrap_or_else(default_data_dir),
+        tls_cert,
+        tls_key,
     })
 }
 
@@ -854,22 +2079,29 @@ fn build_result_response(id: Option<u64>, result: Value) -> Option<String> {
     }))
 }
 
-fn build_event_notification(event: DaemonEvent) -> Option<String> {
-    let payload = match event {
+fn event_notification_value(entry: SequencedEvent) -> Value {
+    let SequencedEvent { seq, event } = entry;
+    match event {
         DaemonEvent::AppServer(payload) => json!({
             "method": "app-server-event",
             "params": payload,
+            "seq": seq,
         }),
         DaemonEvent::TerminalOutput(payload) => json!({
             "method": "terminal-output",
             "params": payload,
+            "seq": seq,
         }),
         DaemonEvent::TerminalExit(payload) => json!({
             "method": "terminal-exit",
             "params": payload,
+            "seq": seq,
         }),
-    };
-    serde_json::to_string(&payload).ok()
+    }
+}
+
+fn build_event_notification(entry: SequencedEvent) -> Option<String> {
+    serde_json::to_string(&event_notification_value(entry)).ok()
 }
 
 fn parse_auth_token(params: &Value) -> Option<String> {
@@ -917,6 +2149,38 @@ fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     }
 }
 
+fn parse_u64(value: &Value, key: &str) -> Result<u64, String> {
+    match value {
+        Value::Object(map) => map
+            .get(key)
+            .and_then(|value| value.as_u64())
+            .ok_or_else(|| format!("missing or invalid `{key}`")),
+        _ => Err(format!("missing `{key}`")),
+    }
+}
+
+fn parse_optional_u64(value: &Value, key: &str) -> Option<u64> {
+    match value {
+        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()),
+        _ => None,
+    }
+}
+
+fn parse_u16(value: &Value, key: &str) -> Result<u16, String> {
+    parse_u64(value, key)
+        .and_then(|value| u16::try_from(value).map_err(|_| format!("invalid `{key}`")))
+}
+
+fn parse_optional_usize(value: &Value, key: &str) -> Option<usize> {
+    match value {
+        Value::Object(map) => map
+            .get(key)
+            .and_then(|value| value.as_u64())
+            .and_then(|value| usize::try_from(value).ok()),
+        _ => None,
+    }
+}
+
 fn parse_optional_bool(value: &Value, key: &str) -> Option<bool> {
     match value {
         Value::Object(map) => map.get(key).and_then(|value| value.as_bool()),
@@ -924,6 +2188,18 @@ fn parse_optional_bool(value: &Value, key: &str) -> Option<bool> {
     }
 }
 
+fn parse_optional_usize_array(value: &Value, key: &str) -> Option<Vec<usize>> {
+    match value {
+        Value::Object(map) => map.get(key).and_then(|value| value.as_array()).map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_u64().and_then(|value| usize::try_from(value).ok()))
+                .collect::<Vec<_>>()
+        }),
+        _ => None,
+    }
+}
+
 fn parse_optional_string_array(value: &Value, key: &str) -> Option<Vec<String>> {
     match value {
         Value::Object(map) => map.get(key).and_then(|value| value.as_array()).map(|items| {
@@ -974,14 +2250,23 @@ fn parse_file_write_request(params: &Value) -> Result<FileWriteRequest, String>
 
 async fn handle_rpc_request(
     state: &DaemonState,
+    client: &ClientToken,
     method: &str,
     params: Value,
     client_version: String,
 ) -> Result<Value, String> {
     match method {
         "ping" => Ok(json!({ "ok": true })),
+        "daemon_status" => {
+            let mut status = state.daemon_status().await;
+            status
+                .workspaces
+                .retain(|workspace| client.allows_workspace(&workspace.id));
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
         "list_workspaces" => {
-            let workspaces = state.list_workspaces().await;
+            let mut workspaces = state.list_workspaces().await;
+            workspaces.retain(|workspace| client.allows_workspace(&workspace.id));
             serde_json::to_value(workspaces).map_err(|err| err.to_string())
         }
         "is_workspace_path_dir" => {
@@ -1015,6 +2300,18 @@ async fn handle_rpc_request(
             state.worktree_setup_mark_ran(workspace_id).await?;
             Ok(json!({ "ok": true }))
         }
+        "run_workspace_script" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let script = parse_optional_value(&params, "script").ok_or("missing `script`")?;
+            let script = serde_json::from_value(script).map_err(|err| err.to_string())?;
+            let run_id = parse_optional_string(&params, "runId");
+            let timeout_secs = parse_optional_u64(&params, "timeoutSecs");
+            let use_command_exec = parse_optional_bool(&params, "useCommandExec").unwrap_or(false);
+            let run = state
+                .run_workspace_script(workspace_id, script, run_id, timeout_secs, use_command_exec)
+                .await?;
+            serde_json::to_value(run).map_err(|err| err.to_string())
+        }
         "connect_workspace" => {
             let id = parse_string(&params, "id")?;
             state.connect_workspace(id, client_version).await?;
@@ -1058,6 +2355,14 @@ async fn handle_rpc_request(
                 .await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "switch_workspace_account" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let profile_id = parse_optional_string(&params, "profileId");
+            let workspace = state
+                .switch_workspace_account(workspace_id, profile_id, client_version)
+                .await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
         "update_workspace_codex_bin" => {
             let id = parse_string(&params, "id")?;
             let codex_bin = parse_optional_string(&params, "codex_bin");
@@ -1112,9 +2417,327 @@ async fn handle_rpc_request(
             let path = settings_core::get_codex_config_path_core()?;
             Ok(Value::String(path))
         }
-        "get_config_model" => {
+        "get_config_model" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.get_config_model(workspace_id).await
+        }
+        "read_codex_config" => {
+            let workspace_id = parse_optional_string(&params, "workspaceId");
+            let include_layers = parse_optional_bool(&params, "includeLayers").unwrap_or(false);
+            let mut config = state.read_codex_config(workspace_id, include_layers).await?;
+            if client.read_only {
+                codex_mcp::redact_config_secrets(&mut config);
+            }
+            Ok(config)
+        }
+        "write_codex_config_value" => {
+            let workspace_id = parse_optional_string(&params, "workspaceId");
+            let merge_strategy = match parse_optional_value(&params, "mergeStrategy") {
+                Some(value) if !value.is_null() => {
+                    serde_json::from_value(value).map_err(|err| err.to_string())?
+                }
+                _ => MergeStrategy::default(),
+            };
+            let edit = ConfigEdit {
+                key_path: parse_string(&params, "keyPath")?,
+                value: parse_optional_value(&params, "value").unwrap_or(Value::Null),
+                merge_strategy,
+            };
+            let expected_version = parse_optional_string(&params, "expectedVersion");
+            state
+                .write_codex_config(workspace_id, vec![edit], expected_version)
+                .await
+        }
+        "batch_write_codex_config" => {
+            let workspace_id = parse_optional_string(&params, "workspaceId");
+            let edits = parse_optional_value(&params, "edits").ok_or("missing `edits`")?;
+            let edits: Vec<ConfigEdit> =
+                serde_json::from_value(edits).map_err(|err| err.to_string())?;
+            let expected_version = parse_optional_string(&params, "expectedVersion");
+            state
+                .write_codex_config(workspace_id, edits, expected_version)
+                .await
+        }
+        "get_git_status" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.get_git_status(workspace_id).await
+        }
+        "list_git_roots" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let depth = parse_optional_usize(&params, "depth");
+            let roots = state.list_git_roots(workspace_id, depth).await?;
+            serde_json::to_value(roots).map_err(|err| err.to_string())
+        }
+        "get_git_diffs" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let diffs = state.get_git_diffs(workspace_id).await?;
+            serde_json::to_value(diffs).map_err(|err| err.to_string())
+        }
+        "get_git_log" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let limit = parse_optional_usize(&params, "limit");
+            let log = state.get_git_log(workspace_id, limit).await?;
+            serde_json::to_value(log).map_err(|err| err.to_string())
+        }
+        "get_git_commit_diff" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            let diffs = state.get_git_commit_diff(workspace_id, sha).await?;
+            serde_json::to_value(diffs).map_err(|err| err.to_string())
+        }
+        "get_git_ref_diff" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let base = parse_string(&params, "base")?;
+            let head = parse_string(&params, "head")?;
+            let merge_base = parse_optional_bool(&params, "mergeBase").unwrap_or(true);
+            let diff = state
+                .get_git_ref_diff(workspace_id, base, head, merge_base)
+                .await?;
+            serde_json::to_value(diff).map_err(|err| err.to_string())
+        }
+        "get_git_remote" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let remote = state.get_git_remote(workspace_id).await?;
+            serde_json::to_value(remote).map_err(|err| err.to_string())
+        }
+        "stage_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            state.stage_git_file(workspace_id, path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "stage_git_all" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.stage_git_all(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "unstage_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            state.unstage_git_file(workspace_id, path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "revert_git_file" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            state.revert_git_file(workspace_id, path).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "stage_git_hunk" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let hunk = parse_string(&params, "hunk")?;
+            let lines = parse_optional_usize_array(&params, "lines");
+            state.stage_git_hunk(workspace_id, path, hunk, lines).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "unstage_git_hunk" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let hunk = parse_string(&params, "hunk")?;
+            let lines = parse_optional_usize_array(&params, "lines");
+            state.unstage_git_hunk(workspace_id, path, hunk, lines).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "revert_git_hunk" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let hunk = parse_string(&params, "hunk")?;
+            let lines = parse_optional_usize_array(&params, "lines");
+            state.revert_git_hunk(workspace_id, path, hunk, lines).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "get_git_blame" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let hunks = state.get_git_blame(workspace_id, path).await?;
+            serde_json::to_value(hunks).map_err(|err| err.to_string())
+        }
+        "get_git_file_history" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let limit = parse_optional_usize(&params, "limit");
+            let entries = state.get_git_file_history(workspace_id, path, limit).await?;
+            serde_json::to_value(entries).map_err(|err| err.to_string())
+        }
+        "get_git_conflicts" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let conflicts = state.get_git_conflicts(workspace_id).await?;
+            serde_json::to_value(conflicts).map_err(|err| err.to_string())
+        }
+        "resolve_git_conflict" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let strategy = parse_optional_value(&params, "strategy").ok_or("missing `strategy`")?;
+            let strategy = serde_json::from_value(strategy).map_err(|err| err.to_string())?;
+            let content = parse_optional_string(&params, "content");
+            state
+                .resolve_git_conflict(workspace_id, path, strategy, content)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "list_git_stashes" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let stashes = state.list_git_stashes(workspace_id).await?;
+            serde_json::to_value(stashes).map_err(|err| err.to_string())
+        }
+        "create_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let message = parse_optional_string(&params, "message");
+            let include_untracked =
+                parse_optional_bool(&params, "includeUntracked").unwrap_or(false);
+            state
+                .create_git_stash(workspace_id, message, include_untracked)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "apply_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            state.apply_git_stash(workspace_id, sha).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "pop_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            state.pop_git_stash(workspace_id, sha).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "drop_git_stash" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            state.drop_git_stash(workspace_id, sha).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "get_git_stash_diff" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sha = parse_string(&params, "sha")?;
+            let diffs = state.get_git_stash_diff(workspace_id, sha).await?;
+            serde_json::to_value(diffs).map_err(|err| err.to_string())
+        }
+        "revert_git_all" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.revert_git_all(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "commit_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let message = parse_string(&params, "message")?;
+            state.commit_git(workspace_id, message).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "push_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.push_git(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "pull_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.pull_git(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "fetch_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.fetch_git(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "sync_git" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.sync_git(workspace_id).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "get_github_issues" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let issues = state.get_github_issues(workspace_id).await?;
+            serde_json::to_value(issues).map_err(|err| err.to_string())
+        }
+        "get_github_pull_requests" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let pull_requests = state.get_github_pull_requests(workspace_id).await?;
+            serde_json::to_value(pull_requests).map_err(|err| err.to_string())
+        }
+        "get_github_pull_request_diff" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let pr_number = parse_u64(&params, "prNumber")?;
+            let diff = state
+                .get_github_pull_request_diff(workspace_id, pr_number)
+                .await?;
+            serde_json::to_value(diff).map_err(|err| err.to_string())
+        }
+        "get_github_pull_request_comments" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let pr_number = parse_u64(&params, "prNumber")?;
+            let comments = state
+                .get_github_pull_request_comments(workspace_id, pr_number)
+                .await?;
+            serde_json::to_value(comments).map_err(|err| err.to_string())
+        }
+        "list_git_branches" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.list_git_branches(workspace_id).await
+        }
+        "checkout_git_branch" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            state.checkout_git_branch(workspace_id, name).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "create_git_branch" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            state.create_git_branch(workspace_id, name).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "events_since" => {
+            // Without `seq` this only reports the current cursor.
+            let seq = parse_optional_u64(&params, "seq").unwrap_or(u64::MAX);
+            let since = state.event_sink.events_since(seq);
+            let events: Vec<Value> = since
+                .events
+                .into_iter()
+                .filter(|entry| client.allows_workspace(entry.event.workspace_id()))
+                .map(event_notification_value)
+                .collect();
+            Ok(json!({
+                "events": events,
+                "latestSeq": since.latest_seq,
+                "truncated": since.truncated,
+            }))
+        }
+        "terminal_open" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let cols = parse_u16(&params, "cols")?;
+            let rows = parse_u16(&params, "rows")?;
+            let info = state
+                .terminal_open(workspace_id, terminal_id, cols, rows)
+                .await?;
+            serde_json::to_value(info).map_err(|err| err.to_string())
+        }
+        "terminal_write" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let data = parse_string(&params, "data")?;
+            state
+                .terminal_write(workspace_id, terminal_id, data)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "terminal_resize" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let terminal_id = parse_string(&params, "terminalId")?;
+            let cols = parse_u16(&params, "cols")?;
+            let rows = parse_u16(&params, "rows")?;
+            state
+                .terminal_resize(workspace_id, terminal_id, cols, rows)
+                .await?;
+            Ok(json!({ "ok": true }))
+        }
+        "terminal_close" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
-            state.get_config_model(workspace_id).await
+            let terminal_id = parse_string(&params, "terminalId")?;
+            state.terminal_close(workspace_id, terminal_id).await?;
+            Ok(json!({ "ok": true }))
         }
         "start_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
@@ -1143,11 +2766,69 @@ async fn handle_rpc_request(
             let limit = parse_optional_u32(&params, "limit");
             state.list_mcp_server_status(workspace_id, cursor, limit).await
         }
+        "list_mcp_servers" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let mut servers = state.list_mcp_servers(workspace_id).await?;
+            if client.read_only {
+                codex_mcp::redact_listed_servers(&mut servers);
+            }
+            Ok(servers)
+        }
+        "upsert_mcp_server" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            let config = parse_optional_value(&params, "config").ok_or("missing `config`")?;
+            let config: McpServerConfig =
+                serde_json::from_value(config).map_err(|err| err.to_string())?;
+            state.write_mcp_server(workspace_id, name, Some(config)).await
+        }
+        "remove_mcp_server" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            state.write_mcp_server(workspace_id, name, None).await
+        }
+        "reload_mcp_servers" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.reload_mcp_servers(workspace_id).await
+        }
+        "mcp_server_oauth_login" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let name = parse_string(&params, "name")?;
+            let scopes = parse_optional_string_array(&params, "scopes");
+            let timeout_secs = parse_optional_u64(&params, "timeoutSecs");
+            state
+                .mcp_server_oauth_login(workspace_id, name, scopes, timeout_secs)
+                .await
+        }
         "archive_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
             state.archive_thread(workspace_id, thread_id).await
         }
+        "unarchive_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            state.unarchive_thread(workspace_id, thread_id).await
+        }
+        "read_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let include_turns = parse_optional_bool(&params, "includeTurns").unwrap_or(true);
+            state.read_thread(workspace_id, thread_id, include_turns).await
+        }
+        "rollback_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let num_turns =
+                parse_optional_u32(&params, "numTurns").ok_or("missing or invalid `numTurns`")?;
+            state.rollback_thread(workspace_id, thread_id, num_turns).await
+        }
+        "list_loaded_threads" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let cursor = parse_optional_string(&params, "cursor");
+            let limit = parse_optional_u32(&params, "limit");
+            state.list_loaded_threads(workspace_id, cursor, limit).await
+        }
         "compact_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -1187,6 +2868,11 @@ async fn handle_rpc_request(
             let turn_id = parse_string(&params, "turnId")?;
             state.turn_interrupt(workspace_id, thread_id, turn_id).await
         }
+        "cancel_app_server_request" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let method = parse_string(&params, "method")?;
+            state.cancel_app_server_request(workspace_id, method).await
+        }
         "start_review" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -1222,6 +2908,10 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.codex_login_cancel(workspace_id).await
         }
+        "codex_logout" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            state.codex_logout(workspace_id).await
+        }
         "skills_list" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.skills_list(workspace_id).await
@@ -1232,6 +2922,25 @@ async fn handle_rpc_request(
             let limit = parse_optional_u32(&params, "limit");
             state.apps_list(workspace_id, cursor, limit).await
         }
+        "get_commit_message_prompt" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let prompt = state.get_commit_message_prompt(workspace_id).await?;
+            Ok(Value::String(prompt))
+        }
+        "generate_commit_message" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let message = state.generate_commit_message(workspace_id).await?;
+            Ok(Value::String(message))
+        }
+        "run_background_prompt" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let prompt = parse_string(&params, "prompt")?;
+            let options = match parse_optional_value(&params, "options").filter(|v| !v.is_null()) {
+                Some(value) => serde_json::from_value(value).map_err(|err| err.to_string())?,
+                None => BackgroundPromptOptions::default(),
+            };
+            state.run_background_prompt(workspace_id, prompt, options).await
+        }
         "respond_to_server_request" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let map = params.as_object().ok_or("missing requestId")?;
@@ -1255,57 +2964,73 @@ async fn handle_rpc_request(
 }
 
 async fn forward_events(
-    mut rx: broadcast::Receiver<DaemonEvent>,
+    event_sink: DaemonEventSink,
+    metrics: Arc<DaemonMetrics>,
+    client: Arc<ClientToken>,
     out_tx_events: mpsc::UnboundedSender<String>,
 ) {
+    let (mut rx, mut last_seq) = event_sink.subscribe();
     loop {
-        let event = match rx.recv().await {
-            Ok(event) => event,
-            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+        let entries = match rx.recv().await {
+            Ok(entry) => vec![entry],
+            // The receiver skipped ahead; catch up from the replay buffer instead of
+            // dropping the deltas in between.
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                metrics.lagged_events.fetch_add(skipped, Ordering::Relaxed);
+                let since = event_sink.events_since(last_seq);
+                if since.truncated {
+                    metrics.lost_event_gaps.fetch_add(1, Ordering::Relaxed);
+                    eprintln!("client fell too far behind; events after seq {last_seq} were lost");
+                }
+                since.events
+            }
             Err(broadcast::error::RecvError::Closed) => break,
         };
 
-        let Some(payload) = build_event_notification(event) else {
-            continue;
-        };
-
-        if out_tx_events.send(payload).is_err() {
-            break;
+        for entry in entries {
+            // Entries still queued in the channel may already have been replayed.
+            if entry.seq <= last_seq {
+                continue;
+            }
+            last_seq = entry.seq;
+            if !client.allows_workspace(entry.event.workspace_id()) {
+                continue;
+            }
+            let Some(payload) = build_event_notification(entry) else {
+                continue;
+            };
+            if out_tx_events.send(payload).is_err() {
+                return;
+            }
         }
     }
 }
 
 async fn handle_client(
-    socket: TcpStream,
+    mut incoming: mpsc::UnboundedReceiver<String>,
+    out_tx: mpsc::UnboundedSender<String>,
     config: Arc<DaemonConfig>,
     state: Arc<DaemonState>,
-    events: broadcast::Sender<DaemonEvent>,
 ) {
-    let (reader, mut writer) = socket.into_split();
-    let mut lines = BufReader::new(reader).lines();
-
-    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
-    let write_task = tokio::spawn(async move {
-        while let Some(message) = out_rx.recv().await {
-            if writer.write_all(message.as_bytes()).await.is_err() {
-                break;
-            }
-            if writer.write_all(b"\n").await.is_err() {
-                break;
-            }
-        }
-    });
-
-    let mut authenticated = config.token.is_none();
+    let _client_guard = ClientGuard::new(&state.metrics);
+    let mut client: Option<Arc<ClientToken>> = if config.tokens.is_empty() {
+        Some(Arc::new(ClientToken::unrestricted("anonymous")))
+    } else {
+        None
+    };
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
 
-    if authenticated {
-        let rx = events.subscribe();
+    if let Some(client) = &client {
         let out_tx_events = out_tx.clone();
-        events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+        events_task = Some(tokio::spawn(forward_events(
+            state.event_sink.clone(),
+            Arc::clone(&state.metrics),
+            Arc::clone(client),
+            out_tx_events,
+        )));
     }
 
-    while let Ok(Some(line)) = lines.next_line().await {
+    while let Some(line) = incoming.recv().await {
         let line = line.trim();
         if line.is_empty() {
             continue;
@@ -1324,7 +3049,7 @@ async fn handle_client(
             .to_string();
         let params = message.get("params").cloned().unwrap_or(Value::Null);
 
-        if !authenticated {
+        let Some(authorized) = client.as_ref() else {
             if method != "auth" {
                 if let Some(response) = build_error_response(id, "unauthorized") {
                     let _ = out_tx.send(response);
@@ -1332,29 +3057,59 @@ async fn handle_client(
                 continue;
             }
 
-            let expected = config.token.clone().unwrap_or_default();
             let provided = parse_auth_token(&params).unwrap_or_default();
-            if expected != provided {
+            let Some(matched) = find_client_token(&config.tokens, &provided) else {
                 if let Some(response) = build_error_response(id, "invalid token") {
                     let _ = out_tx.send(response);
                 }
                 continue;
-            }
-
-            authenticated = true;
+            };
+            let matched = Arc::new(matched.clone());
+            audit_call(&matched, &method, None, "ok");
             if let Some(response) = build_result_response(id, json!({ "ok": true })) {
                 let _ = out_tx.send(response);
             }
 
-            let rx = events.subscribe();
             let out_tx_events = out_tx.clone();
-            events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+            events_task = Some(tokio::spawn(forward_events(
+                state.event_sink.clone(),
+                Arc::clone(&state.metrics),
+                Arc::clone(&matched),
+                out_tx_events,
+            )));
+            client = Some(matched);
+
+            continue;
+        };
+
+        if method == "auth" && config.tokens.is_empty() {
+            // Clients configured with a token still send `auth` to daemons that
+            // don't require one (e.g. a local Unix socket).
+            if let Some(response) = build_result_response(id, json!({ "ok": true })) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
 
+        let workspace_id = request_workspace_id(&method, &params);
+        if let Err(message) = authorized.authorize(&method, &params) {
+            audit_call(authorized, &method, workspace_id.as_deref(), "denied");
+            if let Some(response) = build_error_response(id, &message) {
+                let _ = out_tx.send(response);
+            }
             continue;
         }
 
         let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
+        let result = handle_rpc_request(&state, authorized, &method, params, client_version).await;
+        if !config.tokens.is_empty() {
+            audit_call(
+                authorized,
+                &method,
+                workspace_id.as_deref(),
+                if result.is_ok() { "ok" } else { "error" },
+            );
+        }
         let response = match result {
             Ok(result) => build_result_response(id, result),
             Err(message) => build_error_response(id, &message),
@@ -1368,9 +3123,421 @@ async fn handle_client(
     if let Some(task) = events_task {
         task.abort();
     }
+}
+
+async fn handle_tcp_client<S>(socket: S, config: Arc<DaemonConfig>, state: Arc<DaemonState>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(socket);
+
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+    let write_task = tokio::spawn(async move {
+        while let Some(message) = out_rx.recv().await {
+            if writer.write_all(message.as_bytes()).await.is_err() {
+                break;
+            }
+            if writer.write_all(b"\n").await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let (in_tx, in_rx) = mpsc::unbounded_channel::<String>();
+    let read_task = tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if in_tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    handle_client(in_rx, out_tx, config, state).await;
+    read_task.abort();
+    write_task.abort();
+}
+
+async fn handle_ws_client<S>(socket: S, config: Arc<DaemonConfig>, state: Arc<DaemonState>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let stream = match tokio_tungstenite::accept_async(socket).await {
+        Ok(stream) => stream,
+        Err(err) => {
+            eprintln!("websocket handshake failed: {err}");
+            return;
+        }
+    };
+    let (mut sink, mut source) = stream.split();
+
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+    let write_task = tokio::spawn(async move {
+        while let Some(message) = out_rx.recv().await {
+            if sink.send(Message::Text(message)).await.is_err() {
+                break;
+            }
+        }
+        let _ = sink.close().await;
+    });
+
+    // One JSON-RPC envelope per text frame; control frames are answered by tungstenite.
+    let (in_tx, in_rx) = mpsc::unbounded_channel::<String>();
+    let read_task = tokio::spawn(async move {
+        while let Some(Ok(message)) = source.next().await {
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Binary(bytes) => match String::from_utf8(bytes) {
+                    Ok(text) => text,
+                    Err(_) => continue,
+                },
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            if in_tx.send(text).is_err() {
+                break;
+            }
+        }
+    });
+
+    handle_client(in_rx, out_tx, config, state).await;
+    read_task.abort();
     write_task.abort();
 }
 
+#[derive(Clone, Copy)]
+enum ListenerKind {
+    Tcp,
+    WebSocket,
+}
+
+async fn handle_connection<S>(
+    kind: ListenerKind,
+    socket: S,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    match kind {
+        ListenerKind::Tcp => handle_tcp_client(socket, config, state).await,
+        ListenerKind::WebSocket => handle_ws_client(socket, config, state).await,
+    }
+}
+
+async fn serve_listener(
+    listener: TcpListener,
+    kind: ListenerKind,
+    tls: Option<TlsAcceptor>,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _addr)) => socket,
+            Err(_) => continue,
+        };
+        let tls = tls.clone();
+        let config = Arc::clone(&config);
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            match tls {
+                Some(acceptor) => match acceptor.accept(socket).await {
+                    Ok(stream) => handle_connection(kind, stream, config, state).await,
+                    Err(err) => eprintln!("TLS handshake failed: {err}"),
+                },
+                None => handle_connection(kind, socket, config, state).await,
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn serve_unix_listener(
+    listener: UnixListener,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _addr)) => socket,
+            Err(_) => continue,
+        };
+        let config = Arc::clone(&config);
+        let state = Arc::clone(&state);
+        tokio::spawn(handle_connection(ListenerKind::Tcp, socket, config, state));
+    }
+}
+
+/// Binds a Unix socket that only the current user can connect to, replacing a
+/// stale socket file left behind by a previous run.
+#[cfg(unix)]
+fn bind_unix_listener(path: &Path) -> Result<UnixListener, String> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(format!("{} exists and is not a socket", path.display()));
+        }
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(format!("{} is already in use", path.display()));
+        }
+        std::fs::remove_file(path)
+            .map_err(|err| format!("Failed to remove stale {}: {err}", path.display()))?;
+    }
+
+    // Bind under a restrictive umask so the socket is never briefly reachable by
+    // other users before its mode is set.
+    let previous_umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(previous_umask) };
+    let listener = listener.map_err(|err| err.to_string())?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|err| format!("Failed to set permissions on {}: {err}", path.display()))?;
+    Ok(listener)
+}
+
+const METRICS_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Minimal HTTP/1.1 responder for Prometheus scrapes. When tokens are configured
+/// scrapes must send one as `Authorization: Bearer <token>`; it travels in plain
+/// HTTP, so still bind this to a trusted interface.
+async fn serve_metrics(listener: TcpListener, state: Arc<DaemonState>, config: Arc<DaemonConfig>) {
+    loop {
+        let socket = match listener.accept().await {
+            Ok((socket, _addr)) => socket,
+            Err(_) => continue,
+        };
+        let state = Arc::clone(&state);
+        let config = Arc::clone(&config);
+        tokio::spawn(async move {
+            let response = respond_metrics(socket, state, config);
+            let _ = tokio::time::timeout(METRICS_REQUEST_TIMEOUT, response).await;
+        });
+    }
+}
+
+/// Scrapes need an unscoped token once tokens are configured, since the metrics
+/// cover every workspace.
+fn metrics_authorized(tokens: &[ClientToken], authorization: Option<&str>) -> bool {
+    if tokens.is_empty() {
+        return true;
+    }
+    authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|provided| find_client_token(tokens, provided.trim()))
+        .is_some_and(|entry| entry.workspaces.is_none())
+}
+
+async fn respond_metrics(
+    socket: tokio::net::TcpStream,
+    state: Arc<DaemonState>,
+    config: Arc<DaemonConfig>,
+) {
+    let (reader, mut writer) = tokio::io::split(socket);
+    let mut reader = BufReader::new(reader);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).await.is_err() {
+        return;
+    }
+    // Drain the headers, keeping `Authorization`; the request body (if any) is ignored.
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("authorization") {
+                        authorization = Some(value.trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let authorized = metrics_authorized(&config.tokens, authorization.as_deref());
+    let (status, body) = match (method, path) {
+        ("GET", "/metrics") if !authorized => ("401 Unauthorized", "unauthorized\n".to_string()),
+        ("GET", "/metrics") => ("200 OK", render_prometheus(&state.daemon_status().await)),
+        ("GET", _) => ("404 Not Found", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = writer.write_all(response.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn render_prometheus(status: &DaemonStatus) -> String {
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, u64)>| {
+        out.push_str(&format!("# HELP codex_monitor_daemon_{name} {help}\n"));
+        out.push_str(&format!("# TYPE codex_monitor_daemon_{name} {kind}\n"));
+        for (labels, value) in samples {
+            out.push_str(&format!("codex_monitor_daemon_{name}{labels} {value}\n"));
+        }
+    };
+    let single = |value: u64| vec![(String::new(), value)];
+    let per_workspace = |value: fn(&WorkspaceStatus) -> u64| {
+        status
+            .workspaces
+            .iter()
+            .map(|workspace| {
+                (
+                    format!(
+                        "{{workspace_id=\"{}\",workspace_name=\"{}\"}}",
+                        escape_label_value(&workspace.id),
+                        escape_label_value(&workspace.name)
+                    ),
+                    value(workspace),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let totals: [(&str, &str, &str, u64); 8] = [
+        (
+            "uptime_seconds",
+            "gauge",
+            "Seconds since start.",
+            status.uptime_secs,
+        ),
+        (
+            "clients_connected",
+            "gauge",
+            "Connected clients.",
+            status.clients.connected as u64,
+        ),
+        (
+            "connections_total",
+            "counter",
+            "Accepted connections.",
+            status.clients.total,
+        ),
+        (
+            "events_latest_seq",
+            "counter",
+            "Latest event seq.",
+            status.events.latest_seq,
+        ),
+        (
+            "events_buffered",
+            "gauge",
+            "Replay buffer size.",
+            status.events.buffered as u64,
+        ),
+        (
+            "events_lagged_total",
+            "counter",
+            "Events replayed to lagging clients.",
+            status.events.lagged,
+        ),
+        (
+            "events_lost_gaps_total",
+            "counter",
+            "Catch-ups that outran the buffer.",
+            status.events.lost_gaps,
+        ),
+        (
+            "terminal_sessions",
+            "gauge",
+            "Open terminal sessions.",
+            status.terminal_sessions as u64,
+        ),
+    ];
+    for (name, kind, help, value) in totals {
+        metric(name, kind, help, single(value));
+    }
+    metric(
+        "workspace_running",
+        "gauge",
+        "Whether the workspace's codex app-server is running.",
+        per_workspace(|workspace| u64::from(workspace.state == "running")),
+    );
+    metric(
+        "workspace_pending_requests",
+        "gauge",
+        "Requests awaiting a response from the app-server.",
+        per_workspace(|workspace| workspace.pending_requests as u64),
+    );
+    metric(
+        "workspace_background_threads",
+        "gauge",
+        "Hidden background threads in flight.",
+        per_workspace(|workspace| workspace.background_threads as u64),
+    );
+    out
+}
+
+/// Loads the PEM certificate chain and key, returning the acceptor along with the
+/// SHA-256 fingerprint of the leaf certificate so it can be pinned by clients.
+fn load_tls_acceptor(cert_path: &Path, key_path: &Path) -> Result<(TlsAcceptor, String), String> {
+    let cert_file = File::open(cert_path)
+        .map_err(|err| format!("Failed to open {}: {err}", cert_path.display()))?;
+    let certs = rustls_pemfile::certs(&mut std::io::BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to parse {}: {err}", cert_path.display()))?;
+    let leaf = certs
+        .first()
+        .ok_or_else(|| format!("No certificates found in {}", cert_path.display()))?;
+    let fingerprint = Sha256::digest(leaf.as_ref())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    let key_file = File::open(key_path)
+        .map_err(|err| format!("Failed to open {}: {err}", key_path.display()))?;
+    let key = rustls_pemfile::private_key(&mut std::io::BufReader::new(key_file))
+        .map_err(|err| format!("Failed to parse {}: {err}", key_path.display()))?
+        .ok_or_else(|| format!("No private key found in {}", key_path.display()))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let server_config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| format!("Invalid TLS certificate/key: {err}"))?;
+    Ok((TlsAcceptor::from(Arc::new(server_config)), fingerprint))
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
 fn main() {
     let config = match parse_args() {
         Ok(config) => config,
@@ -1380,44 +3547,302 @@ fn main() {
         }
     };
 
+    let tls = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => match load_tls_acceptor(cert, key) {
+            Ok((acceptor, fingerprint)) => {
+                eprintln!(
+                    "codex-monitor-daemon TLS certificate SHA-256 fingerprint: {fingerprint}"
+                );
+                Some(acceptor)
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+
     let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
         .expect("failed to build tokio runtime");
 
     runtime.block_on(async move {
-        let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
-        let event_sink = DaemonEventSink {
-            tx: events_tx.clone(),
-        };
+        let (events_tx, _events_rx) = broadcast::channel::<SequencedEvent>(2048);
+        let event_sink = DaemonEventSink::new(events_tx);
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
 
-        let listener = TcpListener::bind(config.listen)
-            .await
-            .unwrap_or_else(|err| panic!("failed to bind {}: {err}", config.listen));
-        eprintln!(
-            "codex-monitor-daemon listening on {} (data dir: {})",
-            config.listen,
-            state
-                .storage_path
-                .parent()
-                .unwrap_or(&state.storage_path)
-                .display()
-        );
+        let data_dir = state
+            .storage_path
+            .parent()
+            .unwrap_or(&state.storage_path)
+            .display()
+            .to_string();
+
+        if let Some(metrics_listen) = config.metrics_listen {
+            let metrics_listener = TcpListener::bind(metrics_listen)
+                .await
+                .unwrap_or_else(|err| panic!("failed to bind {metrics_listen}: {err}"));
+            eprintln!("codex-monitor-daemon serving metrics on http://{metrics_listen}/metrics");
+            tokio::spawn(serve_metrics(
+                metrics_listener,
+                Arc::clone(&state),
+                Arc::clone(&config),
+            ));
+        }
 
-        loop {
-            match listener.accept().await {
-                Ok((socket, _addr)) => {
-                    let config = Arc::clone(&config);
-                    let state = Arc::clone(&state);
-                    let events = events_tx.clone();
-                    tokio::spawn(async move {
-                        handle_client(socket, config, state, events).await;
-                    });
+        if let Some(listen_ws) = config.listen_ws {
+            let ws_listener = TcpListener::bind(listen_ws)
+                .await
+                .unwrap_or_else(|err| panic!("failed to bind {listen_ws}: {err}"));
+            eprintln!("codex-monitor-daemon accepting WebSocket clients on {listen_ws}");
+            tokio::spawn(serve_listener(
+                ws_listener,
+                ListenerKind::WebSocket,
+                tls.clone(),
+                Arc::clone(&config),
+                Arc::clone(&state),
+            ));
+        }
+
+        let restore_state = Arc::clone(&state);
+        tokio::spawn(async move { restore_state.restore_sessions().await });
+
+        let shutdown_state = Arc::clone(&state);
+        #[cfg(unix)]
+        let socket_path = match &config.listen {
+            ListenAddr::Unix(path) => Some(path.clone()),
+            ListenAddr::Tcp(_) => None,
+        };
+        let serve = async move {
+            match config.listen.clone() {
+                ListenAddr::Tcp(addr) => {
+                    let listener = TcpListener::bind(addr)
+                        .await
+                        .unwrap_or_else(|err| panic!("failed to bind {addr}: {err}"));
+                    eprintln!("codex-monitor-daemon listening on {addr} (data dir: {data_dir})");
+                    serve_listener(listener, ListenerKind::Tcp, tls, config, state).await;
                 }
-                Err(_) => continue,
+                #[cfg(unix)]
+                ListenAddr::Unix(path) => {
+                    let listener = bind_unix_listener(&path)
+                        .unwrap_or_else(|err| panic!("failed to bind {}: {err}", path.display()));
+                    eprintln!(
+                        "codex-monitor-daemon listening on {} (data dir: {data_dir})",
+                        config.listen
+                    );
+                    if tls.is_some() {
+                        eprintln!("TLS is not used on the Unix socket listener");
+                    }
+                    serve_unix_listener(listener, config, state).await;
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = serve => {}
+            _ = shutdown_signal() => {
+                eprintln!("codex-monitor-daemon shutting down");
+                shutdown_state.shutdown().await;
             }
         }
+        #[cfg(unix)]
+        if let Some(path) = socket_path {
+            let _ = std::fs::remove_file(path);
+        }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terminal_exit(terminal_id: &str) -> TerminalExit {
+        TerminalExit {
+            workspace_id: "ws".to_string(),
+            terminal_id: terminal_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn events_since_returns_events_after_seq() {
+        let (tx, _rx) = broadcast::channel(16);
+        let sink = DaemonEventSink::new(tx);
+        sink.emit_terminal_exit(terminal_exit("a"));
+        sink.emit_terminal_exit(terminal_exit("b"));
+        sink.emit_terminal_exit(terminal_exit("c"));
+
+        let since = sink.events_since(1);
+        let seqs: Vec<u64> = since.events.iter().map(|entry| entry.seq).collect();
+        assert_eq!(seqs, vec![2, 3]);
+        assert_eq!(since.latest_seq, 3);
+        assert!(!since.truncated);
+
+        let cursor = sink.events_since(u64::MAX);
+        assert!(cursor.events.is_empty());
+        assert_eq!(cursor.latest_seq, 3);
+    }
+
+    #[test]
+    fn events_since_reports_evicted_events() {
+        let (tx, _rx) = broadcast::channel(16);
+        let sink = DaemonEventSink::new(tx);
+        for _ in 0..EVENT_BUFFER_CAPACITY + 5 {
+            sink.emit_terminal_exit(terminal_exit("a"));
+        }
+
+        let since = sink.events_since(0);
+        assert!(since.truncated);
+        assert_eq!(since.events.len(), EVENT_BUFFER_CAPACITY);
+        assert_eq!(since.events.first().map(|entry| entry.seq), Some(6));
+        assert!(!sink.events_since(5).truncated);
+    }
+
+    fn scoped_token(name: &str, token: &str, workspaces: &[&str], read_only: bool) -> ClientToken {
+        ClientToken {
+            name: name.to_string(),
+            token: token.to_string(),
+            workspaces: Some(workspaces.iter().map(|id| id.to_string()).collect()),
+            read_only,
+        }
+    }
+
+    #[test]
+    fn find_client_token_matches_exact_value() {
+        let tokens = vec![
+            scoped_token("alice", "alice-secret", &["ws-1"], false),
+            scoped_token("bob", "bob-secret", &["ws-2"], true),
+        ];
+        let matched = find_client_token(&tokens, "bob-secret").map(|entry| entry.name.as_str());
+        assert_eq!(matched, Some("bob"));
+        assert!(find_client_token(&tokens, "bob-secre").is_none());
+        assert!(find_client_token(&tokens, "").is_none());
+    }
+
+    #[test]
+    fn metrics_require_an_unscoped_token_once_tokens_are_set() {
+        assert!(metrics_authorized(&[], None));
+        let tokens = vec![
+            ClientToken {
+                token: "admin-secret".to_string(),
+                ..ClientToken::unrestricted("admin")
+            },
+            scoped_token("alice", "alice-secret", &["ws-1"], false),
+        ];
+        assert!(metrics_authorized(&tokens, Some("Bearer admin-secret")));
+        assert!(!metrics_authorized(&tokens, Some("Bearer alice-secret")));
+        assert!(!metrics_authorized(&tokens, Some("Bearer wrong")));
+        assert!(!metrics_authorized(&tokens, Some("admin-secret")));
+        assert!(!metrics_authorized(&tokens, None));
+    }
+
+    #[test]
+    fn read_only_tokens_cannot_drive_sessions() {
+        let token = ClientToken {
+            read_only: true,
+            ..ClientToken::unrestricted("observer")
+        };
+        let params = json!({ "workspaceId": "ws-1", "threadId": "t", "text": "hi" });
+        assert!(token.authorize("send_user_message", &params).is_err());
+        assert!(token.authorize("connect_workspace", &params).is_err());
+        assert!(token.authorize("resume_thread", &params).is_err());
+        assert!(token
+            .authorize("remove_workspace", &json!({ "id": "ws-1" }))
+            .is_err());
+        let write = json!({ "scope": "workspace", "workspaceId": "ws-1" });
+        assert!(token.authorize("file_write", &write).is_err());
+        assert!(token.authorize("list_threads", &params).is_ok());
+        let config = json!({ "scope": "global", "kind": "config" });
+        assert!(token.authorize("file_read", &config).is_err());
+        let agents = json!({ "scope": "global", "kind": "agents" });
+        assert!(token.authorize("file_read", &agents).is_ok());
+    }
+
+    #[test]
+    fn scoped_tokens_only_reach_their_workspaces() {
+        let token = scoped_token("alice", "secret", &["ws-1"], false);
+        assert!(token
+            .authorize("get_git_status", &json!({ "workspaceId": "ws-1" }))
+            .is_ok());
+        assert!(token
+            .authorize("get_git_status", &json!({ "workspaceId": "ws-2" }))
+            .is_err());
+        assert!(token
+            .authorize("remove_workspace", &json!({ "id": "ws-2" }))
+            .is_err());
+        assert!(token
+            .authorize("file_read", &json!({ "scope": "global", "kind": "agents" }))
+            .is_err());
+        assert!(token
+            .authorize("add_workspace", &json!({ "path": "/tmp" }))
+            .is_err());
+        assert!(token.authorize("list_workspaces", &Value::Null).is_ok());
+    }
+
+    #[test]
+    fn session_ids_to_persist_keeps_pending_restores() {
+        let connected = vec!["ws-2".to_string(), "ws-1".to_string()];
+        let pending = vec!["ws-3".to_string(), "ws-1".to_string()];
+        assert_eq!(
+            session_ids_to_persist(connected, &pending),
+            vec!["ws-1", "ws-2", "ws-3"]
+        );
+        assert!(session_ids_to_persist(Vec::new(), &[]).is_empty());
+    }
+
+    #[test]
+    fn parse_listen_addr_accepts_tcp_and_unix() {
+        assert!(matches!(
+            parse_listen_addr("127.0.0.1:4732"),
+            Ok(ListenAddr::Tcp(_))
+        ));
+        #[cfg(unix)]
+        {
+            let listen = parse_listen_addr("unix:/tmp/codex-monitor.sock").unwrap();
+            assert!(listen.is_unix());
+            assert_eq!(listen.to_string(), "unix:/tmp/codex-monitor.sock");
+            assert!(parse_listen_addr("unix:").is_err());
+        }
+        assert!(parse_listen_addr("not-an-addr").is_err());
+    }
+
+    #[test]
+    fn render_prometheus_labels_workspaces() {
+        let status = DaemonStatus {
+            version: "0.0.0",
+            pid: 1,
+            uptime_secs: 42,
+            clients: ClientStats {
+                connected: 2,
+                total: 5,
+            },
+            events: EventStats {
+                latest_seq: 10,
+                buffered: 10,
+                subscribers: 2,
+                lagged: 3,
+                lost_gaps: 0,
+            },
+            terminal_sessions: 0,
+            workspaces: vec![WorkspaceStatus {
+                id: "ws-1".to_string(),
+                name: "my \"repo\"".to_string(),
+                state: "running",
+                pid: Some(1234),
+                exit_code: None,
+                pending_requests: 4,
+                background_threads: 0,
+            }],
+        };
+
+        let text = render_prometheus(&status);
+        assert!(text.contains("codex_monitor_daemon_uptime_seconds 42\n"));
+        assert!(text.contains("# TYPE codex_monitor_daemon_connections_total counter\n"));
+        assert!(text.contains(
+            "codex_monitor_daemon_workspace_pending_requests{workspace_id=\"ws-1\",workspace_name=\"my \\\"repo\\\"\"} 4\n"
+        ));
+        assert!(text.contains("codex_monitor_daemon_workspace_running{workspace_id=\"ws-1\""));
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol::{
+    ApprovalPolicy, ErrorDetail, Item, SandboxPolicy, ServerNotification, ThreadArchiveParams,
+    ThreadStartParams, TurnStartParams, UserInput,
+};
+
+pub(crate) const DEFAULT_BACKGROUND_PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A one-shot prompt run in a hidden helper thread.
+#[derive(Debug, Clone)]
+pub(crate) struct BackgroundPrompt {
+    pub(crate) prompt: String,
+    pub(crate) timeout: Duration,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    pub(crate) sandbox_policy: SandboxPolicy,
+}
+
+impl BackgroundPrompt {
+    pub(crate) fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            timeout: DEFAULT_BACKGROUND_PROMPT_TIMEOUT,
+            model: None,
+            effort: None,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+        }
+    }
+}
+
+/// Caller-facing knobs for `run_background_prompt`, as sent by the frontend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct BackgroundPromptOptions {
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    /// `read-only` (the default), `current` or `full-access`.
+    pub(crate) access_mode: Option<String>,
+    pub(crate) timeout_secs: Option<u64>,
+    /// Parse the reply as a JSON object instead of returning only text.
+    pub(crate) expect_json: bool,
+}
+
+impl BackgroundPromptOptions {
+    pub(crate) fn into_prompt(self, prompt: String, workspace_path: &str) -> BackgroundPrompt {
+        let mut request = BackgroundPrompt::new(prompt);
+        if let Some(secs) = self.timeout_secs.filter(|secs| *secs > 0) {
+            request.timeout = Duration::from_secs(secs);
+        }
+        if let Some(access_mode) = self.access_mode.as_deref() {
+            request.sandbox_policy = SandboxPolicy::for_access_mode(access_mode, workspace_path);
+        }
+        request.model = self.model;
+        request.effort = self.effort;
+        request
+    }
+}
+
+/// Runs `request` in a new thread that is hidden from the sidebar and never
+/// asks for approval, and returns the trimmed agent reply. The thread is
+/// archived afterwards, whether the turn succeeded or not.
+pub(crate) async fn run_background_prompt<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    request: BackgroundPrompt,
+) -> Result<String, String> {
+    let thread = session
+        .call(&ThreadStartParams {
+            cwd: session.entry.path.clone(),
+            approval_policy: ApprovalPolicy::Never,
+        })
+        .await?;
+    let thread_id = thread.thread_id()?.to_string();
+
+    // Hide background helper threads from the sidebar, even if a thread/started event leaked.
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: session.entry.id.clone(),
+        message: json!({
+            "method": "codex/backgroundThread",
+            "params": {
+                "threadId": thread_id,
+                "action": "hide"
+            }
+        }),
+    });
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    {
+        let mut callbacks = session.background_thread_callbacks.lock().await;
+        callbacks.insert(thread_id.clone(), tx);
+    }
+
+    let limit = request.timeout;
+    let turn = session
+        .call(&TurnStartParams {
+            thread_id: thread_id.clone(),
+            input: vec![UserInput::Text {
+                text: request.prompt,
+            }],
+            cwd: session.entry.path.clone(),
+            approval_policy: ApprovalPolicy::Never,
+            sandbox_policy: request.sandbox_policy,
+            model: request.model,
+            effort: request.effort,
+            collaboration_mode: None,
+        })
+        .await;
+    let result = match turn {
+        Ok(_) => {
+            let collect = async {
+                let mut text = String::new();
+                while let Some(event) = rx.recv().await {
+                    if collect_agent_text(&mut text, &event)? {
+                        break;
+                    }
+                }
+                Ok(text.trim().to_string())
+            };
+            match timeout(limit, collect).await {
+                Ok(result) => result,
+                Err(_) => Err(format!(
+                    "Timed out waiting for the background prompt after {}s",
+                    limit.as_secs()
+                )),
+            }
+        }
+        Err(error) => Err(error),
+    };
+
+    {
+        let mut callbacks = session.background_thread_callbacks.lock().await;
+        callbacks.remove(&thread_id);
+    }
+    let _ = session.send(&ThreadArchiveParams { thread_id }).await;
+
+    result
+}
+
+/// Appends the agent text carried by `event` to `text`. Returns `Ok(true)`
+/// once the turn has completed and `Err` if it failed for good.
+fn collect_agent_text(text: &mut String, event: &Value) -> Result<bool, String> {
+    match ServerNotification::decode(event)? {
+        Some(ServerNotification::AgentMessageDelta(params)) => text.push_str(&params.delta),
+        // Builds that skip deltas only send the finished message
+        Some(ServerNotification::ItemCompleted(params)) if text.is_empty() => {
+            if let Some(message) = agent_message_text(&params.item) {
+                text.push_str(message);
+            }
+        }
+        Some(ServerNotification::TurnCompleted(_)) => return Ok(true),
+        Some(ServerNotification::TurnError(params)) if !params.will_retry => {
+            let message = params
+                .error
+                .as_ref()
+                .map(ErrorDetail::message)
+                .unwrap_or("Unknown error while running the background prompt");
+            return Err(message.to_string());
+        }
+        // Ignore other events (turn/started, item/started, reasoning events, etc.)
+        _ => {}
+    }
+    Ok(false)
+}
+
+fn agent_message_text(item: &Item) -> Option<&str> {
+    if item.kind != "agentMessage" {
+        return None;
+    }
+    item.fields.get("text").and_then(Value::as_str)
+}
+
+/// Extracts the outermost `{...}` object from a model reply that may wrap it
+/// in prose or a code fence.
+pub(crate) fn extract_json_value(raw: &str) -> Option<Value> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str::<Value>(&raw[start..=end]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_agent_text_joins_deltas_until_turn_completes() {
+        let mut text = String::new();
+        let delta = |delta: &str| json!({ "method": "item/agentMessage/delta", "params": { "delta": delta } });
+        assert_eq!(collect_agent_text(&mut text, &delta("Fix ")), Ok(false));
+        assert_eq!(collect_agent_text(&mut text, &delta("typo")), Ok(false));
+        let completed_item = json!({
+            "method": "item/completed",
+            "params": { "item": { "type": "agentMessage", "text": "Fix typo" } }
+        });
+        assert_eq!(collect_agent_text(&mut text, &completed_item), Ok(false));
+        let completed = json!({ "method": "turn/completed", "params": { "threadId": "t" } });
+        assert_eq!(collect_agent_text(&mut text, &completed), Ok(true));
+        assert_eq!(text, "Fix typo");
+    }
+
+    #[test]
+    fn collect_agent_text_falls_back_to_completed_item_and_reports_errors() {
+        let mut text = String::new();
+        let completed_item = json!({
+            "method": "item/completed",
+            "params": { "item": { "type": "agentMessage", "text": "{\"title\":\"x\"}" } }
+        });
+        assert_eq!(collect_agent_text(&mut text, &completed_item), Ok(false));
+        assert_eq!(text, "{\"title\":\"x\"}");
+
+        let retrying = json!({
+            "method": "error",
+            "params": { "error": { "message": "overloaded" }, "willRetry": true }
+        });
+        assert_eq!(collect_agent_text(&mut text, &retrying), Ok(false));
+        let failed = json!({
+            "method": "error",
+            "params": { "error": { "message": "overloaded" }, "willRetry": false }
+        });
+        assert_eq!(
+            collect_agent_text(&mut text, &failed),
+            Err("overloaded".to_string())
+        );
+    }
+
+    #[test]
+    fn options_default_to_read_only_with_default_timeout() {
+        let prompt = BackgroundPromptOptions::default().into_prompt("hi".to_string(), "/repo");
+        assert_eq!(prompt.sandbox_policy, SandboxPolicy::ReadOnly);
+        assert_eq!(prompt.timeout, DEFAULT_BACKGROUND_PROMPT_TIMEOUT);
+
+        let options: BackgroundPromptOptions =
+            serde_json::from_value(json!({ "accessMode": "current", "timeoutSecs": 5 })).unwrap();
+        let prompt = options.into_prompt("hi".to_string(), "/repo");
+        assert_eq!(prompt.timeout, Duration::from_secs(5));
+        assert_eq!(
+            prompt.sandbox_policy,
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec!["/repo".to_string()],
+                network_access: true,
+            }
+        );
+    }
+
+    #[test]
+    fn extract_json_value_ignores_surrounding_prose() {
+        let raw = "Sure:\n```json\n{\"title\":\"Add Tests\"}\n```";
+        assert_eq!(
+            extract_json_value(raw),
+            Some(json!({ "title": "Add Tests" }))
+        );
+        assert_eq!(extract_json_value("no json here"), None);
+    }
+}
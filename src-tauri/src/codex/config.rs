@@ -1,7 +1,10 @@
 use std::path::{Path, PathBuf};
 
-use toml::Value as TomlValue;
+use serde_json::Value;
+use toml::{Table as TomlTable, Value as TomlValue};
+use toml_edit::{DocumentMut, Item as TomlItem, TableLike};
 
+use crate::backend::protocol::{ConfigEdit, MergeStrategy};
 use crate::files::io::read_text_file_within;
 use crate::files::ops::write_with_policy;
 use crate::files::policy::{policy_for, FileKind, FileScope};
@@ -39,52 +42,6 @@ pub(crate) fn read_personality() -> Result<Option<String>, String> {
         .map(|value| value.to_string()))
 }
 
-pub(crate) fn write_steer_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("steer", enabled)
-}
-
-pub(crate) fn write_collab_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("collab", enabled)
-}
-
-pub(crate) fn write_collaboration_modes_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("collaboration_modes", enabled)
-}
-
-pub(crate) fn write_unified_exec_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("unified_exec", enabled)
-}
-
-pub(crate) fn write_apps_enabled(enabled: bool) -> Result<(), String> {
-    write_feature_flag("apps", enabled)
-}
-
-pub(crate) fn write_personality(personality: &str) -> Result<(), String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(());
-    };
-    let policy = config_policy()?;
-    let response = read_text_file_within(
-        &root,
-        policy.filename,
-        policy.root_may_be_missing,
-        policy.root_context,
-        policy.filename,
-        policy.allow_external_symlink_target,
-    )?;
-    let contents = if response.exists {
-        response.content
-    } else {
-        String::new()
-    };
-    let normalized = normalize_personality_value(personality);
-    let updated = match normalized {
-        Some(value) => upsert_top_level_string_key(&contents, "personality", value),
-        None => remove_top_level_key(&contents, "personality"),
-    };
-    write_with_policy(&root, policy, &updated)
-}
-
 fn read_feature_flag(key: &str) -> Result<Option<bool>, String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(None);
@@ -93,26 +50,35 @@ fn read_feature_flag(key: &str) -> Result<Option<bool>, String> {
     Ok(contents.as_deref().and_then(|value| find_feature_flag(value, key)))
 }
 
-fn write_feature_flag(key: &str, enabled: bool) -> Result<(), String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(());
-    };
-    let policy = config_policy()?;
-    let response = read_text_file_within(
-        &root,
-        policy.filename,
-        policy.root_may_be_missing,
-        policy.root_context,
-        policy.filename,
-        policy.allow_external_symlink_target,
-    )?;
-    let contents = if response.exists {
-        response.content
-    } else {
-        String::new()
-    };
-    let updated = upsert_feature_flag(&contents, key, enabled);
-    write_with_policy(&root, policy, &updated)
+/// Reads `config.toml` from `codex_home` (or the default CODEX_HOME) as JSON,
+/// for when no app-server is running to answer `config/read`.
+pub(crate) fn read_config_json(codex_home: Option<PathBuf>) -> Result<Value, String> {
+    let root = codex_home
+        .or_else(resolve_default_codex_home)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    let contents = read_config_contents_from_root(&root)?.unwrap_or_default();
+    let table: TomlTable =
+        toml::from_str(&contents).map_err(|err| format!("Invalid config.toml: {err}"))?;
+    serde_json::to_value(table).map_err(|err| err.to_string())
+}
+
+/// Applies `edits` to `config.toml` directly, for when no app-server is
+/// running to validate them. Untouched keys keep their comments and layout.
+pub(crate) fn write_config_edits(
+    codex_home: Option<PathBuf>,
+    edits: &[ConfigEdit],
+) -> Result<(), String> {
+    let root = codex_home
+        .or_else(resolve_default_codex_home)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    let contents = read_config_contents_from_root(&root)?.unwrap_or_default();
+    let mut document: DocumentMut = contents
+        .parse()
+        .map_err(|err| format!("Invalid config.toml: {err}"))?;
+    for edit in edits {
+        apply_config_edit(&mut document, edit)?;
+    }
+    write_with_policy(&root, config_policy()?, &document.to_string())
 }
 
 pub(crate) fn config_toml_path() -> Option<PathBuf> {
@@ -182,6 +148,100 @@ fn normalize_personality_value(value: &str) -> Option<&'static str> {
     }
 }
 
+fn apply_config_edit(document: &mut DocumentMut, edit: &ConfigEdit) -> Result<(), String> {
+    let segments: Vec<&str> = edit.key_path.split('.').map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(format!("Invalid config key path: {}", edit.key_path));
+    }
+    let (key, parents) = segments
+        .split_last()
+        .ok_or_else(|| format!("Invalid config key path: {}", edit.key_path))?;
+    let mut current: &mut dyn TableLike = document.as_table_mut();
+    for segment in parents {
+        if current.get(segment).is_none() {
+            let mut table = toml_edit::Table::new();
+            table.set_implicit(true);
+            current.insert(segment, TomlItem::Table(table));
+        }
+        current = current
+            .get_mut(segment)
+            .and_then(TomlItem::as_table_like_mut)
+            .ok_or_else(|| format!("`{segment}` in {} is not a table", edit.key_path))?;
+    }
+    if edit.value.is_null() {
+        current.remove(key);
+        return Ok(());
+    }
+    let value = TomlValue::try_from(&edit.value)
+        .map_err(|err| format!("Invalid value for {}: {err}", edit.key_path))?;
+    match value {
+        TomlValue::Table(incoming)
+            if edit.merge_strategy == MergeStrategy::Upsert
+                && current.get(key).is_some_and(TomlItem::is_table_like) =>
+        {
+            if let Some(existing) = current.get_mut(key).and_then(TomlItem::as_table_like_mut) {
+                merge_tables(existing, incoming);
+            }
+        }
+        value => {
+            current.insert(key, toml_item(value));
+        }
+    }
+    Ok(())
+}
+
+fn merge_tables(existing: &mut dyn TableLike, incoming: TomlTable) {
+    for (key, value) in incoming {
+        match value {
+            TomlValue::Table(incoming)
+                if existing.get(&key).is_some_and(TomlItem::is_table_like) =>
+            {
+                if let Some(current) = existing.get_mut(&key).and_then(TomlItem::as_table_like_mut)
+                {
+                    merge_tables(current, incoming);
+                }
+            }
+            value => {
+                existing.insert(&key, toml_item(value));
+            }
+        }
+    }
+}
+
+/// New tables become `[section]` headers; everything else is written inline.
+fn toml_item(value: TomlValue) -> TomlItem {
+    match value {
+        TomlValue::Table(table) => {
+            let mut converted = toml_edit::Table::new();
+            for (key, value) in table {
+                converted.insert(&key, toml_item(value));
+            }
+            TomlItem::Table(converted)
+        }
+        value => TomlItem::Value(toml_value(value)),
+    }
+}
+
+fn toml_value(value: TomlValue) -> toml_edit::Value {
+    match value {
+        TomlValue::String(value) => value.into(),
+        TomlValue::Integer(value) => value.into(),
+        TomlValue::Float(value) => value.into(),
+        TomlValue::Boolean(value) => value.into(),
+        TomlValue::Datetime(value) => value.into(),
+        TomlValue::Array(values) => values
+            .into_iter()
+            .map(toml_value)
+            .collect::<toml_edit::Array>()
+            .into(),
+        TomlValue::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, toml_value(value)))
+            .collect::<toml_edit::InlineTable>()
+            .into(),
+    }
+}
+
 fn find_feature_flag(contents: &str, key: &str) -> Option<bool> {
     let mut in_features = false;
     for line in contents.lines() {
@@ -207,142 +267,19 @@ fn find_feature_flag(contents: &str, key: &str) -> Option<bool> {
     None
 }
 
-fn upsert_feature_flag(contents: &str, key: &str, enabled: bool) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let mut in_features = false;
-    let mut features_start: Option<usize> = None;
-    let mut features_end: Option<usize> = None;
-    let mut key_index: Option<usize> = None;
-
-    for (idx, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            if in_features {
-                features_end = Some(idx);
-                break;
-            }
-            in_features = trimmed == FEATURES_TABLE;
-            if in_features {
-                features_start = Some(idx);
-            }
-            continue;
-        }
-        if !in_features || trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
-        if let Some((candidate_key, _)) = trimmed.split_once('=') {
-            if candidate_key.trim() == key {
-                key_index = Some(idx);
-                break;
-            }
-        }
-    }
-
-    let flag_line = format!("{key} = {}", if enabled { "true" } else { "false" });
-
-    if let Some(start) = features_start {
-        let end = features_end.unwrap_or(lines.len());
-        if let Some(index) = key_index {
-            lines[index] = flag_line;
-        } else {
-            let insert_at = if end > start + 1 { end } else { start + 1 };
-            lines.insert(insert_at, flag_line);
-        }
-    } else {
-        if !lines.is_empty() && !lines.last().unwrap().trim().is_empty() {
-            lines.push(String::new());
-        }
-        lines.push(FEATURES_TABLE.to_string());
-        lines.push(flag_line);
-    }
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
-    }
-    updated
-}
-
-fn remove_top_level_key(contents: &str, key: &str) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let table_start = first_table_start_index(&lines).unwrap_or(lines.len());
-    lines.retain_with_index(|idx, line| {
-        if idx >= table_start {
-            return true;
-        }
-        !is_key_value_for(line, key)
-    });
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
-    }
-    updated
-}
-
-fn upsert_top_level_string_key(contents: &str, key: &str, value: &str) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let table_start = first_table_start_index(&lines).unwrap_or(lines.len());
-    let replacement = format!("{key} = \"{value}\"");
-    let mut replaced = false;
-
-    for line in lines.iter_mut().take(table_start) {
-        if is_key_value_for(line, key) {
-            *line = replacement.clone();
-            replaced = true;
-            break;
-        }
-    }
-
-    if !replaced {
-        lines.insert(table_start, replacement);
-    }
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
-    }
-    updated
-}
-
-fn is_key_value_for(line: &str, key: &str) -> bool {
-    let trimmed = line.trim();
-    if trimmed.is_empty() || trimmed.starts_with('#') {
-        return false;
-    }
-    let Some((candidate_key, _)) = trimmed.split_once('=') else {
-        return false;
-    };
-    candidate_key.trim() == key
-}
-
-fn first_table_start_index(lines: &[String]) -> Option<usize> {
-    lines.iter().position(|line| {
-        let trimmed = line.trim();
-        trimmed.starts_with('[') && trimmed.ends_with(']')
-    })
-}
-
-trait RetainWithIndex<T> {
-    fn retain_with_index<F: FnMut(usize, &T) -> bool>(&mut self, f: F);
-}
-
-impl<T> RetainWithIndex<T> for Vec<T> {
-    fn retain_with_index<F: FnMut(usize, &T) -> bool>(&mut self, mut f: F) {
-        let mut index = 0usize;
-        self.retain(|item| {
-            let keep = f(index, item);
-            index += 1;
-            keep
-        });
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{
-        parse_personality_from_toml, remove_top_level_key, upsert_top_level_string_key,
-    };
+    use super::{apply_config_edit, parse_personality_from_toml, DocumentMut};
+    use crate::backend::protocol::{ConfigEdit, MergeStrategy};
+    use serde_json::json;
+
+    fn edit(key_path: &str, value: serde_json::Value, merge_strategy: MergeStrategy) -> ConfigEdit {
+        ConfigEdit {
+            key_path: key_path.to_string(),
+            value,
+            merge_strategy,
+        }
+    }
 
     #[test]
     fn parse_personality_reads_supported_values() {
@@ -358,23 +295,119 @@ mod tests {
     }
 
     #[test]
-    fn upsert_top_level_personality_before_tables() {
-        let input = "[features]\nsteer = true\n";
-        let updated = upsert_top_level_string_key(input, "personality", "friendly");
-        assert_eq!(updated, "personality = \"friendly\"\n[features]\nsteer = true\n");
+    fn apply_config_edit_sets_and_removes_top_level_personality() {
+        let mut document: DocumentMut = "[features]\nsteer = true\n".parse().unwrap();
+        let personality = |value| edit("personality", value, MergeStrategy::Replace);
+        apply_config_edit(&mut document, &personality(json!("friendly"))).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "personality = \"friendly\"\n[features]\nsteer = true\n"
+        );
+        apply_config_edit(&mut document, &personality(json!(null))).unwrap();
+        assert_eq!(document.to_string(), "[features]\nsteer = true\n");
+    }
+
+    #[test]
+    fn apply_config_edit_writes_nested_keys_and_profiles() {
+        let mut table: DocumentMut = "model = \"gpt-5\"\n[profiles.fast]\nmodel = \"o4\"\n"
+            .parse()
+            .unwrap();
+        apply_config_edit(
+            &mut table,
+            &edit("features.steer", json!(true), MergeStrategy::Replace),
+        )
+        .unwrap();
+        apply_config_edit(
+            &mut table,
+            &edit(
+                "profiles.fast.model_reasoning_effort",
+                json!("low"),
+                MergeStrategy::Replace,
+            ),
+        )
+        .unwrap();
+        apply_config_edit(
+            &mut table,
+            &edit("model", json!(null), MergeStrategy::Replace),
+        )
+        .unwrap();
+
+        assert!(table.get("model").is_none());
+        assert_eq!(table["features"]["steer"].as_bool(), Some(true));
+        assert_eq!(table["profiles"]["fast"]["model"].as_str(), Some("o4"));
+        assert_eq!(
+            table["profiles"]["fast"]["model_reasoning_effort"].as_str(),
+            Some("low")
+        );
+    }
+
+    #[test]
+    fn apply_config_edit_upsert_merges_tables() {
+        let mut table: DocumentMut = "[mcp_servers.docs]\ncommand = \"docs\"\nargs = [\"a\"]\n"
+            .parse()
+            .unwrap();
+        apply_config_edit(
+            &mut table,
+            &edit(
+                "mcp_servers",
+                json!({ "docs": { "args": ["b"] } }),
+                MergeStrategy::Upsert,
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            table["mcp_servers"]["docs"]["command"].as_str(),
+            Some("docs")
+        );
+        assert_eq!(table["mcp_servers"]["docs"]["args"][0].as_str(), Some("b"));
+
+        apply_config_edit(
+            &mut table,
+            &edit(
+                "mcp_servers",
+                json!({ "other": {} }),
+                MergeStrategy::Replace,
+            ),
+        )
+        .unwrap();
+        assert!(table["mcp_servers"].get("docs").is_none());
     }
 
     #[test]
-    fn upsert_replaces_existing_top_level_personality() {
-        let input = "personality = \"friendly\"\n[features]\nsteer = true\n";
-        let updated = upsert_top_level_string_key(input, "personality", "pragmatic");
-        assert_eq!(updated, "personality = \"pragmatic\"\n[features]\nsteer = true\n");
+    fn apply_config_edit_rejects_bad_paths() {
+        let mut table: DocumentMut = "model = \"gpt-5\"\n".parse().unwrap();
+        assert!(
+            apply_config_edit(&mut table, &edit("a..b", json!(1), MergeStrategy::Replace)).is_err()
+        );
+        assert!(apply_config_edit(
+            &mut table,
+            &edit("model.name", json!(1), MergeStrategy::Replace)
+        )
+        .is_err());
     }
 
     #[test]
-    fn remove_top_level_personality_keeps_other_keys() {
-        let input = "personality = \"friendly\"\nmodel = \"gpt-5\"\n[features]\nsteer = true\n";
-        let updated = remove_top_level_key(input, "personality");
-        assert_eq!(updated, "model = \"gpt-5\"\n[features]\nsteer = true\n");
+    fn apply_config_edit_keeps_comments_and_layout() {
+        let input = "# Default model\nmodel = \"gpt-5\" # pinned\n\n[mcp_servers.docs]\n# local docs server\ncommand = \"docs\"\n";
+        let mut document: DocumentMut = input.parse().unwrap();
+        apply_config_edit(
+            &mut document,
+            &edit(
+                "mcp_servers.docs.args",
+                json!(["--port", 8080]),
+                MergeStrategy::Replace,
+            ),
+        )
+        .unwrap();
+        apply_config_edit(
+            &mut document,
+            &edit("features.steer", json!(true), MergeStrategy::Replace),
+        )
+        .unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "# Default model\nmodel = \"gpt-5\" # pinned\n\n[mcp_servers.docs]\n# local docs server\ncommand = \"docs\"\nargs = [\"--port\", 8080]\n\n[features]\nsteer = true\n"
+        );
     }
 }
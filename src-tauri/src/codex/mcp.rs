@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::backend::protocol::{ConfigEdit, MergeStrategy};
+
+const MCP_SERVERS_TABLE: &str = "mcp_servers";
+
+/// Server tables whose values are credentials rather than settings.
+const SECRET_TABLES: &[&str] = &["env", "http_headers"];
+
+/// Shown to read-only daemon clients in place of a secret value.
+pub(crate) const REDACTED_VALUE: &str = "<redacted>";
+
+/// One `[mcp_servers.<name>]` table from `config.toml`. Field names match the
+/// TOML keys; keys this editor does not know about are kept in `extra` so a
+/// round trip does not drop them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct McpServerConfig {
+    /// Launches a stdio server. Mutually exclusive with `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cwd: Option<String>,
+    /// Connects to a streamable HTTP server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bearer_token_env_var: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) http_headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) startup_timeout_sec: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tool_timeout_sec: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) enabled_tools: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) disabled_tools: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub(crate) extra: Map<String, Value>,
+}
+
+impl McpServerConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let command = self.command.as_deref().map(str::trim).unwrap_or("");
+        let url = self.url.as_deref().map(str::trim).unwrap_or("");
+        match (command.is_empty(), url.is_empty()) {
+            (true, true) => Err("An MCP server needs either a command or a url".to_string()),
+            (false, false) => Err("An MCP server cannot have both a command and a url".to_string()),
+            (false, true)
+                if self.bearer_token_env_var.is_some() || !self.http_headers.is_empty() =>
+            {
+                Err("bearer_token_env_var and http_headers only apply to url servers".to_string())
+            }
+            (true, false)
+                if !self.args.is_empty() || !self.env.is_empty() || self.cwd.is_some() =>
+            {
+                Err("args, env and cwd only apply to command servers".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct McpServerEntry {
+    pub(crate) name: String,
+    pub(crate) config: McpServerConfig,
+}
+
+/// Server names become a segment of the `mcp_servers.<name>` key path, so
+/// they are limited to the characters Codex accepts.
+pub(crate) fn validate_server_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid MCP server name `{name}`: use letters, digits, `-` or `_`"
+        ))
+    }
+}
+
+/// Reads the `mcp_servers` tables out of a JSON-converted `config.toml`.
+pub(crate) fn servers_from_config(config: &Value) -> Result<Vec<McpServerEntry>, String> {
+    let Some(servers) = config.get(MCP_SERVERS_TABLE).and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+    let mut entries = servers
+        .iter()
+        .map(|(name, value)| {
+            let config = serde_json::from_value(value.clone())
+                .map_err(|err| format!("Invalid MCP server `{name}` in config.toml: {err}"))?;
+            Ok(McpServerEntry {
+                name: name.clone(),
+                config,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Masks MCP `env` and `http_headers` values in a config read result. The
+/// `mcp_servers` tables are found wherever they nest, so `config/read` layers
+/// are covered too.
+pub(crate) fn redact_config_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                match child {
+                    Value::Object(servers) if key == MCP_SERVERS_TABLE => {
+                        servers.values_mut().for_each(redact_server);
+                    }
+                    child => redact_config_secrets(child),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_config_secrets),
+        _ => {}
+    }
+}
+
+/// Masks secrets in a `{ "servers": [{ name, config }] }` listing.
+pub(crate) fn redact_listed_servers(value: &mut Value) {
+    if let Some(Value::Array(servers)) = value.get_mut("servers") {
+        for entry in servers {
+            if let Some(config) = entry.get_mut("config") {
+                redact_server(config);
+            }
+        }
+    }
+}
+
+fn redact_server(server: &mut Value) {
+    for table in SECRET_TABLES {
+        if let Some(Value::Object(values)) = server.get_mut(*table) {
+            for value in values.values_mut() {
+                *value = Value::String(REDACTED_VALUE.to_string());
+            }
+        }
+    }
+}
+
+/// The config edit that replaces `[mcp_servers.<name>]` with `config`, or
+/// removes the table when `config` is `None`.
+pub(crate) fn server_edit(
+    name: &str,
+    config: Option<&McpServerConfig>,
+) -> Result<ConfigEdit, String> {
+    validate_server_name(name)?;
+    let value = match config {
+        Some(config) => {
+            config.validate()?;
+            serde_json::to_value(config).map_err(|err| err.to_string())?
+        }
+        None => Value::Null,
+    };
+    Ok(ConfigEdit {
+        key_path: format!("{MCP_SERVERS_TABLE}.{name}"),
+        value,
+        merge_strategy: MergeStrategy::Replace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn servers_from_config_keeps_unknown_keys() {
+        let config = json!({
+            "model": "gpt-5",
+            "mcp_servers": {
+                "linear": { "url": "https://mcp.linear.app/mcp" },
+                "docs": {
+                    "command": "npx",
+                    "args": ["-y", "docs-mcp"],
+                    "env": { "TOKEN": "x" },
+                    "startup_timeout_sec": 20,
+                    "experimental_flag": true
+                }
+            }
+        });
+        let servers = servers_from_config(&config).unwrap();
+        assert_eq!(
+            servers
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["docs", "linear"]
+        );
+        let docs = &servers[0].config;
+        assert_eq!(docs.command.as_deref(), Some("npx"));
+        assert_eq!(docs.startup_timeout_sec, Some(20.0));
+        assert_eq!(docs.extra.get("experimental_flag"), Some(&json!(true)));
+        assert_eq!(
+            serde_json::to_value(docs).unwrap()["experimental_flag"],
+            json!(true)
+        );
+        assert!(servers_from_config(&json!({})).unwrap().is_empty());
+    }
+
+    #[test]
+    fn redaction_masks_env_and_headers_in_config_and_layers() {
+        let server = json!({
+            "command": "npx",
+            "env": { "TOKEN": "secret" },
+            "http_headers": { "Authorization": "Bearer secret" },
+            "env_http_headers": { "X-Key": "KEY_VAR" }
+        });
+        let mut config = json!({
+            "config": { "model": "gpt-5", "mcp_servers": { "docs": server.clone() } },
+            "layers": [{ "config": { "mcp_servers": { "docs": server } } }]
+        });
+        redact_config_secrets(&mut config);
+        for docs in [
+            &config["config"]["mcp_servers"]["docs"],
+            &config["layers"][0]["config"]["mcp_servers"]["docs"],
+        ] {
+            assert_eq!(docs["env"]["TOKEN"], json!(REDACTED_VALUE));
+            assert_eq!(docs["http_headers"]["Authorization"], json!(REDACTED_VALUE));
+            assert_eq!(docs["env_http_headers"]["X-Key"], json!("KEY_VAR"));
+            assert_eq!(docs["command"], json!("npx"));
+        }
+        assert_eq!(config["config"]["model"], json!("gpt-5"));
+
+        let mut listing = json!({
+            "servers": [{ "name": "docs", "config": { "env": { "TOKEN": "secret" } } }]
+        });
+        redact_listed_servers(&mut listing);
+        assert_eq!(
+            listing["servers"][0]["config"]["env"]["TOKEN"],
+            json!(REDACTED_VALUE)
+        );
+    }
+
+    #[test]
+    fn server_edit_validates_name_and_transport() {
+        let config = McpServerConfig {
+            url: Some("https://example.com/mcp".to_string()),
+            bearer_token_env_var: Some("EXAMPLE_TOKEN".to_string()),
+            ..Default::default()
+        };
+        let edit = server_edit("example", Some(&config)).unwrap();
+        assert_eq!(edit.key_path, "mcp_servers.example");
+        assert_eq!(
+            edit.value,
+            json!({ "url": "https://example.com/mcp", "bearer_token_env_var": "EXAMPLE_TOKEN" })
+        );
+        assert_eq!(server_edit("example", None).unwrap().value, Value::Null);
+
+        assert!(server_edit("bad.name", Some(&config)).is_err());
+        assert!(server_edit("", None).is_err());
+        assert!(server_edit("empty", Some(&McpServerConfig::default())).is_err());
+        let both = McpServerConfig {
+            command: Some("npx".to_string()),
+            ..config.clone()
+        };
+        assert!(server_edit("both", Some(&both)).is_err());
+        let stdio_with_headers = McpServerConfig {
+            command: Some("npx".to_string()),
+            http_headers: BTreeMap::from([("X-Key".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+        assert!(stdio_with_headers.validate().is_err());
+    }
+}
@@ -4,19 +4,22 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tauri::{AppHandle, Emitter, State};
-use tokio::sync::mpsc;
+use tauri::{AppHandle, Manager, State};
 use tokio::time::timeout;
 
 pub(crate) mod args;
+pub(crate) mod background;
 pub(crate) mod config;
 pub(crate) mod home;
+pub(crate) mod mcp;
 
+use self::background::{extract_json_value, BackgroundPrompt, BackgroundPromptOptions};
+use self::mcp::McpServerConfig;
 pub(crate) use crate::backend::app_server::WorkspaceSession;
-use crate::backend::events::AppServerEvent;
+use crate::backend::protocol::{ConfigEdit, MergeStrategy};
 use crate::backend::app_server::{
     build_codex_command_with_bin, build_codex_path_env, check_codex_installation,
-    spawn_workspace_session as spawn_workspace_session_inner,
+    spawn_workspace_session as spawn_workspace_session_inner, SessionOptions,
 };
 use crate::shared::process_core::tokio_command;
 use crate::event_sink::TauriEventSink;
@@ -33,6 +36,11 @@ pub(crate) async fn spawn_workspace_session(
     codex_home: Option<PathBuf>,
 ) -> Result<Arc<WorkspaceSession>, String> {
     let client_version = app_handle.package_info().version.to_string();
+    let options = {
+        let state = app_handle.state::<AppState>();
+        let settings = state.app_settings.lock().await;
+        SessionOptions::from_settings(&settings)
+    };
     let event_sink = TauriEventSink::new(app_handle);
     spawn_workspace_session_inner(
         entry,
@@ -40,6 +48,7 @@ pub(crate) async fn spawn_workspace_session(
         codex_args,
         codex_home,
         client_version,
+        options,
         event_sink,
     )
     .await
@@ -251,6 +260,127 @@ pub(crate) async fn list_mcp_server_status(
     codex_core::list_mcp_server_status_core(&state.sessions, workspace_id, cursor, limit).await
 }
 
+#[tauri::command]
+pub(crate) async fn list_mcp_servers(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_mcp_servers",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    codex_core::list_mcp_servers_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn upsert_mcp_server(
+    workspace_id: String,
+    name: String,
+    config: McpServerConfig,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "upsert_mcp_server",
+            json!({ "workspaceId": workspace_id, "name": name, "config": config }),
+        )
+        .await;
+    }
+
+    codex_core::write_mcp_server_core(
+        &state.sessions,
+        &state.workspaces,
+        workspace_id,
+        name,
+        Some(config),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn remove_mcp_server(
+    workspace_id: String,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "remove_mcp_server",
+            json!({ "workspaceId": workspace_id, "name": name }),
+        )
+        .await;
+    }
+
+    codex_core::write_mcp_server_core(&state.sessions, &state.workspaces, workspace_id, name, None)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn reload_mcp_servers(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "reload_mcp_servers",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    codex_core::reload_mcp_servers_core(&state.sessions, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn mcp_server_oauth_login(
+    workspace_id: String,
+    name: String,
+    scopes: Option<Vec<String>>,
+    timeout_secs: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "mcp_server_oauth_login",
+            json!({
+                "workspaceId": workspace_id,
+                "name": name,
+                "scopes": scopes,
+                "timeoutSecs": timeout_secs,
+            }),
+        )
+        .await;
+    }
+
+    codex_core::mcp_server_oauth_login_core(
+        &state.sessions,
+        workspace_id,
+        name,
+        scopes,
+        timeout_secs,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn archive_thread(
     workspace_id: String,
@@ -271,6 +401,98 @@ pub(crate) async fn archive_thread(
     codex_core::archive_thread_core(&state.sessions, workspace_id, thread_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn unarchive_thread(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "unarchive_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    codex_core::unarchive_thread_core(&state.sessions, workspace_id, thread_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn read_thread(
+    workspace_id: String,
+    thread_id: String,
+    include_turns: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let include_turns = include_turns.unwrap_or(true);
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "read_thread",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "includeTurns": include_turns
+            }),
+        )
+        .await;
+    }
+
+    codex_core::read_thread_core(&state.sessions, workspace_id, thread_id, include_turns).await
+}
+
+#[tauri::command]
+pub(crate) async fn rollback_thread(
+    workspace_id: String,
+    thread_id: String,
+    num_turns: u32,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "rollback_thread",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "numTurns": num_turns
+            }),
+        )
+        .await;
+    }
+
+    codex_core::rollback_thread_core(&state.sessions, workspace_id, thread_id, num_turns).await
+}
+
+#[tauri::command]
+pub(crate) async fn list_loaded_threads(
+    workspace_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_loaded_threads",
+            json!({ "workspaceId": workspace_id, "cursor": cursor, "limit": limit }),
+        )
+        .await;
+    }
+
+    codex_core::list_loaded_threads_core(&state.sessions, workspace_id, cursor, limit).await
+}
+
 #[tauri::command]
 pub(crate) async fn compact_thread(
     workspace_id: String,
@@ -408,6 +630,26 @@ pub(crate) async fn turn_interrupt(
     codex_core::turn_interrupt_core(&state.sessions, workspace_id, thread_id, turn_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn cancel_app_server_request(
+    workspace_id: String,
+    method: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_app_server_request",
+            json!({ "workspaceId": workspace_id, "method": method }),
+        )
+        .await;
+    }
+
+    codex_core::cancel_app_server_request_core(&state.sessions, workspace_id, method).await
+}
+
 #[tauri::command]
 pub(crate) async fn start_review(
     workspace_id: String,
@@ -536,6 +778,31 @@ pub(crate) async fn codex_login_cancel(
         .await
 }
 
+#[tauri::command]
+pub(crate) async fn codex_logout(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_logout",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await;
+    }
+
+    codex_core::codex_logout_core(
+        &state.sessions,
+        &state.workspaces,
+        &state.codex_login_cancels,
+        workspace_id,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn skills_list(
     workspace_id: String,
@@ -599,32 +866,25 @@ pub(crate) async fn respond_to_server_request(
         .await
 }
 
-fn build_commit_message_prompt(diff: &str) -> String {
-    format!(
-        "Generate a concise git commit message for the following changes. \
-Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
-Keep the summary line under 72 characters. \
-Only output the commit message, nothing else.\n\n\
-Changes:\n{diff}"
-    )
-}
-
 /// Gets the diff content for commit message generation
 #[tauri::command]
 pub(crate) async fn get_commit_message_prompt(
     workspace_id: String,
     state: State<'_, AppState>,
+    app: AppHandle,
 ) -> Result<String, String> {
-    // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
-
-    if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_commit_message_prompt",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    let prompt = build_commit_message_prompt(&diff);
-
-    Ok(prompt)
+    codex_core::get_commit_message_prompt_core(&state.workspaces, &workspace_id).await
 }
 
 #[tauri::command]
@@ -655,180 +915,137 @@ pub(crate) async fn get_config_model(
     codex_core::get_config_model_core(&state.workspaces, workspace_id).await
 }
 
-/// Generates a commit message in the background without showing in the main chat
 #[tauri::command]
-pub(crate) async fn generate_commit_message(
-    workspace_id: String,
+pub(crate) async fn read_codex_config(
+    workspace_id: Option<String>,
+    include_layers: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<String, String> {
-    // Get the diff from git
-    let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
-
-    if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+) -> Result<Value, String> {
+    let include_layers = include_layers.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "read_codex_config",
+            json!({ "workspaceId": workspace_id, "includeLayers": include_layers }),
+        )
+        .await;
     }
 
-    let prompt = build_commit_message_prompt(&diff);
+    codex_core::read_codex_config_core(
+        &state.sessions,
+        &state.workspaces,
+        workspace_id,
+        include_layers,
+    )
+    .await
+}
 
-    // Get the session
-    let session = {
-        let sessions = state.sessions.lock().await;
-        sessions
-            .get(&workspace_id)
-            .ok_or("workspace not connected")?
-            .clone()
+#[tauri::command]
+pub(crate) async fn write_codex_config_value(
+    workspace_id: Option<String>,
+    key_path: String,
+    value: Value,
+    merge_strategy: Option<MergeStrategy>,
+    expected_version: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let edit = ConfigEdit {
+        key_path,
+        value,
+        merge_strategy: merge_strategy.unwrap_or_default(),
     };
+    batch_write_codex_config(workspace_id, vec![edit], expected_version, state, app).await
+}
 
-    // Create a background thread
-    let thread_params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "never"  // Never ask for approval in background
-    });
-    let thread_result = session.send_request("thread/start", thread_params).await?;
-
-    // Handle error response
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
-
-    // Extract threadId - try multiple paths since response format may vary
-    let thread_id = thread_result
-        .get("result")
-        .and_then(|r| r.get("threadId"))
-        .or_else(|| thread_result.get("result").and_then(|r| r.get("thread")).and_then(|t| t.get("id")))
-        .or_else(|| thread_result.get("threadId"))
-        .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-        .and_then(|t| t.as_str())
-        .ok_or_else(|| format!("Failed to get threadId from thread/start response: {:?}", thread_result))?
-        .to_string();
-
-    // Hide background helper threads from the sidebar, even if a thread/started event leaked.
-    let _ = app.emit(
-        "app-server-event",
-        AppServerEvent {
-            workspace_id: workspace_id.clone(),
-            message: json!({
-                "method": "codex/backgroundThread",
-                "params": {
-                    "threadId": thread_id,
-                    "action": "hide"
-                }
+#[tauri::command]
+pub(crate) async fn batch_write_codex_config(
+    workspace_id: Option<String>,
+    edits: Vec<ConfigEdit>,
+    expected_version: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "batch_write_codex_config",
+            json!({
+                "workspaceId": workspace_id,
+                "edits": edits,
+                "expectedVersion": expected_version
             }),
-        },
-    );
-
-    // Create channel for receiving events
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-
-    // Register callback for this thread
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.insert(thread_id.clone(), tx);
+        )
+        .await;
     }
 
-    // Start a turn with the commit message prompt
-    let turn_params = json!({
-        "threadId": thread_id,
-        "input": [{ "type": "text", "text": prompt }],
-        "cwd": session.entry.path,
-        "approvalPolicy": "never",
-        "sandboxPolicy": { "type": "readOnly" },
-    });
-    let turn_result = session.send_request("turn/start", turn_params).await;
-    let turn_result = match turn_result {
-        Ok(result) => result,
-        Err(error) => {
-            // Clean up if turn fails to start
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error);
-        }
-    };
-
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting turn");
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-        let archive_params = json!({ "threadId": thread_id.as_str() });
-        let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
-    }
+    codex_core::write_codex_config_core(
+        &state.sessions,
+        &state.workspaces,
+        workspace_id,
+        edits,
+        expected_version,
+    )
+    .await
+}
 
-    // Collect assistant text from events
-    let mut commit_message = String::new();
-    let timeout_duration = Duration::from_secs(60);
-    let collect_result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-
-            match method {
-                "item/agentMessage/delta" => {
-                    // Extract text delta from agent messages
-                    if let Some(params) = event.get("params") {
-                        if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            commit_message.push_str(delta);
-                        }
-                    }
-                }
-                "turn/completed" => {
-                    // Turn completed, we can stop listening
-                    break;
-                }
-                "turn/error" => {
-                    // Error occurred
-                    let error_msg = event
-                        .get("params")
-                        .and_then(|p| p.get("error"))
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("Unknown error during commit message generation");
-                    return Err(error_msg.to_string());
-                }
-                _ => {
-                    // Ignore other events (turn/started, item/started, item/completed, reasoning events, etc.)
-                }
-            }
-        }
-        Ok(())
-    })
-    .await;
-
-    // Unregister callback
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.remove(&thread_id);
+/// Runs a one-shot prompt in a hidden helper thread and returns `{ text, json? }`.
+#[tauri::command]
+pub(crate) async fn run_background_prompt(
+    workspace_id: String,
+    prompt: String,
+    options: Option<BackgroundPromptOptions>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "run_background_prompt",
+            json!({ "workspaceId": workspace_id, "prompt": prompt, "options": options }),
+        )
+        .await;
     }
 
-    // Archive the thread to clean up
-    let archive_params = json!({ "threadId": thread_id });
-    let _ = session.send_request("thread/archive", archive_params).await;
-
-    // Handle timeout or collection error
-    match collect_result {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("Timeout waiting for commit message generation".to_string()),
-    }
+    codex_core::run_background_prompt_core(
+        &state.sessions,
+        &TauriEventSink::new(app),
+        workspace_id,
+        prompt,
+        options.unwrap_or_default(),
+    )
+    .await
+}
 
-    let trimmed = commit_message.trim().to_string();
-    if trimmed.is_empty() {
-        return Err("No commit message was generated".to_string());
+/// Generates a commit message in the background without showing in the main chat
+#[tauri::command]
+pub(crate) async fn generate_commit_message(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "generate_commit_message",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    Ok(trimmed)
+    codex_core::generate_commit_message_core(
+        &state.sessions,
+        &state.workspaces,
+        &TauriEventSink::new(app),
+        workspace_id,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -881,135 +1098,17 @@ Examples:\n\
 Task:\n{cleaned_prompt}"
     );
 
-    let thread_params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "never"
-    });
-    let thread_result = session.send_request("thread/start", thread_params).await?;
-
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
-
-    let thread_id = thread_result
-        .get("result")
-        .and_then(|r| r.get("threadId"))
-        .or_else(|| thread_result.get("result").and_then(|r| r.get("thread")).and_then(|t| t.get("id")))
-        .or_else(|| thread_result.get("threadId"))
-        .or_else(|| thread_result.get("thread").and_then(|t| t.get("id")))
-        .and_then(|t| t.as_str())
-        .ok_or_else(|| format!("Failed to get threadId from thread/start response: {:?}", thread_result))?
-        .to_string();
-
-    // Hide background helper threads from the sidebar, even if a thread/started event leaked.
-    let _ = app.emit(
-        "app-server-event",
-        AppServerEvent {
-            workspace_id: workspace_id.clone(),
-            message: json!({
-                "method": "codex/backgroundThread",
-                "params": {
-                    "threadId": thread_id,
-                    "action": "hide"
-                }
-            }),
-        },
-    );
-
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.insert(thread_id.clone(), tx);
-    }
-
-    let turn_params = json!({
-        "threadId": thread_id,
-        "input": [{ "type": "text", "text": title_prompt }],
-        "cwd": session.entry.path,
-        "approvalPolicy": "never",
-        "sandboxPolicy": { "type": "readOnly" },
-    });
-    let turn_result = session.send_request("turn/start", turn_params).await;
-    let turn_result = match turn_result {
-        Ok(result) => result,
-        Err(error) => {
-            {
-                let mut callbacks = session.background_thread_callbacks.lock().await;
-                callbacks.remove(&thread_id);
-            }
-            let archive_params = json!({ "threadId": thread_id.as_str() });
-            let _ = session.send_request("thread/archive", archive_params).await;
-            return Err(error);
-        }
-    };
-
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting turn");
-        {
-            let mut callbacks = session.background_thread_callbacks.lock().await;
-            callbacks.remove(&thread_id);
-        }
-        let archive_params = json!({ "threadId": thread_id.as_str() });
-        let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
-    }
-
-    let mut response_text = String::new();
-    let timeout_duration = Duration::from_secs(60);
-    let collect_result = timeout(timeout_duration, async {
-        while let Some(event) = rx.recv().await {
-            let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
-            match method {
-                "item/agentMessage/delta" => {
-                    if let Some(params) = event.get("params") {
-                        if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            response_text.push_str(delta);
-                        }
-                    }
-                }
-                "turn/completed" => break,
-                "turn/error" => {
-                    let error_msg = event
-                        .get("params")
-                        .and_then(|p| p.get("error"))
-                        .and_then(|e| e.as_str())
-                        .unwrap_or("Unknown error during metadata generation");
-                    return Err(error_msg.to_string());
-                }
-                _ => {}
-            }
-        }
-        Ok(())
-    })
-    .await;
-
-    {
-        let mut callbacks = session.background_thread_callbacks.lock().await;
-        callbacks.remove(&thread_id);
-    }
-
-    let archive_params = json!({ "threadId": thread_id });
-    let _ = session.send_request("thread/archive", archive_params).await;
-
-    match collect_result {
-        Ok(Ok(())) => {}
-        Ok(Err(e)) => return Err(e),
-        Err(_) => return Err("Timeout waiting for metadata generation".to_string()),
-    }
-
-    let trimmed = response_text.trim();
-    if trimmed.is_empty() {
+    let response_text = background::run_background_prompt(
+        &session,
+        &TauriEventSink::new(app),
+        BackgroundPrompt::new(title_prompt),
+    )
+    .await?;
+    if response_text.is_empty() {
         return Err("No metadata was generated".to_string());
     }
 
-    let json_value = extract_json_value(trimmed)
+    let json_value = extract_json_value(&response_text)
         .ok_or_else(|| "Failed to parse metadata JSON".to_string())?;
     let title = json_value
         .get("title")
@@ -1031,15 +1130,6 @@ Task:\n{cleaned_prompt}"
     }))
 }
 
-fn extract_json_value(raw: &str) -> Option<Value> {
-    let start = raw.find('{')?;
-    let end = raw.rfind('}')?;
-    if end <= start {
-        return None;
-    }
-    serde_json::from_str::<Value>(&raw[start..=end]).ok()
-}
-
 fn sanitize_run_worktree_name(value: &str) -> String {
     let trimmed = value.trim().to_lowercase();
     let mut cleaned = String::new();
@@ -86,27 +86,43 @@ pub fn run() {
             workspaces::add_worktree,
             workspaces::worktree_setup_status,
             workspaces::worktree_setup_mark_ran,
+            workspaces::run_workspace_script,
             workspaces::remove_workspace,
             workspaces::remove_worktree,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
             workspaces::apply_worktree_changes,
             workspaces::update_workspace_settings,
+            workspaces::switch_workspace_account,
             workspaces::update_workspace_codex_bin,
             codex::start_thread,
             codex::send_user_message,
             codex::turn_interrupt,
+            codex::cancel_app_server_request,
             codex::start_review,
             codex::respond_to_server_request,
             codex::remember_approval_rule,
             codex::get_commit_message_prompt,
             codex::generate_commit_message,
             codex::generate_run_metadata,
+            codex::run_background_prompt,
+            codex::read_codex_config,
+            codex::write_codex_config_value,
+            codex::batch_write_codex_config,
             codex::resume_thread,
             codex::fork_thread,
             codex::list_threads,
             codex::list_mcp_server_status,
+            codex::list_mcp_servers,
+            codex::upsert_mcp_server,
+            codex::remove_mcp_server,
+            codex::reload_mcp_servers,
+            codex::mcp_server_oauth_login,
             codex::archive_thread,
+            codex::unarchive_thread,
+            codex::read_thread,
+            codex::rollback_thread,
+            codex::list_loaded_threads,
             codex::compact_thread,
             codex::set_thread_name,
             codex::collaboration_mode_list,
@@ -116,11 +132,25 @@ pub fn run() {
             git::get_git_diffs,
             git::get_git_log,
             git::get_git_commit_diff,
+            git::get_git_ref_diff,
             git::get_git_remote,
             git::stage_git_file,
             git::stage_git_all,
             git::unstage_git_file,
             git::revert_git_file,
+            git::stage_git_hunk,
+            git::unstage_git_hunk,
+            git::revert_git_hunk,
+            git::get_git_blame,
+            git::get_git_file_history,
+            git::get_git_conflicts,
+            git::resolve_git_conflict,
+            git::list_git_stashes,
+            git::create_git_stash,
+            git::apply_git_stash,
+            git::pop_git_stash,
+            git::drop_git_stash,
+            git::get_git_stash_diff,
             git::revert_git_all,
             git::commit_git,
             git::push_git,
@@ -143,6 +173,7 @@ pub fn run() {
             codex::account_read,
             codex::codex_login,
             codex::codex_login_cancel,
+            codex::codex_logout,
             codex::skills_list,
             codex::apps_list,
             prompts::prompts_list,
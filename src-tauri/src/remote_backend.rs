@@ -1,21 +1,65 @@
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
+use futures_util::{SinkExt, StreamExt};
+use sha2::{Digest, Sha256};
 use tauri::{AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::CryptoProvider;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{DigitallySignedStruct, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
 
 use crate::state::AppState;
-use crate::types::BackendMode;
+use crate::types::{AppSettings, BackendMode};
 
 const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
 const DISCONNECTED_MESSAGE: &str = "remote backend disconnected";
+const TLS_PREFIX: &str = "tls://";
+const UNIX_PREFIX: &str = "unix:";
+const STATUS_EVENT: &str = "remote-backend-status";
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a call issued while reconnecting waits for the link to come back.
+const RECONNECT_WAIT: Duration = Duration::from_secs(15);
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
 
 type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
 
+/// Tracks the seq of the last daemon event delivered to the UI so a reconnect can
+/// ask `events_since` for exactly what was missed.
+#[derive(Default)]
+struct EventCursor {
+    state: std::sync::Mutex<EventCursorState>,
+}
+
+#[derive(Default)]
+struct EventCursorState {
+    last_seq: u64,
+    primed: bool,
+    /// Live notifications parked while a replay is in flight, emitted after it.
+    held: Option<Vec<Value>>,
+}
+
+impl EventCursor {
+    fn lock(&self) -> std::sync::MutexGuard<'_, EventCursorState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
 pub(crate) fn normalize_path_for_remote(path: String) -> String {
     let trimmed = path.trim();
     if trimmed.is_empty() {
@@ -58,34 +102,87 @@ pub(crate) struct RemoteBackend {
 struct RemoteBackendInner {
     out_tx: mpsc::UnboundedSender<String>,
     pending: Arc<Mutex<PendingMap>>,
-    next_id: AtomicU64,
-    connected: Arc<AtomicBool>,
+    next_id: Arc<AtomicU64>,
+    connected: Arc<watch::Sender<bool>>,
+}
+
+#[derive(Clone)]
+struct ConnectOptions {
+    host: String,
+    token: Option<String>,
+    fingerprint: Option<String>,
+}
+
+/// A single live transport. Dropping it tears down both IO tasks; `closed` fires
+/// once either half of the transport goes away.
+struct Connection {
+    out_tx: mpsc::UnboundedSender<String>,
+    closed: Arc<Notify>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RemoteBackendStatus {
+    state: &'static str,
+    host: String,
+    attempt: u32,
+    retry_in_ms: Option<u64>,
+    error: Option<String>,
 }
 
 impl RemoteBackend {
     pub(crate) async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
-        if !self.inner.connected.load(Ordering::SeqCst) {
-            return Err(DISCONNECTED_MESSAGE.to_string());
+        if !*self.inner.connected.borrow() {
+            let mut connected = self.inner.connected.subscribe();
+            let ready = tokio::time::timeout(RECONNECT_WAIT, connected.wait_for(|value| *value));
+            if !matches!(ready.await, Ok(Ok(_))) {
+                return Err(DISCONNECTED_MESSAGE.to_string());
+            }
         }
 
-        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
-        let (tx, rx) = oneshot::channel();
-        self.inner.pending.lock().await.insert(id, tx);
-
-        let request = json!({
-            "id": id,
-            "method": method,
-            "params": params,
-        });
-        let message = serde_json::to_string(&request).map_err(|err| err.to_string())?;
-        if self.inner.out_tx.send(message).is_err() {
-            self.inner.pending.lock().await.remove(&id);
-            return Err(DISCONNECTED_MESSAGE.to_string());
-        }
+        send_request(
+            &self.inner.out_tx,
+            &self.inner.pending,
+            &self.inner.next_id,
+            method,
+            params,
+        )
+        .await
+    }
+}
 
-        rx.await
-            .map_err(|_| DISCONNECTED_MESSAGE.to_string())?
+async fn send_request(
+    out_tx: &mpsc::UnboundedSender<String>,
+    pending: &Mutex<PendingMap>,
+    next_id: &AtomicU64,
+    method: &str,
+    params: Value,
+) -> Result<Value, String> {
+    let id = next_id.fetch_add(1, Ordering::SeqCst);
+    let (tx, rx) = oneshot::channel();
+    pending.lock().await.insert(id, tx);
+
+    let request = json!({
+        "id": id,
+        "method": method,
+        "params": params,
+    });
+    let message = serde_json::to_string(&request).map_err(|err| err.to_string())?;
+    if out_tx.send(message).is_err() {
+        pending.lock().await.remove(&id);
+        return Err(DISCONNECTED_MESSAGE.to_string());
     }
+
+    rx.await.map_err(|_| DISCONNECTED_MESSAGE.to_string())?
 }
 
 pub(crate) async fn is_remote_mode(state: &AppState) -> bool {
@@ -93,6 +190,17 @@ pub(crate) async fn is_remote_mode(state: &AppState) -> bool {
     matches!(settings.backend_mode, BackendMode::Remote)
 }
 
+/// Whether a settings update changes how (or whether) we reach the daemon, in
+/// which case the current connection should be dropped and rebuilt lazily.
+pub(crate) fn connection_settings_changed(previous: &AppSettings, next: &AppSettings) -> bool {
+    !matches!(
+        (&previous.backend_mode, &next.backend_mode),
+        (BackendMode::Local, BackendMode::Local) | (BackendMode::Remote, BackendMode::Remote)
+    ) || previous.remote_backend_host != next.remote_backend_host
+        || previous.remote_backend_token != next.remote_backend_token
+        || previous.remote_backend_tls_fingerprint != next.remote_backend_tls_fingerprint
+}
+
 pub(crate) async fn call_remote(
     state: &AppState,
     app: AppHandle,
@@ -100,167 +208,691 @@ pub(crate) async fn call_remote(
     params: Value,
 ) -> Result<Value, String> {
     let client = ensure_remote_backend(state, app).await?;
-    match client.call(method, params).await {
-        Ok(value) => Ok(value),
-        Err(err) => {
-            *state.remote_backend.lock().await = None;
-            Err(err)
-        }
-    }
+    client.call(method, params).await
 }
 
 async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<RemoteBackend, String> {
-    {
-        let guard = state.remote_backend.lock().await;
-        if let Some(client) = guard.as_ref() {
-            return Ok(client.clone());
-        }
+    let mut guard = state.remote_backend.lock().await;
+    if let Some(client) = guard.as_ref() {
+        return Ok(client.clone());
     }
 
-    let (host, token) = {
+    let options = {
         let settings = state.app_settings.lock().await;
-        (
-            settings.remote_backend_host.clone(),
-            settings.remote_backend_token.clone(),
-        )
+        let host = settings.remote_backend_host.trim();
+        ConnectOptions {
+            host: if host.is_empty() {
+                DEFAULT_REMOTE_HOST.to_string()
+            } else {
+                host.to_string()
+            },
+            token: settings.remote_backend_token.clone(),
+            fingerprint: settings.remote_backend_tls_fingerprint.clone(),
+        }
+    };
+
+    let pending = Arc::new(Mutex::new(PendingMap::new()));
+    let next_id = Arc::new(AtomicU64::new(1));
+    let events = Arc::new(EventCursor::default());
+    let connection = open_connection(&app, &options, &pending, &next_id, &events).await?;
+    let (connected, _) = watch::channel(true);
+    let connected = Arc::new(connected);
+    let (out_tx, out_rx) = mpsc::unbounded_channel::<String>();
+
+    emit_status(&app, "connected", &options.host, 0, None, None);
+    tokio::spawn(supervise_connection(
+        app,
+        options,
+        out_rx,
+        connection,
+        Arc::clone(&pending),
+        Arc::clone(&next_id),
+        events,
+        Arc::clone(&connected),
+    ));
+
+    let client = RemoteBackend {
+        inner: Arc::new(RemoteBackendInner {
+            out_tx,
+            pending,
+            next_id,
+            connected,
+        }),
     };
+    *guard = Some(client.clone());
+    Ok(client)
+}
+
+/// Pumps requests into the live connection and, when it drops, reconnects with
+/// exponential backoff. Requests that were in flight when the link dropped are
+/// failed rather than replayed since the daemon may already have applied them;
+/// calls issued while reconnecting wait (up to `RECONNECT_WAIT`) and are sent once
+/// the new connection is authenticated and has replayed the events missed in the
+/// gap. Exits once every `RemoteBackend` handle has been dropped.
+#[allow(clippy::too_many_arguments)]
+async fn supervise_connection(
+    app: AppHandle,
+    options: ConnectOptions,
+    mut out_rx: mpsc::UnboundedReceiver<String>,
+    mut connection: Connection,
+    pending: Arc<Mutex<PendingMap>>,
+    next_id: Arc<AtomicU64>,
+    events: Arc<EventCursor>,
+    connected: Arc<watch::Sender<bool>>,
+) {
+    loop {
+        let shutdown = loop {
+            tokio::select! {
+                message = out_rx.recv() => match message {
+                    Some(message) => {
+                        if connection.out_tx.send(message).is_err() {
+                            break false;
+                        }
+                    }
+                    None => break true,
+                },
+                _ = connection.closed.notified() => break false,
+            }
+        };
+        drop(connection);
+        connected.send_replace(false);
+        fail_pending(&pending).await;
+        if shutdown {
+            return;
+        }
+        emit_status(&app, "disconnected", &options.host, 0, None, None);
+
+        let mut attempt = 0;
+        let mut last_error = None;
+        connection = loop {
+            attempt += 1;
+            let delay = reconnect_delay(attempt);
+            emit_status(
+                &app,
+                "reconnecting",
+                &options.host,
+                attempt,
+                Some(delay),
+                last_error.take(),
+            );
+            let sleep = tokio::time::sleep(delay);
+            tokio::pin!(sleep);
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    message = out_rx.recv() => match message {
+                        // Raced with the disconnect; nothing can answer it now.
+                        Some(_) => fail_pending(&pending).await,
+                        None => return,
+                    },
+                }
+            }
+            match open_connection(&app, &options, &pending, &next_id, &events).await {
+                Ok(connection) => break connection,
+                Err(err) => last_error = Some(err),
+            }
+        };
+        let warning = resume_events(&app, &connection, &pending, &next_id, &events).await;
+        connected.send_replace(true);
+        emit_status(&app, "connected", &options.host, attempt, None, warning);
+    }
+}
+
+fn reconnect_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    RECONNECT_BASE_DELAY
+        .saturating_mul(1 << exponent)
+        .min(RECONNECT_MAX_DELAY)
+}
+
+fn emit_status(
+    app: &AppHandle,
+    state: &'static str,
+    host: &str,
+    attempt: u32,
+    retry_in: Option<Duration>,
+    error: Option<String>,
+) {
+    let payload = RemoteBackendStatus {
+        state,
+        host: host.to_string(),
+        attempt,
+        retry_in_ms: retry_in.map(|delay| delay.as_millis() as u64),
+        error,
+    };
+    let _ = app.emit(STATUS_EVENT, payload);
+}
 
-    let resolved_host = if host.trim().is_empty() {
-        DEFAULT_REMOTE_HOST.to_string()
+async fn open_connection(
+    app: &AppHandle,
+    options: &ConnectOptions,
+    pending: &Arc<Mutex<PendingMap>>,
+    next_id: &AtomicU64,
+    events: &Arc<EventCursor>,
+) -> Result<Connection, String> {
+    let host = options.host.as_str();
+    let fingerprint = options.fingerprint.as_deref();
+    let connect_error =
+        |err: String| format!("Failed to connect to remote backend at {host}: {err}");
+    let lower_host = host.to_ascii_lowercase();
+
+    let (out_tx, out_rx) = mpsc::unbounded_channel::<String>();
+    let closed = Arc::new(Notify::new());
+
+    let tasks = if lower_host.starts_with("ws://") || lower_host.starts_with("wss://") {
+        let connector = if lower_host.starts_with("wss://") {
+            Some(Connector::Rustls(build_tls_config(fingerprint)?))
+        } else {
+            None
+        };
+        let (stream, _) =
+            tokio_tungstenite::connect_async_tls_with_config(host, None, false, connector)
+                .await
+                .map_err(|err| connect_error(err.to_string()))?;
+        let (sink, source) = stream.split();
+        vec![
+            tokio::spawn(ws_write_loop(sink, out_rx, Arc::clone(&closed))),
+            tokio::spawn(ws_read_loop(
+                app.clone(),
+                source,
+                Arc::clone(pending),
+                Arc::clone(events),
+                Arc::clone(&closed),
+            )),
+        ]
+    } else if lower_host.starts_with(TLS_PREFIX) {
+        let address = &host[TLS_PREFIX.len()..];
+        let server_name = tls_server_name(address)?;
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|err| connect_error(err.to_string()))?;
+        let connector = TlsConnector::from(build_tls_config(fingerprint)?);
+        let stream = connector
+            .connect(server_name, stream)
+            .await
+            .map_err(|err| connect_error(err.to_string()))?;
+        spawn_stream_tasks(app.clone(), stream, out_rx, pending, events, &closed)
+    } else if lower_host.starts_with(UNIX_PREFIX) {
+        #[cfg(unix)]
+        {
+            let stream = tokio::net::UnixStream::connect(&host[UNIX_PREFIX.len()..])
+                .await
+                .map_err(|err| connect_error(err.to_string()))?;
+            spawn_stream_tasks(app.clone(), stream, out_rx, pending, events, &closed)
+        }
+        #[cfg(not(unix))]
+        {
+            return Err(connect_error(
+                "Unix sockets are not supported on this platform".to_string(),
+            ));
+        }
     } else {
-        host
+        let stream = TcpStream::connect(host)
+            .await
+            .map_err(|err| connect_error(err.to_string()))?;
+        spawn_stream_tasks(app.clone(), stream, out_rx, pending, events, &closed)
     };
 
-    let stream = TcpStream::connect(resolved_host.clone())
-        .await
-        .map_err(|err| format!("Failed to connect to remote backend at {resolved_host}: {err}"))?;
-    let (reader, mut writer) = stream.into_split();
+    let connection = Connection {
+        out_tx,
+        closed,
+        tasks,
+    };
 
-    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
-    let pending = Arc::new(Mutex::new(PendingMap::new()));
-    let pending_for_writer = Arc::clone(&pending);
-    let pending_for_reader = Arc::clone(&pending);
+    if let Some(token) = options.token.as_ref() {
+        let auth = send_request(
+            &connection.out_tx,
+            pending,
+            next_id,
+            "auth",
+            json!({ "token": token }),
+        );
+        match tokio::time::timeout(AUTH_TIMEOUT, auth).await {
+            Ok(result) => {
+                result?;
+            }
+            Err(_) => return Err("remote backend auth timed out".to_string()),
+        }
+    }
 
-    let connected = Arc::new(AtomicBool::new(true));
-    let connected_for_writer = Arc::clone(&connected);
-    let connected_for_reader = Arc::clone(&connected);
+    let primed = events.lock().primed;
+    if !primed {
+        resume_events(app, &connection, pending, next_id, events).await;
+    }
 
-    let write_task = tokio::spawn(async move {
-        while let Some(message) = out_rx.recv().await {
-            if writer.write_all(message.as_bytes()).await.is_err()
-                || writer.write_all(b"\n").await.is_err()
+    Ok(connection)
+}
+
+/// Brings the event cursor up to date on a fresh connection. The first time this
+/// only records the daemon's current seq; after a reconnect it replays whatever
+/// the daemon emitted since the last event we delivered. Returns a warning when
+/// the daemon no longer buffers all of the missed events.
+async fn resume_events(
+    app: &AppHandle,
+    connection: &Connection,
+    pending: &Mutex<PendingMap>,
+    next_id: &AtomicU64,
+    events: &EventCursor,
+) -> Option<String> {
+    let since = {
+        let mut state = events.lock();
+        if state.primed {
+            state.held = Some(Vec::new());
+            Some(state.last_seq)
+        } else {
+            None
+        }
+    };
+    let request_events = |seq: Option<u64>| {
+        let params = match seq {
+            Some(seq) => json!({ "seq": seq }),
+            None => json!({}),
+        };
+        send_request(&connection.out_tx, pending, next_id, "events_since", params)
+    };
+
+    let mut response = request_events(since).await;
+    if let (Some(seq), Ok(value)) = (since, &response) {
+        // A daemon restart starts its seqs over; replay its whole buffer instead.
+        if latest_seq(value) < seq {
+            response = request_events(Some(0)).await;
+            events.lock().last_seq = 0;
+        }
+    }
+
+    let mut state = events.lock();
+    state.primed = true;
+    let held = state.held.take().unwrap_or_default();
+    let mut warning = None;
+    // Daemons without `events_since` simply don't replay.
+    if let Ok(value) = response {
+        if since.is_none() {
+            state.last_seq = state.last_seq.max(latest_seq(&value));
+        } else {
+            if value
+                .get("truncated")
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
             {
-                connected_for_writer.store(false, Ordering::SeqCst);
-                let mut pending = pending_for_writer.lock().await;
-                for (_, sender) in pending.drain() {
-                    let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
-                }
-                break;
+                warning = Some("Some remote events were missed while disconnected".to_string());
+            }
+            let replayed = value
+                .get("events")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for message in &replayed {
+                deliver_sequenced(app, &mut state, message);
             }
         }
-    });
+    }
+    for message in &held {
+        deliver_sequenced(app, &mut state, message);
+    }
+    warning
+}
+
+fn latest_seq(response: &Value) -> u64 {
+    response
+        .get("latestSeq")
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+async fn fail_pending(pending: &Mutex<PendingMap>) {
+    let mut pending = pending.lock().await;
+    for (_, sender) in pending.drain() {
+        let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
+    }
+}
 
-    let app_for_reader = app.clone();
-    let read_task = tokio::spawn(async move {
-        read_loop(
-            app_for_reader,
+fn spawn_stream_tasks<S>(
+    app: AppHandle,
+    stream: S,
+    out_rx: mpsc::UnboundedReceiver<String>,
+    pending: &Arc<Mutex<PendingMap>>,
+    events: &Arc<EventCursor>,
+    closed: &Arc<Notify>,
+) -> Vec<JoinHandle<()>>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, writer) = tokio::io::split(stream);
+    vec![
+        tokio::spawn(write_loop(writer, out_rx, Arc::clone(closed))),
+        tokio::spawn(read_loop(
+            app,
             reader,
-            pending_for_reader,
-            connected_for_reader,
-        )
-        .await;
-    });
+            Arc::clone(pending),
+            Arc::clone(events),
+            Arc::clone(closed),
+        )),
+    ]
+}
 
-    let client = RemoteBackend {
-        inner: Arc::new(RemoteBackendInner {
-            out_tx,
-            pending,
-            next_id: AtomicU64::new(1),
-            connected,
-        }),
+fn tls_server_name(address: &str) -> Result<ServerName<'static>, String> {
+    let host = match address.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|ch| ch.is_ascii_digit()) => host,
+        _ => address,
     };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    ServerName::try_from(host.to_string())
+        .map_err(|err| format!("Invalid remote backend host `{host}`: {err}"))
+}
 
-    if let Some(token) = token {
-        client
-            .call("auth", json!({ "token": token }))
-            .await
-            .map(|_| ())?;
+/// Parses a SHA-256 certificate fingerprint written as hex, optionally separated
+/// by colons or spaces (the format `openssl x509 -fingerprint -sha256` prints).
+fn parse_fingerprint(value: &str) -> Result<Vec<u8>, String> {
+    let trimmed = value.trim();
+    let trimmed = trimmed
+        .strip_prefix("sha256:")
+        .or_else(|| trimmed.strip_prefix("SHA256:"))
+        .unwrap_or(trimmed);
+    let hex: String = trimmed
+        .chars()
+        .filter(|ch| !matches!(ch, ':' | ' ' | '-'))
+        .collect();
+    // Checked before slicing so multi-byte characters can't split a char boundary.
+    if !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err("TLS fingerprint contains non-hex characters".to_string());
     }
+    if hex.len() != 64 {
+        return Err("TLS fingerprint must be a SHA-256 hash (64 hex digits)".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&hex[index..index + 2], 16)
+                .map_err(|_| "TLS fingerprint contains non-hex characters".to_string())
+        })
+        .collect()
+}
+
+fn build_tls_config(fingerprint: Option<&str>) -> Result<Arc<rustls::ClientConfig>, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?;
+    let fingerprint = fingerprint
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(parse_fingerprint)
+        .transpose()?;
+    let config = match fingerprint {
+        Some(fingerprint) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier {
+                fingerprint,
+                provider,
+            }))
+            .with_no_client_auth(),
+        None => {
+            let roots = rustls::RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+            builder.with_root_certificates(roots).with_no_client_auth()
+        }
+    };
+    Ok(Arc::new(config))
+}
+
+/// Accepts exactly one server certificate, identified by the SHA-256 of its DER
+/// encoding. Chain and hostname checks are skipped on purpose: the daemon usually
+/// runs with a self-signed certificate and the pin is the trust anchor.
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: Vec<u8>,
+    provider: Arc<CryptoProvider>,
+}
 
-    {
-        let mut guard = state.remote_backend.lock().await;
-        *guard = Some(client.clone());
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let digest = Sha256::digest(end_entity.as_ref());
+        if digest.as_slice() == self.fingerprint.as_slice() {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "remote backend certificate does not match the pinned fingerprint".to_string(),
+            ))
+        }
     }
 
-    drop((write_task, read_task));
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
 
-    Ok(client)
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+async fn write_loop<W>(
+    mut writer: W,
+    mut out_rx: mpsc::UnboundedReceiver<String>,
+    closed: Arc<Notify>,
+) where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = out_rx.recv().await {
+        if writer.write_all(message.as_bytes()).await.is_err()
+            || writer.write_all(b"\n").await.is_err()
+        {
+            break;
+        }
+    }
+    closed.notify_one();
+}
+
+async fn ws_write_loop<S>(
+    mut sink: S,
+    mut out_rx: mpsc::UnboundedReceiver<String>,
+    closed: Arc<Notify>,
+) where
+    S: futures_util::Sink<Message> + Unpin,
+{
+    while let Some(message) = out_rx.recv().await {
+        if sink.send(Message::Text(message)).await.is_err() {
+            break;
+        }
+    }
+    closed.notify_one();
 }
 
-async fn read_loop(
+async fn read_loop<R>(
     app: AppHandle,
-    reader: tokio::net::tcp::OwnedReadHalf,
+    reader: R,
     pending: Arc<Mutex<PendingMap>>,
-    connected: Arc<AtomicBool>,
-) {
+    events: Arc<EventCursor>,
+    closed: Arc<Notify>,
+) where
+    R: AsyncRead + Unpin,
+{
     let mut lines = BufReader::new(reader).lines();
-
     while let Ok(Some(line)) = lines.next_line().await {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+        handle_incoming_message(&app, &pending, &events, &line).await;
+    }
+    closed.notify_one();
+}
+
+async fn ws_read_loop<S>(
+    app: AppHandle,
+    mut source: S,
+    pending: Arc<Mutex<PendingMap>>,
+    events: Arc<EventCursor>,
+    closed: Arc<Notify>,
+) where
+    S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    while let Some(Ok(message)) = source.next().await {
+        match message {
+            Message::Text(text) => handle_incoming_message(&app, &pending, &events, &text).await,
+            Message::Close(_) => break,
+            _ => {}
         }
+    }
+    closed.notify_one();
+}
 
-        let message: Value = match serde_json::from_str(trimmed) {
-            Ok(value) => value,
-            Err(_) => continue,
+async fn handle_incoming_message(
+    app: &AppHandle,
+    pending: &Mutex<PendingMap>,
+    events: &EventCursor,
+    line: &str,
+) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return;
+    }
+
+    let message: Value = match serde_json::from_str(trimmed) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+
+    if let Some(id) = message.get("id").and_then(|value| value.as_u64()) {
+        let sender = pending.lock().await.remove(&id);
+        let Some(sender) = sender else {
+            return;
         };
 
-        if let Some(id) = message.get("id").and_then(|value| value.as_u64()) {
-            let sender = pending.lock().await.remove(&id);
-            let Some(sender) = sender else {
-                continue;
-            };
+        if let Some(error) = message.get("error") {
+            let err_message = error
+                .get("message")
+                .and_then(|value| value.as_str())
+                .unwrap_or("remote error");
+            let _ = sender.send(Err(err_message.to_string()));
+            return;
+        }
 
-            if let Some(error) = message.get("error") {
-                let err_message = error
-                    .get("message")
-                    .and_then(|value| value.as_str())
-                    .unwrap_or("remote error");
-                let _ = sender.send(Err(err_message.to_string()));
-                continue;
-            }
+        let result = message.get("result").cloned().unwrap_or(Value::Null);
+        let _ = sender.send(Ok(result));
+        return;
+    }
 
-            let result = message.get("result").cloned().unwrap_or(Value::Null);
-            let _ = sender.send(Ok(result));
-            continue;
+    let mut state = events.lock();
+    if let Some(held) = state.held.as_mut() {
+        held.push(message);
+        return;
+    }
+    deliver_sequenced(app, &mut state, &message);
+}
+
+/// Emits a daemon notification unless its seq shows it was already delivered.
+fn deliver_sequenced(app: &AppHandle, state: &mut EventCursorState, message: &Value) {
+    if let Some(seq) = message.get("seq").and_then(Value::as_u64) {
+        if seq <= state.last_seq {
+            return;
         }
+        state.last_seq = seq;
+    }
+    dispatch_event(app, message);
+}
 
-        let method = message
-            .get("method")
-            .and_then(|value| value.as_str())
-            .unwrap_or("");
-        if method.is_empty() {
-            continue;
+fn dispatch_event(app: &AppHandle, message: &Value) {
+    let method = message
+        .get("method")
+        .and_then(|value| value.as_str())
+        .unwrap_or("");
+    if method.is_empty() {
+        return;
+    }
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    match method {
+        "app-server-event" => {
+            let _ = app.emit("app-server-event", params);
         }
-        let params = message.get("params").cloned().unwrap_or(Value::Null);
-        match method {
-            "app-server-event" => {
-                let _ = app.emit("app-server-event", params);
-            }
-            "terminal-output" => {
-                let _ = app.emit("terminal-output", params);
-            }
-            "terminal-exit" => {
-                let _ = app.emit("terminal-exit", params);
-            }
-            _ => {}
+        "terminal-output" => {
+            let _ = app.emit("terminal-output", params);
+        }
+        "terminal-exit" => {
+            let _ = app.emit("terminal-exit", params);
         }
+        _ => {}
     }
+}
 
-    connected.store(false, Ordering::SeqCst);
-    let mut pending = pending.lock().await;
-    for (_, sender) in pending.drain() {
-        let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_fingerprint, reconnect_delay, tls_server_name};
+
+    #[test]
+    fn reconnect_delay_backs_off_exponentially_up_to_cap() {
+        assert_eq!(reconnect_delay(1), Duration::from_millis(500));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(4), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(7), Duration::from_secs(30));
+        assert_eq!(reconnect_delay(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parse_fingerprint_accepts_openssl_format() {
+        let colon_separated = (0..32)
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(":");
+        let parsed = parse_fingerprint(&colon_separated).expect("parse fingerprint");
+        assert_eq!(parsed, (0..32).collect::<Vec<u8>>());
+
+        let plain = colon_separated.replace(':', "").to_lowercase();
+        assert_eq!(
+            parse_fingerprint(&format!("sha256:{plain}")).unwrap(),
+            parsed
+        );
+    }
+
+    #[test]
+    fn parse_fingerprint_rejects_wrong_length() {
+        assert!(parse_fingerprint("AB:CD").is_err());
+        assert!(parse_fingerprint(&"zz".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn parse_fingerprint_rejects_non_hex_characters() {
+        let non_ascii = format!("a\u{e9}{}", "a".repeat(61));
+        assert_eq!(non_ascii.len(), 64);
+        assert!(parse_fingerprint(&non_ascii).is_err());
+        assert!(parse_fingerprint(&format!("+f{}", "a".repeat(62))).is_err());
+    }
+
+    #[test]
+    fn tls_server_name_strips_port() {
+        assert!(tls_server_name("build-box.local:4732").is_ok());
+        assert!(tls_server_name("10.0.0.5:4732").is_ok());
+        assert!(tls_server_name("[::1]:4732").is_ok());
     }
 }
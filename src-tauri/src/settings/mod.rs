@@ -1,5 +1,6 @@
 use tauri::{State, Window};
 
+use crate::remote_backend;
 use crate::state::AppState;
 use crate::shared::settings_core::{
     get_app_settings_core, get_codex_config_path_core, update_app_settings_core,
@@ -23,8 +24,17 @@ pub(crate) async fn update_app_settings(
     state: State<'_, AppState>,
     window: Window,
 ) -> Result<AppSettings, String> {
-    let updated =
-        update_app_settings_core(settings, &state.app_settings, &state.settings_path).await?;
+    let previous = state.app_settings.lock().await.clone();
+    let updated = update_app_settings_core(
+        settings,
+        &state.app_settings,
+        &state.settings_path,
+        &state.sessions,
+    )
+    .await?;
+    if remote_backend::connection_settings_changed(&previous, &updated) {
+        *state.remote_backend.lock().await = None;
+    }
     let _ = window::apply_window_appearance(&window, updated.theme.as_str());
     Ok(updated)
 }
@@ -1,4 +1,4 @@
-use serde_json::{json, Map, Value};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -11,10 +11,27 @@ use tokio::time::timeout;
 use tokio::time::Instant;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::EventSink;
+use crate::backend::protocol::{
+    decode_response, AccountLogoutParams, AccountRateLimitsReadParams, AccountReadParams,
+    ApprovalPolicy, AppsListParams, CollaborationModeListParams, ConfigBatchWriteParams,
+    ConfigEdit, ConfigReadParams, ConfigValueWriteParams, LoginCancelParams, LoginCancelResponse,
+    LoginStartParams, LoginStartResponse, McpServerOauthLoginParams, McpServerOauthLoginResponse,
+    McpServerReloadParams, McpServerStatusListParams, ModelListParams, Request, ReviewStartParams,
+    SandboxPolicy, SkillsListParams, ThreadArchiveParams, ThreadCompactParams, ThreadForkParams,
+    ThreadListParams, ThreadLoadedListParams, ThreadReadParams, ThreadResumeParams,
+    ThreadRollbackParams, ThreadSetNameParams, ThreadStartParams, ThreadUnarchiveParams,
+    TurnInterruptParams, TurnStartParams, UserInput,
+};
+use crate::codex::background::{
+    extract_json_value, run_background_prompt, BackgroundPrompt, BackgroundPromptOptions,
+};
 use crate::codex::config as codex_config;
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
+use crate::codex::mcp::{self, McpServerConfig};
 use crate::rules;
 use crate::shared::account::{build_account_response, read_auth_account};
+use crate::shared::git_ui_core;
 use crate::types::WorkspaceEntry;
 
 const LOGIN_START_TIMEOUT: Duration = Duration::from_secs(30);
@@ -67,11 +84,12 @@ pub(crate) async fn start_thread_core(
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({
-        "cwd": session.entry.path,
-        "approvalPolicy": "on-request"
-    });
-    session.send_request("thread/start", params).await
+    session
+        .send(&ThreadStartParams {
+            cwd: session.entry.path.clone(),
+            approval_policy: ApprovalPolicy::OnRequest,
+        })
+        .await
 }
 
 pub(crate) async fn resume_thread_core(
@@ -80,8 +98,7 @@ pub(crate) async fn resume_thread_core(
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id });
-    session.send_request("thread/resume", params).await
+    session.send(&ThreadResumeParams { thread_id }).await
 }
 
 pub(crate) async fn fork_thread_core(
@@ -90,8 +107,7 @@ pub(crate) async fn fork_thread_core(
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id });
-    session.send_request("thread/fork", params).await
+    session.send(&ThreadForkParams { thread_id }).await
 }
 
 pub(crate) async fn list_threads_core(
@@ -102,8 +118,13 @@ pub(crate) async fn list_threads_core(
     sort_key: Option<String>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "cursor": cursor, "limit": limit, "sortKey": sort_key });
-    session.send_request("thread/list", params).await
+    session
+        .send(&ThreadListParams {
+            cursor,
+            limit,
+            sort_key,
+        })
+        .await
 }
 
 pub(crate) async fn list_mcp_server_status_core(
@@ -113,8 +134,98 @@ pub(crate) async fn list_mcp_server_status_core(
     limit: Option<u32>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "cursor": cursor, "limit": limit });
-    session.send_request("mcpServerStatus/list", params).await
+    session
+        .send(&McpServerStatusListParams { cursor, limit })
+        .await
+}
+
+/// Lists the `mcp_servers` tables in the workspace's CODEX_HOME `config.toml`.
+pub(crate) async fn list_mcp_servers_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let codex_home = resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
+    let config = codex_config::read_config_json(Some(codex_home))?;
+    let servers = mcp::servers_from_config(&config)?;
+    Ok(json!({ "servers": servers }))
+}
+
+/// Adds or replaces `[mcp_servers.<name>]` (removes it when `config` is
+/// `None`) and reloads every connected app-server on the same CODEX_HOME.
+pub(crate) async fn write_mcp_server_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+    config: Option<McpServerConfig>,
+) -> Result<Value, String> {
+    let edit = mcp::server_edit(name.trim(), config.as_ref())?;
+    write_codex_config_core(
+        sessions,
+        workspaces,
+        Some(workspace_id.clone()),
+        vec![edit],
+        None,
+    )
+    .await?;
+    let reloaded = reload_mcp_servers_for_home(sessions, workspaces, &workspace_id).await?;
+    Ok(json!({ "ok": true, "reloaded": reloaded }))
+}
+
+pub(crate) async fn reload_mcp_servers_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session.send(&McpServerReloadParams).await
+}
+
+/// Returns how many sessions picked up the change. A session that rejects
+/// the reload keeps its old servers until it restarts.
+async fn reload_mcp_servers_for_home(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<usize, String> {
+    let codex_home = resolve_codex_home_for_workspace_core(workspaces, workspace_id).await?;
+    let default_home = resolve_default_codex_home();
+    let targets: Vec<Arc<WorkspaceSession>> = {
+        let sessions = sessions.lock().await;
+        sessions
+            .values()
+            .filter(|session| {
+                let home = session.codex_home().or(default_home.as_deref());
+                session.is_connected() && home == Some(codex_home.as_path())
+            })
+            .cloned()
+            .collect()
+    };
+    let mut reloaded = 0;
+    for session in targets {
+        if session.call(&McpServerReloadParams).await.is_ok() {
+            reloaded += 1;
+        }
+    }
+    Ok(reloaded)
+}
+
+/// Starts an MCP server OAuth login and returns the URL to open. The result
+/// arrives later as `mcpServer/oauthLogin/completed`.
+pub(crate) async fn mcp_server_oauth_login_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    name: String,
+    scopes: Option<Vec<String>>,
+    timeout_secs: Option<u64>,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let params = McpServerOauthLoginParams {
+        name,
+        scopes: scopes.filter(|scopes| !scopes.is_empty()),
+        timeout_secs,
+    };
+    let response: McpServerOauthLoginResponse = session.call(&params).await?;
+    Ok(json!({ "authorizationUrl": response.authorization_url }))
 }
 
 pub(crate) async fn archive_thread_core(
@@ -123,8 +234,61 @@ pub(crate) async fn archive_thread_core(
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id });
-    session.send_request("thread/archive", params).await
+    session.send(&ThreadArchiveParams { thread_id }).await
+}
+
+pub(crate) async fn unarchive_thread_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session.send(&ThreadUnarchiveParams { thread_id }).await
+}
+
+pub(crate) async fn read_thread_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+    include_turns: bool,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session
+        .send(&ThreadReadParams {
+            thread_id,
+            include_turns,
+        })
+        .await
+}
+
+pub(crate) async fn rollback_thread_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+    num_turns: u32,
+) -> Result<Value, String> {
+    if num_turns == 0 {
+        return Err("numTurns must be at least 1".to_string());
+    }
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session
+        .send(&ThreadRollbackParams {
+            thread_id,
+            num_turns,
+        })
+        .await
+}
+
+pub(crate) async fn list_loaded_threads_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session
+        .send(&ThreadLoadedListParams { cursor, limit })
+        .await
 }
 
 pub(crate) async fn compact_thread_core(
@@ -133,8 +297,7 @@ pub(crate) async fn compact_thread_core(
     thread_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id });
-    session.send_request("thread/compact/start", params).await
+    session.send(&ThreadCompactParams { thread_id }).await
 }
 
 pub(crate) async fn set_thread_name_core(
@@ -144,8 +307,7 @@ pub(crate) async fn set_thread_name_core(
     name: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id, "name": name });
-    session.send_request("thread/name/set", params).await
+    session.send(&ThreadSetNameParams { thread_id, name }).await
 }
 
 pub(crate) async fn send_user_message_core(
@@ -161,26 +323,20 @@ pub(crate) async fn send_user_message_core(
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
     let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
-    let sandbox_policy = match access_mode.as_str() {
-        "full-access" => json!({ "type": "dangerFullAccess" }),
-        "read-only" => json!({ "type": "readOnly" }),
-        _ => json!({
-            "type": "workspaceWrite",
-            "writableRoots": [session.entry.path],
-            "networkAccess": true
-        }),
-    };
+    let sandbox_policy = SandboxPolicy::for_access_mode(&access_mode, &session.entry.path);
 
     let approval_policy = if access_mode == "full-access" {
-        "never"
+        ApprovalPolicy::Never
     } else {
-        "on-request"
+        ApprovalPolicy::OnRequest
     };
 
     let trimmed_text = text.trim();
-    let mut input: Vec<Value> = Vec::new();
+    let mut input: Vec<UserInput> = Vec::new();
     if !trimmed_text.is_empty() {
-        input.push(json!({ "type": "text", "text": trimmed_text }));
+        input.push(UserInput::Text {
+            text: trimmed_text.to_string(),
+        });
     }
     if let Some(paths) = images {
         for path in paths {
@@ -192,9 +348,13 @@ pub(crate) async fn send_user_message_core(
                 || trimmed.starts_with("http://")
                 || trimmed.starts_with("https://")
             {
-                input.push(json!({ "type": "image", "url": trimmed }));
+                input.push(UserInput::Image {
+                    url: trimmed.to_string(),
+                });
             } else {
-                input.push(json!({ "type": "localImage", "path": trimmed }));
+                input.push(UserInput::LocalImage {
+                    path: trimmed.to_string(),
+                });
             }
         }
     }
@@ -202,21 +362,17 @@ pub(crate) async fn send_user_message_core(
         return Err("empty user message".to_string());
     }
 
-    let mut params = Map::new();
-    params.insert("threadId".to_string(), json!(thread_id));
-    params.insert("input".to_string(), json!(input));
-    params.insert("cwd".to_string(), json!(session.entry.path));
-    params.insert("approvalPolicy".to_string(), json!(approval_policy));
-    params.insert("sandboxPolicy".to_string(), json!(sandbox_policy));
-    params.insert("model".to_string(), json!(model));
-    params.insert("effort".to_string(), json!(effort));
-    if let Some(mode) = collaboration_mode {
-        if !mode.is_null() {
-            params.insert("collaborationMode".to_string(), mode);
-        }
-    }
     session
-        .send_request("turn/start", Value::Object(params))
+        .send(&TurnStartParams {
+            thread_id,
+            input,
+            cwd: session.entry.path.clone(),
+            approval_policy,
+            sandbox_policy,
+            model,
+            effort,
+            collaboration_mode: collaboration_mode.filter(|mode| !mode.is_null()),
+        })
         .await
 }
 
@@ -225,9 +381,7 @@ pub(crate) async fn collaboration_mode_list_core(
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    session
-        .send_request("collaborationMode/list", json!({}))
-        .await
+    session.send(&CollaborationModeListParams::default()).await
 }
 
 pub(crate) async fn turn_interrupt_core(
@@ -237,8 +391,21 @@ pub(crate) async fn turn_interrupt_core(
     turn_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id, "turnId": turn_id });
-    session.send_request("turn/interrupt", params).await
+    session
+        .send(&TurnInterruptParams { thread_id, turn_id })
+        .await
+}
+
+/// Cancels the workspace's in-flight app-server requests for `method`; their
+/// callers get a "request canceled" error.
+pub(crate) async fn cancel_app_server_request_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    method: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let cancelled = session.cancel_requests(&method).await;
+    Ok(json!({ "cancelled": cancelled }))
 }
 
 pub(crate) async fn start_review_core(
@@ -249,14 +416,12 @@ pub(crate) async fn start_review_core(
     delivery: Option<String>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let mut params = Map::new();
-    params.insert("threadId".to_string(), json!(thread_id));
-    params.insert("target".to_string(), target);
-    if let Some(delivery) = delivery {
-        params.insert("delivery".to_string(), json!(delivery));
-    }
     session
-        .send_request("review/start", Value::Object(params))
+        .send(&ReviewStartParams {
+            thread_id,
+            target,
+            delivery,
+        })
         .await
 }
 
@@ -265,7 +430,7 @@ pub(crate) async fn model_list_core(
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    session.send_request("model/list", json!({})).await
+    session.send(&ModelListParams::default()).await
 }
 
 pub(crate) async fn account_rate_limits_core(
@@ -273,9 +438,7 @@ pub(crate) async fn account_rate_limits_core(
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    session
-        .send_request("account/rateLimits/read", Value::Null)
-        .await
+    session.send(&AccountRateLimitsReadParams).await
 }
 
 pub(crate) async fn account_read_core(
@@ -288,7 +451,7 @@ pub(crate) async fn account_read_core(
         sessions.get(&workspace_id).cloned()
     };
     let response = if let Some(session) = session {
-        session.send_request("account/read", Value::Null).await.ok()
+        session.send(&AccountReadParams).await.ok()
     } else {
         None
     };
@@ -323,9 +486,7 @@ pub(crate) async fn codex_login_core(
 
     let start = Instant::now();
     let mut cancel_rx = cancel_rx;
-    let mut login_request: Pin<Box<_>> = Box::pin(
-        session.send_request("account/login/start", json!({ "type": "chatgpt" })),
-    );
+    let mut login_request: Pin<Box<_>> = Box::pin(session.send(&LoginStartParams::Chatgpt));
 
     let response = loop {
         match cancel_rx.try_recv() {
@@ -359,19 +520,8 @@ pub(crate) async fn codex_login_core(
         }
     };
 
-    let payload = response.get("result").unwrap_or(&response);
-    let login_id = payload
-        .get("loginId")
-        .or_else(|| payload.get("login_id"))
-        .and_then(Value::as_str)
-        .map(str::to_string)
-        .ok_or_else(|| "missing login id in account/login/start response".to_string())?;
-    let auth_url = payload
-        .get("authUrl")
-        .or_else(|| payload.get("auth_url"))
-        .and_then(Value::as_str)
-        .map(str::to_string)
-        .ok_or_else(|| "missing auth url in account/login/start response".to_string())?;
+    let LoginStartResponse { login_id, auth_url } =
+        decode_response(LoginStartParams::METHOD, response.clone())?;
 
     {
         let mut cancels = codex_login_cancels.lock().await;
@@ -409,20 +559,9 @@ pub(crate) async fn codex_login_cancel_core(
         }
         CodexLoginCancelState::LoginId(login_id) => {
             let session = get_session_clone(sessions, &workspace_id).await?;
-            let response = session
-                .send_request(
-                    "account/login/cancel",
-                    json!({
-                        "loginId": login_id,
-                    }),
-                )
-                .await?;
-
-            let payload = response.get("result").unwrap_or(&response);
-            let status = payload
-                .get("status")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
+            let response = session.send(&LoginCancelParams { login_id }).await?;
+            let LoginCancelResponse { status } =
+                decode_response(LoginCancelParams::METHOD, response.clone())?;
             let canceled = status.eq_ignore_ascii_case("canceled");
 
             Ok(json!({
@@ -434,13 +573,45 @@ pub(crate) async fn codex_login_cancel_core(
     }
 }
 
+/// Signs the workspace's CODEX_HOME out. Without a running app-server the
+/// stored `auth.json` is removed directly, which is what Codex does itself.
+pub(crate) async fn codex_logout_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    if let Some(CodexLoginCancelState::PendingStart(cancel_tx)) =
+        codex_login_cancels.lock().await.remove(&workspace_id)
+    {
+        let _ = cancel_tx.send(());
+    }
+
+    if let Some(session) = connected_session(sessions, &workspace_id).await {
+        session.call(&AccountLogoutParams).await?;
+        return Ok(json!({ "loggedOut": true }));
+    }
+
+    let codex_home = resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
+    match std::fs::remove_file(codex_home.join("auth.json")) {
+        Ok(()) => Ok(json!({ "loggedOut": true, "source": "file" })),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            Ok(json!({ "loggedOut": false, "source": "file" }))
+        }
+        Err(err) => Err(format!("Failed to remove auth.json: {err}")),
+    }
+}
+
 pub(crate) async fn skills_list_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "cwd": session.entry.path });
-    session.send_request("skills/list", params).await
+    session
+        .send(&SkillsListParams {
+            cwd: session.entry.path.clone(),
+        })
+        .await
 }
 
 pub(crate) async fn apps_list_core(
@@ -450,8 +621,69 @@ pub(crate) async fn apps_list_core(
     limit: Option<u32>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "cursor": cursor, "limit": limit });
-    session.send_request("app/list", params).await
+    session.send(&AppsListParams { cursor, limit }).await
+}
+
+fn build_commit_message_prompt(diff: &str) -> String {
+    format!(
+        "Generate a concise git commit message for the following changes. \
+Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
+Keep the summary line under 72 characters. \
+Only output the commit message, nothing else.\n\n\
+Changes:\n{diff}"
+    )
+}
+
+pub(crate) async fn get_commit_message_prompt_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<String, String> {
+    let diff = git_ui_core::get_workspace_diff_core(workspaces, workspace_id).await?;
+    if diff.trim().is_empty() {
+        return Err("No changes to generate commit message for".to_string());
+    }
+    Ok(build_commit_message_prompt(&diff))
+}
+
+pub(crate) async fn generate_commit_message_core<E: EventSink>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    event_sink: &E,
+    workspace_id: String,
+) -> Result<String, String> {
+    let prompt = get_commit_message_prompt_core(workspaces, &workspace_id).await?;
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let commit_message =
+        run_background_prompt(&session, event_sink, BackgroundPrompt::new(prompt)).await?;
+    if commit_message.is_empty() {
+        return Err("No commit message was generated".to_string());
+    }
+    Ok(commit_message)
+}
+
+pub(crate) async fn run_background_prompt_core<E: EventSink>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: &E,
+    workspace_id: String,
+    prompt: String,
+    options: BackgroundPromptOptions,
+) -> Result<Value, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt is required.".to_string());
+    }
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let expect_json = options.expect_json;
+    let request = options.into_prompt(prompt, &session.entry.path);
+    let text = run_background_prompt(&session, event_sink, request).await?;
+    if text.is_empty() {
+        return Err("The background prompt returned no text".to_string());
+    }
+    if !expect_json {
+        return Ok(json!({ "text": text }));
+    }
+    let value = extract_json_value(&text)
+        .ok_or_else(|| "The background prompt did not return a JSON object".to_string())?;
+    Ok(json!({ "text": text, "json": value }))
 }
 
 pub(crate) async fn respond_to_server_request_core(
@@ -488,6 +720,142 @@ pub(crate) async fn remember_approval_rule_core(
     }))
 }
 
+/// A connected session whose app-server uses the default CODEX_HOME, so its
+/// `config/*` requests see the same `config.toml` as the app settings.
+async fn default_config_session(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+) -> Option<Arc<WorkspaceSession>> {
+    let default_home = resolve_default_codex_home();
+    let sessions = sessions.lock().await;
+    sessions
+        .values()
+        .find(|session| {
+            session.is_connected()
+                && session
+                    .codex_home()
+                    .is_none_or(|home| default_home.as_deref() == Some(home))
+        })
+        .cloned()
+}
+
+async fn connected_session(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: &str,
+) -> Option<Arc<WorkspaceSession>> {
+    let sessions = sessions.lock().await;
+    sessions
+        .get(workspace_id)
+        .filter(|session| session.is_connected())
+        .cloned()
+}
+
+/// Reads the effective Codex config. Without a workspace, any session on the
+/// default CODEX_HOME answers; with no session at all `config.toml` is parsed
+/// directly and the response is marked `"source": "file"`.
+pub(crate) async fn read_codex_config_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: Option<String>,
+    include_layers: bool,
+) -> Result<Value, String> {
+    let session = match workspace_id.as_deref() {
+        Some(workspace_id) => connected_session(sessions, workspace_id).await,
+        None => default_config_session(sessions).await,
+    };
+    if let Some(session) = session {
+        let cwd = workspace_id.is_some().then(|| session.entry.path.clone());
+        return session
+            .call(&ConfigReadParams {
+                include_layers,
+                cwd,
+            })
+            .await;
+    }
+
+    let codex_home = match workspace_id {
+        Some(workspace_id) => {
+            Some(resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?)
+        }
+        None => None,
+    };
+    let config = codex_config::read_config_json(codex_home)?;
+    Ok(json!({ "config": config, "origins": {}, "source": "file" }))
+}
+
+/// Sends `edits` as one `config/value/write`, or a `config/batchWrite` when
+/// there are several, so Codex validates them before touching the file.
+/// `Ok(None)` means this app-server predates the config methods.
+async fn write_config_via_session(
+    session: &WorkspaceSession,
+    edits: &[ConfigEdit],
+    expected_version: Option<String>,
+) -> Result<Option<Value>, String> {
+    if let [edit] = edits {
+        return session
+            .call_if_supported(&ConfigValueWriteParams {
+                edit: edit.clone(),
+                expected_version,
+            })
+            .await;
+    }
+    session
+        .call_if_supported(&ConfigBatchWriteParams {
+            edits: edits.to_vec(),
+            expected_version,
+        })
+        .await
+}
+
+/// Writes `edits` through `session` and falls back to editing `config.toml` in
+/// `codex_home` only when there is no session or it lacks the config methods.
+/// Edits Codex rejects are returned as errors, never written to the file.
+async fn write_config_edits(
+    session: Option<Arc<WorkspaceSession>>,
+    codex_home: Option<PathBuf>,
+    edits: Vec<ConfigEdit>,
+    expected_version: Option<String>,
+) -> Result<Value, String> {
+    if let Some(session) = session {
+        if let Some(result) = write_config_via_session(&session, &edits, expected_version).await? {
+            return Ok(result);
+        }
+    }
+    codex_config::write_config_edits(codex_home, &edits)?;
+    Ok(json!({ "status": "ok", "source": "file" }))
+}
+
+/// Writes edits to the default CODEX_HOME's config, through any session on it.
+pub(crate) async fn write_default_codex_config_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    edits: Vec<ConfigEdit>,
+) -> Result<Value, String> {
+    let session = default_config_session(sessions).await;
+    write_config_edits(session, None, edits, None).await
+}
+
+/// Writes config edits through the app-server, falling back to editing
+/// `config.toml` directly when no session is connected or it doesn't support
+/// config writes.
+pub(crate) async fn write_codex_config_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: Option<String>,
+    edits: Vec<ConfigEdit>,
+    expected_version: Option<String>,
+) -> Result<Value, String> {
+    if edits.is_empty() {
+        return Err("No config edits to write".to_string());
+    }
+    let (session, codex_home) = match workspace_id.as_deref() {
+        Some(workspace_id) => (
+            connected_session(sessions, workspace_id).await,
+            Some(resolve_codex_home_for_workspace_core(workspaces, workspace_id).await?),
+        ),
+        None => (default_config_session(sessions).await, None),
+    };
+    write_config_edits(session, codex_home, edits, expected_version).await
+}
+
 pub(crate) async fn get_config_model_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -0,0 +1,3082 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::git_utils::{
+    checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path, image_mime_type,
+    list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
+};
+use crate::shared::process_core::tokio_command;
+use crate::types::{
+    AppSettings, BranchInfo, GitBlameHunk, GitCommitDiff, GitConflictFile, GitConflictStrategy,
+    GitFileDiff, GitFileHistoryEntry, GitFileStatus, GitHubIssue, GitHubIssuesResponse,
+    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff, GitHubPullRequestsResponse,
+    GitLogResponse, GitRefDiffResponse, GitStashEntry, WorkspaceEntry,
+};
+use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
+
+const INDEX_SKIP_WORKTREE_FLAG: u16 = 0x4000;
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+const MAX_TEXT_DIFF_BYTES: usize = 2 * 1024 * 1024;
+const MAX_REF_DIFF_COMMITS: usize = 200;
+
+fn encode_image_base64(data: &[u8]) -> Option<String> {
+    if data.len() > MAX_IMAGE_BYTES {
+        return None;
+    }
+    Some(STANDARD.encode(data))
+}
+
+fn blob_to_base64(blob: git2::Blob) -> Option<String> {
+    if blob.size() > MAX_IMAGE_BYTES {
+        return None;
+    }
+    encode_image_base64(blob.content())
+}
+
+fn read_image_base64(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_IMAGE_BYTES as u64 {
+        return None;
+    }
+    let data = fs::read(path).ok()?;
+    encode_image_base64(&data)
+}
+
+fn bytes_look_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|byte| *byte == 0)
+}
+
+fn split_lines_preserving_newlines(content: &str) -> Vec<String> {
+    if content.is_empty() {
+        return Vec::new();
+    }
+    content
+        .split_inclusive('\n')
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn blob_to_lines(blob: git2::Blob<'_>) -> Option<Vec<String>> {
+    if blob.size() > MAX_TEXT_DIFF_BYTES || blob.is_binary() {
+        return None;
+    }
+    let content = String::from_utf8_lossy(blob.content());
+    Some(split_lines_preserving_newlines(content.as_ref()))
+}
+
+fn read_text_lines(path: &Path) -> Option<Vec<String>> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_TEXT_DIFF_BYTES as u64 {
+        return None;
+    }
+    let data = fs::read(path).ok()?;
+    if bytes_look_binary(&data) {
+        return None;
+    }
+    let content = String::from_utf8_lossy(&data);
+    Some(split_lines_preserving_newlines(content.as_ref()))
+}
+
+async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let output = tokio_command(git_bin)
+        .args(args)
+        .current_dir(repo_root)
+        .env("PATH", git_env_path())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let detail = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    if detail.is_empty() {
+        return Err("Git command failed.".to_string());
+    }
+    Err(detail.to_string())
+}
+
+fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
+    let target = normalize_git_path(path).trim().to_string();
+    if target.is_empty() {
+        return Vec::new();
+    }
+
+    let repo = match Repository::open(repo_root) {
+        Ok(repo) => repo,
+        Err(_) => return vec![target],
+    };
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+
+    let statuses = match repo.statuses(Some(&mut status_options)) {
+        Ok(statuses) => statuses,
+        Err(_) => return vec![target],
+    };
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        if !(status.contains(Status::WT_RENAMED) || status.contains(Status::INDEX_RENAMED)) {
+            continue;
+        }
+        let delta = entry.index_to_workdir().or_else(|| entry.head_to_index());
+        let Some(delta) = delta else {
+            continue;
+        };
+        let (Some(old_path), Some(new_path)) =
+            (delta.old_file().path(), delta.new_file().path())
+        else {
+            continue;
+        };
+        let old_path = normalize_git_path(old_path.to_string_lossy().as_ref());
+        let new_path = normalize_git_path(new_path.to_string_lossy().as_ref());
+        if old_path != target && new_path != target {
+            continue;
+        }
+        if old_path == new_path || new_path.is_empty() {
+            return vec![target];
+        }
+        let mut result = Vec::new();
+        if !old_path.is_empty() {
+            result.push(old_path);
+        }
+        if !new_path.is_empty() && !result.contains(&new_path) {
+            result.push(new_path);
+        }
+        return if result.is_empty() { vec![target] } else { result };
+    }
+
+    vec![target]
+}
+
+fn parse_upstream_ref(name: &str) -> Option<(String, String)> {
+    let trimmed = name.strip_prefix("refs/remotes/").unwrap_or(name);
+    let mut parts = trimmed.splitn(2, '/');
+    let remote = parts.next()?;
+    let branch = parts.next()?;
+    if remote.is_empty() || branch.is_empty() {
+        return None;
+    }
+    Some((remote.to_string(), branch.to_string()))
+}
+
+fn upstream_remote_and_branch(repo_root: &Path) -> Result<Option<(String, String)>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(_) => return Ok(None),
+    };
+    if !head.is_branch() {
+        return Ok(None);
+    }
+    let branch_name = match head.shorthand() {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let branch = repo
+        .find_branch(branch_name, BranchType::Local)
+        .map_err(|e| e.to_string())?;
+    let upstream_branch = match branch.upstream() {
+        Ok(upstream) => upstream,
+        Err(_) => return Ok(None),
+    };
+    let upstream_ref = upstream_branch.get();
+    let upstream_name = upstream_ref
+        .name()
+        .or_else(|| upstream_ref.shorthand());
+    Ok(upstream_name.and_then(parse_upstream_ref))
+}
+
+async fn push_with_upstream(repo_root: &Path) -> Result<(), String> {
+    let upstream = upstream_remote_and_branch(repo_root)?;
+    if let Some((remote, branch)) = upstream {
+        // Refresh remote-tracking refs before push so ahead/behind state is current
+        // and we can surface pull/sync requirements before attempting the push.
+        // This is best-effort because some setups intentionally allow push but not fetch.
+        let _ = run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
+        let refspec = format!("HEAD:{branch}");
+        return run_git_command(
+            repo_root,
+            &["push", remote.as_str(), refspec.as_str()],
+        )
+        .await;
+    }
+    run_git_command(repo_root, &["push"]).await
+}
+
+async fn fetch_with_default_remote(repo_root: &Path) -> Result<(), String> {
+    let upstream = upstream_remote_and_branch(repo_root)?;
+    if let Some((remote, _)) = upstream {
+        return run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
+    }
+    run_git_command(repo_root, &["fetch", "--prune"]).await
+}
+
+async fn pull_with_default_strategy(repo_root: &Path) -> Result<(), String> {
+    fn autostash_unsupported(lower: &str) -> bool {
+        lower.contains("unknown option") && lower.contains("autostash")
+    }
+
+    fn needs_reconcile_strategy(lower: &str) -> bool {
+        lower.contains("need to specify how to reconcile divergent branches")
+            || lower.contains("you have divergent branches")
+    }
+
+    // Respect user/repo git config first, while still allowing dirty worktrees.
+    match run_git_command(repo_root, &["pull", "--autostash"]).await {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let lower = err.to_lowercase();
+            if autostash_unsupported(&lower) {
+                match run_git_command(repo_root, &["pull"]).await {
+                    Ok(()) => Ok(()),
+                    Err(no_autostash_err) => {
+                        let no_autostash_lower = no_autostash_err.to_lowercase();
+                        if needs_reconcile_strategy(&no_autostash_lower) {
+                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                        }
+                        Err(no_autostash_err)
+                    }
+                }
+            } else if needs_reconcile_strategy(&lower) {
+                match run_git_command(repo_root, &["pull", "--no-rebase", "--autostash"]).await {
+                    Ok(()) => Ok(()),
+                    Err(merge_err) => {
+                        let merge_lower = merge_err.to_lowercase();
+                        if autostash_unsupported(&merge_lower) {
+                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                        }
+                        Err(merge_err)
+                    }
+                }
+            } else {
+                Err(err)
+            }
+        }
+    }
+}
+
+fn status_for_index(status: Status) -> Option<&'static str> {
+    if status.contains(Status::INDEX_NEW) {
+        Some("A")
+    } else if status.contains(Status::INDEX_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::INDEX_DELETED) {
+        Some("D")
+    } else if status.contains(Status::INDEX_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::INDEX_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+fn status_for_workdir(status: Status) -> Option<&'static str> {
+    if status.contains(Status::WT_NEW) {
+        Some("A")
+    } else if status.contains(Status::WT_MODIFIED) {
+        Some("M")
+    } else if status.contains(Status::WT_DELETED) {
+        Some("D")
+    } else if status.contains(Status::WT_RENAMED) {
+        Some("R")
+    } else if status.contains(Status::WT_TYPECHANGE) {
+        Some("T")
+    } else {
+        None
+    }
+}
+
+fn status_for_delta(status: git2::Delta) -> &'static str {
+    match status {
+        git2::Delta::Added => "A",
+        git2::Delta::Modified => "M",
+        git2::Delta::Deleted => "D",
+        git2::Delta::Renamed => "R",
+        git2::Delta::Typechange => "T",
+        _ => "M",
+    }
+}
+
+fn build_combined_diff(diff: &git2::Diff) -> String {
+    let mut combined_diff = String::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path());
+        let Some(path) = path else {
+            continue;
+        };
+        let patch = match git2::Patch::from_diff(diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        if !combined_diff.is_empty() {
+            combined_diff.push_str("\n\n");
+        }
+        combined_diff.push_str(&format!("=== {} ===\n", path.display()));
+        combined_diff.push_str(&content);
+    }
+    combined_diff
+}
+
+fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let head_tree = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok());
+
+    let mut options = DiffOptions::new();
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let diff = match head_tree.as_ref() {
+        Some(tree) => repo
+            .diff_tree_to_index(Some(tree), Some(&index), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+        None => repo
+            .diff_tree_to_index(None, Some(&index), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+    };
+    let combined_diff = build_combined_diff(&diff);
+    if !combined_diff.trim().is_empty() {
+        return Ok(combined_diff);
+    }
+
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = match head_tree.as_ref() {
+        Some(tree) => repo
+            .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+            .map_err(|e| e.to_string())?,
+        None => repo
+            .diff_tree_to_workdir_with_index(None, Some(&mut options))
+            .map_err(|e| e.to_string())?,
+    };
+    Ok(build_combined_diff(&diff))
+}
+
+fn github_repo_from_path(path: &Path) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| e.to_string())?;
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin".to_string()
+    } else {
+        remotes
+            .iter()
+            .flatten()
+            .next()
+            .unwrap_or("")
+            .to_string()
+    };
+    if name.is_empty() {
+        return Err("No git remote configured.".to_string());
+    }
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    let remote_url = remote
+        .url()
+        .ok_or("Remote has no URL configured.")?;
+    parse_github_repo(remote_url).ok_or("Remote is not a GitHub repository.".to_string())
+}
+
+fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
+    let mut entries = Vec::new();
+    let mut current_lines: Vec<&str> = Vec::new();
+    let mut current_old_path: Option<String> = None;
+    let mut current_new_path: Option<String> = None;
+    let mut current_status: Option<String> = None;
+
+    let finalize = |lines: &Vec<&str>,
+                        old_path: &Option<String>,
+                        new_path: &Option<String>,
+                        status: &Option<String>,
+                        results: &mut Vec<GitHubPullRequestDiff>| {
+        if lines.is_empty() {
+            return;
+        }
+        let diff_text = lines.join("\n");
+        if diff_text.trim().is_empty() {
+            return;
+        }
+        let status_value = status.clone().unwrap_or_else(|| "M".to_string());
+        let path = if status_value == "D" {
+            old_path.clone().unwrap_or_default()
+        } else {
+            new_path.clone().or_else(|| old_path.clone()).unwrap_or_default()
+        };
+        if path.is_empty() {
+            return;
+        }
+        results.push(GitHubPullRequestDiff {
+            path: normalize_git_path(&path),
+            status: status_value,
+            diff: diff_text,
+        });
+    };
+
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            finalize(
+                &current_lines,
+                &current_old_path,
+                &current_new_path,
+                &current_status,
+                &mut entries,
+            );
+            current_lines = vec![line];
+            current_old_path = None;
+            current_new_path = None;
+            current_status = None;
+
+            let rest = line.trim_start_matches("diff --git ").trim();
+            let mut parts = rest.split_whitespace();
+            let old_part = parts.next().unwrap_or("").trim_start_matches("a/");
+            let new_part = parts.next().unwrap_or("").trim_start_matches("b/");
+            if !old_part.is_empty() {
+                current_old_path = Some(old_part.to_string());
+            }
+            if !new_part.is_empty() {
+                current_new_path = Some(new_part.to_string());
+            }
+            continue;
+        }
+        if line.starts_with("new file mode ") {
+            current_status = Some("A".to_string());
+        } else if line.starts_with("deleted file mode ") {
+            current_status = Some("D".to_string());
+        } else if line.starts_with("rename from ") {
+            current_status = Some("R".to_string());
+            let path = line.trim_start_matches("rename from ").trim();
+            if !path.is_empty() {
+                current_old_path = Some(path.to_string());
+            }
+        } else if line.starts_with("rename to ") {
+            current_status = Some("R".to_string());
+            let path = line.trim_start_matches("rename to ").trim();
+            if !path.is_empty() {
+                current_new_path = Some(path.to_string());
+            }
+        }
+        current_lines.push(line);
+    }
+
+    finalize(
+        &current_lines,
+        &current_old_path,
+        &current_new_path,
+        &current_status,
+        &mut entries,
+    );
+
+    entries
+}
+
+async fn resolve_repo_root(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<PathBuf, String> {
+    let entry = {
+        let workspaces = workspaces.lock().await;
+        workspaces
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?
+    };
+    resolve_git_root(&entry)
+}
+
+async fn read_ignore_whitespace_changes(app_settings: &Mutex<AppSettings>) -> bool {
+    let settings = app_settings.lock().await;
+    settings.git_diff_ignore_whitespace_changes
+}
+
+pub(crate) async fn get_git_status_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+
+    let branch_name = repo
+        .head()
+        .ok()
+        .and_then(|head| head.shorthand().map(|s| s.to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .include_ignored(false);
+
+    let statuses = repo
+        .statuses(Some(&mut status_options))
+        .map_err(|e| e.to_string())?;
+
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo.index().ok();
+
+    let mut files = Vec::new();
+    let mut staged_files = Vec::new();
+    let mut unstaged_files = Vec::new();
+    let mut conflicted_files = Vec::new();
+    let mut total_additions = 0i64;
+    let mut total_deletions = 0i64;
+    for entry in statuses.iter() {
+        let path = entry.path().unwrap_or("");
+        if path.is_empty() {
+            continue;
+        }
+        if let Some(index) = index.as_ref() {
+            if let Some(entry) = index.get_path(Path::new(path), 0) {
+                if entry.flags_extended & INDEX_SKIP_WORKTREE_FLAG != 0 {
+                    continue;
+                }
+            }
+        }
+        let status = entry.status();
+        let normalized_path = normalize_git_path(path);
+        if status.is_conflicted() {
+            let conflicted = GitFileStatus {
+                path: normalized_path,
+                status: "U".to_string(),
+                additions: 0,
+                deletions: 0,
+            };
+            files.push(conflicted.clone());
+            conflicted_files.push(conflicted);
+            continue;
+        }
+        let include_index = status.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        );
+        let include_workdir = status.intersects(
+            Status::WT_NEW
+                | Status::WT_MODIFIED
+                | Status::WT_DELETED
+                | Status::WT_RENAMED
+                | Status::WT_TYPECHANGE,
+        );
+        let mut combined_additions = 0i64;
+        let mut combined_deletions = 0i64;
+
+        if include_index {
+            let (additions, deletions) =
+                diff_stats_for_path(&repo, head_tree.as_ref(), path, true, false)
+                    .unwrap_or((0, 0));
+            if let Some(status_str) = status_for_index(status) {
+                staged_files.push(GitFileStatus {
+                    path: normalized_path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        if include_workdir {
+            let (additions, deletions) =
+                diff_stats_for_path(&repo, head_tree.as_ref(), path, false, true)
+                    .unwrap_or((0, 0));
+            if let Some(status_str) = status_for_workdir(status) {
+                unstaged_files.push(GitFileStatus {
+                    path: normalized_path.clone(),
+                    status: status_str.to_string(),
+                    additions,
+                    deletions,
+                });
+            }
+            combined_additions += additions;
+            combined_deletions += deletions;
+            total_additions += additions;
+            total_deletions += deletions;
+        }
+
+        if include_index || include_workdir {
+            let status_str = status_for_workdir(status)
+                .or_else(|| status_for_index(status))
+                .unwrap_or("--");
+            files.push(GitFileStatus {
+                path: normalized_path,
+                status: status_str.to_string(),
+                additions: combined_additions,
+                deletions: combined_deletions,
+            });
+        }
+    }
+
+    Ok(json!({
+        "branchName": branch_name,
+        "files": files,
+        "stagedFiles": staged_files,
+        "unstagedFiles": unstaged_files,
+        "conflictedFiles": conflicted_files,
+        "operation": repository_operation(&repo),
+        "totalAdditions": total_additions,
+        "totalDeletions": total_deletions,
+    }))
+}
+
+pub(crate) async fn stage_git_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    // If libgit2 reports a rename, we want a single UI action to stage both the
+    // old + new paths so the change actually moves to the staged section.
+    for path in action_paths_for_file(&repo_root, &path) {
+        run_git_command(&repo_root, &["add", "-A", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn stage_git_all_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    run_git_command(&repo_root, &["add", "-A"]).await
+}
+
+pub(crate) async fn unstage_git_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    for path in action_paths_for_file(&repo_root, &path) {
+        run_git_command(&repo_root, &["restore", "--staged", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn revert_git_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    for path in action_paths_for_file(&repo_root, &path) {
+        if run_git_command(
+            &repo_root,
+            &["restore", "--staged", "--worktree", "--", &path],
+        )
+        .await
+        .is_ok()
+        {
+            continue;
+        }
+        run_git_command(&repo_root, &["clean", "-f", "--", &path]).await?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn revert_git_all_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    run_git_command(&repo_root, &["restore", "--staged", "--worktree", "--", "."]).await?;
+    run_git_command(&repo_root, &["clean", "-f", "-d"]).await
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HunkAction {
+    Stage,
+    Unstage,
+    Revert,
+}
+
+impl HunkAction {
+    /// Staging picks from the unstaged changes and unstaging from the staged
+    /// ones; reverting discards what the combined diff shows.
+    fn patch_side(self) -> PatchSide {
+        match self {
+            HunkAction::Stage => PatchSide::Unstaged,
+            HunkAction::Unstage => PatchSide::Staged,
+            HunkAction::Revert => PatchSide::Combined,
+        }
+    }
+
+    /// Unstage and revert apply the hunk backwards.
+    fn is_reverse(self) -> bool {
+        self != HunkAction::Stage
+    }
+}
+
+/// Which two states of a file a patch compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatchSide {
+    /// HEAD to working tree, the `diff` shown by `get_git_diffs`.
+    Combined,
+    /// HEAD to index.
+    Staged,
+    /// Index to working tree.
+    Unstaged,
+}
+
+/// One hunk of a single-file unified diff; `lines` keep their ` `/`-`/`+`/`\`
+/// prefix and trailing newline.
+#[derive(Debug, Clone, PartialEq)]
+struct PatchHunk {
+    old_start: u32,
+    old_lines: u32,
+    new_start: u32,
+    new_lines: u32,
+    lines: Vec<String>,
+}
+
+impl PatchHunk {
+    fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_lines, self.new_start, self.new_lines
+        )
+    }
+
+    /// The same change applied in the other direction.
+    fn reversed(&self) -> PatchHunk {
+        PatchHunk {
+            old_start: self.new_start,
+            old_lines: self.new_lines,
+            new_start: self.old_start,
+            new_lines: self.old_lines,
+            lines: self
+                .lines
+                .iter()
+                .map(|line| match line.as_bytes().first() {
+                    Some(b'+') => format!("-{}", &line[1..]),
+                    Some(b'-') => format!("+{}", &line[1..]),
+                    _ => line.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Parses the ranges out of `@@ -a,b +c,d @@`; anything after the closing
+/// `@@` (the function context) is ignored.
+fn parse_hunk_header(header: &str) -> Option<(u32, u32, u32, u32)> {
+    let ranges = header.trim().strip_prefix("@@ -")?;
+    let (ranges, _) = ranges.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let parse_range = |range: &str| -> Option<(u32, u32)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+/// Finds the hunk with the given header in a single-file patch.
+fn find_patch_hunk(patch: &str, header: &str) -> Option<PatchHunk> {
+    let wanted = parse_hunk_header(header)?;
+    let mut lines = patch.split_inclusive('\n');
+    while let Some(line) = lines.next() {
+        if !line.starts_with("@@") || parse_hunk_header(line) != Some(wanted) {
+            continue;
+        }
+        let (old_start, old_lines, new_start, new_lines) = wanted;
+        let body = lines
+            .take_while(|line| !line.starts_with("@@"))
+            .map(|line| {
+                if line.ends_with('\n') {
+                    line.to_string()
+                } else {
+                    format!("{line}\n")
+                }
+            })
+            .collect();
+        return Some(PatchHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            lines: body,
+        });
+    }
+    None
+}
+
+/// Narrows a hunk to the selected body lines (0-based, `\` markers not
+/// counted), keeping the side the patch is applied to intact. Going forward,
+/// unselected removals stay as context and unselected additions are dropped;
+/// for a hunk that will be `reversed()` it is the other way round, as
+/// `git add -p` does when unstaging.
+fn select_hunk_lines(
+    hunk: &PatchHunk,
+    selected: Option<&[usize]>,
+    reverse: bool,
+) -> Result<PatchHunk, String> {
+    let Some(selected) = selected else {
+        return Ok(hunk.clone());
+    };
+    let (kept, dropped) = if reverse { (b'+', b'-') } else { (b'-', b'+') };
+    let mut lines = Vec::new();
+    let mut index = 0;
+    let mut keep_marker = true;
+    let mut changes = 0;
+    for line in &hunk.lines {
+        if line.starts_with('\\') {
+            if keep_marker {
+                lines.push(line.clone());
+            }
+            continue;
+        }
+        let is_selected = selected.contains(&index);
+        index += 1;
+        keep_marker = true;
+        match line.as_bytes().first() {
+            Some(&prefix) if prefix == dropped && !is_selected => keep_marker = false,
+            Some(&prefix) if prefix == kept && !is_selected => {
+                lines.push(format!(" {}", &line[1..]))
+            }
+            Some(b'+') | Some(b'-') => {
+                changes += 1;
+                lines.push(line.clone());
+            }
+            _ => lines.push(line.clone()),
+        }
+    }
+    if changes == 0 {
+        return Err("No changed lines selected.".to_string());
+    }
+    let count = |prefixes: &[u8]| {
+        lines
+            .iter()
+            .filter(|line| {
+                line.as_bytes()
+                    .first()
+                    .is_some_and(|prefix| prefixes.contains(prefix))
+            })
+            .count() as u32
+    };
+    let old_lines = count(b" -");
+    let new_lines = count(b" +");
+    // Only this hunk is applied, so both sides start where the untouched side
+    // does. A zero-length range names the line before it, so starts shift by
+    // one when either side is empty.
+    let (start, length) = if reverse {
+        (hunk.new_start, hunk.new_lines)
+    } else {
+        (hunk.old_start, hunk.old_lines)
+    };
+    let first_line = if length == 0 { start + 1 } else { start };
+    let start_for = |count: u32| {
+        if count == 0 {
+            first_line - 1
+        } else {
+            first_line
+        }
+    };
+    Ok(PatchHunk {
+        old_start: start_for(old_lines),
+        old_lines,
+        new_start: start_for(new_lines),
+        new_lines,
+        lines,
+    })
+}
+
+fn render_hunk_patch(path: &str, hunk: &PatchHunk) -> String {
+    let mut patch = format!(
+        "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n{}\n",
+        hunk.header()
+    );
+    for line in &hunk.lines {
+        patch.push_str(line);
+    }
+    patch
+}
+
+/// One file's patch between the states `side` names, built the same way as
+/// `get_git_diffs` so hunk headers taken from it match.
+fn file_patch(
+    repo: &Repository,
+    path: &str,
+    side: PatchSide,
+    ignore_whitespace_changes: bool,
+) -> Result<String, String> {
+    let head_tree = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    options
+        .pathspec(path)
+        .disable_pathspec_match(true)
+        .ignore_whitespace_change(ignore_whitespace_changes);
+    let (diff, missing) = match side {
+        PatchSide::Combined => (
+            repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options)),
+            "tracked",
+        ),
+        PatchSide::Staged => (
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options)),
+            "staged",
+        ),
+        PatchSide::Unstaged => (
+            repo.diff_index_to_workdir(None, Some(&mut options)),
+            "unstaged",
+        ),
+    };
+    let diff = diff.map_err(|e| e.to_string())?;
+    let delta = diff
+        .deltas()
+        .next()
+        .ok_or_else(|| format!("No {missing} changes found for {path}."))?;
+    if delta.status() != git2::Delta::Modified {
+        return Err(
+            "Hunks can only be picked from modified files; use the whole-file action instead."
+                .to_string(),
+        );
+    }
+    let mut patch = git2::Patch::from_diff(&diff, 0)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Binary files cannot be changed by hunk.".to_string())?;
+    diff_patch_to_string(&mut patch).map_err(|e| e.to_string())
+}
+
+fn apply_hunk_patch(
+    repo: &Repository,
+    patch: &str,
+    location: git2::ApplyLocation,
+    check_only: bool,
+) -> Result<(), String> {
+    let diff = git2::Diff::from_buffer(patch.as_bytes()).map_err(|e| e.to_string())?;
+    let mut options = git2::ApplyOptions::new();
+    options.check(check_only);
+    repo.apply(&diff, location, Some(&mut options))
+        .map_err(|e| format!("The hunk does not apply cleanly: {}", e.message()))
+}
+
+fn apply_hunk_action(
+    repo_root: &Path,
+    path: &str,
+    hunk: &str,
+    lines: Option<&[usize]>,
+    ignore_whitespace_changes: bool,
+    action: HunkAction,
+) -> Result<(), String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let path = normalize_git_path(path);
+    let file_patch = file_patch(&repo, &path, action.patch_side(), ignore_whitespace_changes)?;
+    let hunk = find_patch_hunk(&file_patch, hunk).ok_or_else(|| {
+        "The hunk no longer matches the working tree; refresh the diff and try again.".to_string()
+    })?;
+    let selected = select_hunk_lines(&hunk, lines, action.is_reverse())?;
+    match action {
+        HunkAction::Stage => apply_hunk_patch(
+            &repo,
+            &render_hunk_patch(&path, &selected),
+            git2::ApplyLocation::Index,
+            false,
+        ),
+        HunkAction::Unstage => apply_hunk_patch(
+            &repo,
+            &render_hunk_patch(&path, &selected.reversed()),
+            git2::ApplyLocation::Index,
+            false,
+        ),
+        HunkAction::Revert => {
+            // Staged changes are reverted in the index too, like `revert_git_file`.
+            let patch = render_hunk_patch(&path, &selected.reversed());
+            let location =
+                if apply_hunk_patch(&repo, &patch, git2::ApplyLocation::Both, true).is_ok() {
+                    git2::ApplyLocation::Both
+                } else {
+                    git2::ApplyLocation::WorkDir
+                };
+            apply_hunk_patch(&repo, &patch, location, false)
+        }
+    }
+}
+
+async fn run_hunk_action(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    path: String,
+    hunk: String,
+    lines: Option<Vec<usize>>,
+    action: HunkAction,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = read_ignore_whitespace_changes(app_settings).await;
+    tokio::task::spawn_blocking(move || {
+        apply_hunk_action(
+            &repo_root,
+            &path,
+            &hunk,
+            lines.as_deref(),
+            ignore_whitespace_changes,
+            action,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Stages one hunk of a modified file, identified by its `@@` header in the
+/// `get_git_diffs` patch (`unstagedDiff` when the file is partly staged).
+/// `lines` narrows it to some of the hunk's body lines.
+pub(crate) async fn stage_git_hunk_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    path: String,
+    hunk: String,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    run_hunk_action(
+        workspaces,
+        app_settings,
+        workspace_id,
+        path,
+        hunk,
+        lines,
+        HunkAction::Stage,
+    )
+    .await
+}
+
+/// Unstages one hunk; its header comes from `stagedDiff` when the file is
+/// partly staged.
+pub(crate) async fn unstage_git_hunk_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    path: String,
+    hunk: String,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    run_hunk_action(
+        workspaces,
+        app_settings,
+        workspace_id,
+        path,
+        hunk,
+        lines,
+        HunkAction::Unstage,
+    )
+    .await
+}
+
+pub(crate) async fn revert_git_hunk_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    path: String,
+    hunk: String,
+    lines: Option<Vec<usize>>,
+) -> Result<(), String> {
+    run_hunk_action(
+        workspaces,
+        app_settings,
+        workspace_id,
+        path,
+        hunk,
+        lines,
+        HunkAction::Revert,
+    )
+    .await
+}
+
+pub(crate) async fn commit_git_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    message: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    run_git_command(&repo_root, &["commit", "-m", &message]).await
+}
+
+pub(crate) async fn push_git_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    push_with_upstream(&repo_root).await
+}
+
+pub(crate) async fn pull_git_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    pull_with_default_strategy(&repo_root).await
+}
+
+pub(crate) async fn fetch_git_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    fetch_with_default_remote(&repo_root).await
+}
+
+pub(crate) async fn sync_git_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    // Pull first, then push (like VSCode sync)
+    pull_with_default_strategy(&repo_root).await?;
+    push_with_upstream(&repo_root).await
+}
+
+pub(crate) async fn list_git_roots_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    depth: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let root = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let depth = depth.unwrap_or(2).clamp(1, 6);
+    Ok(scan_git_roots(&root, depth, 200))
+}
+
+/// Returns the combined diff for a workspace (used by commit message generation).
+pub(crate) async fn get_workspace_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<String, String> {
+    let repo_root = resolve_repo_root(workspaces, workspace_id).await?;
+    collect_workspace_diff(&repo_root)
+}
+
+pub(crate) async fn get_git_diffs_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+) -> Result<Vec<GitFileDiff>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = read_ignore_whitespace_changes(app_settings).await;
+    tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head_tree = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_tree().ok());
+
+        let mut options = DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        options.ignore_whitespace_change(ignore_whitespace_changes);
+
+        let diff = match head_tree.as_ref() {
+            Some(tree) => repo
+                .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
+                .map_err(|e| e.to_string())?,
+            None => repo
+                .diff_tree_to_workdir_with_index(None, Some(&mut options))
+                .map_err(|e| e.to_string())?,
+        };
+
+        let mut results = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let old_path = delta.old_file().path();
+            let new_path = delta.new_file().path();
+            let display_path = new_path.or(old_path);
+            let Some(display_path) = display_path else {
+                continue;
+            };
+            let old_path_str = old_path.map(|path| path.to_string_lossy());
+            let new_path_str = new_path.map(|path| path.to_string_lossy());
+            let display_path_str = display_path.to_string_lossy();
+            let normalized_path = normalize_git_path(&display_path_str);
+            let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+            let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+            let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+            let is_deleted = delta.status() == git2::Delta::Deleted;
+            let is_added = delta.status() == git2::Delta::Added;
+
+            let old_lines = if !is_added {
+                head_tree
+                    .as_ref()
+                    .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                    .and_then(|entry| repo.find_blob(entry.id()).ok())
+                    .and_then(blob_to_lines)
+            } else {
+                None
+            };
+
+            let new_lines = if !is_deleted {
+                match new_path {
+                    Some(path) => {
+                        let full_path = repo_root.join(path);
+                        read_text_lines(&full_path)
+                    }
+                    None => None,
+                }
+            } else {
+                None
+            };
+
+            if is_image {
+                let old_image_data = if !is_added && old_image_mime.is_some() {
+                    head_tree
+                        .as_ref()
+                        .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                        .and_then(|entry| repo.find_blob(entry.id()).ok())
+                        .and_then(blob_to_base64)
+                } else {
+                    None
+                };
+
+                let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                    match new_path {
+                        Some(path) => {
+                            let full_path = repo_root.join(path);
+                            read_image_base64(&full_path)
+                        }
+                        None => None,
+                    }
+                } else {
+                    None
+                };
+
+                results.push(GitFileDiff {
+                    path: normalized_path,
+                    diff: String::new(),
+                    old_lines: None,
+                    new_lines: None,
+                    is_binary: true,
+                    is_image: true,
+                    old_image_data,
+                    new_image_data,
+                    old_image_mime: old_image_mime.map(str::to_string),
+                    new_image_mime: new_image_mime.map(str::to_string),
+                    staged_diff: None,
+                    unstaged_diff: None,
+                });
+                continue;
+            }
+
+            let patch = match git2::Patch::from_diff(&diff, index) {
+                Ok(patch) => patch,
+                Err(_) => continue,
+            };
+            let Some(mut patch) = patch else {
+                continue;
+            };
+            let content = match diff_patch_to_string(&mut patch) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if content.trim().is_empty() {
+                continue;
+            }
+            // Hunk headers in `diff` don't line up with the index when the file
+            // is partly staged, so the two halves are sent for hunk actions.
+            let partly_staged = delta.status() == git2::Delta::Modified
+                && repo.status_file(display_path).is_ok_and(|status| {
+                    status.contains(Status::INDEX_MODIFIED) && status.contains(Status::WT_MODIFIED)
+                });
+            let (staged_diff, unstaged_diff) = if partly_staged {
+                let side_patch = |side| {
+                    file_patch(&repo, &normalized_path, side, ignore_whitespace_changes).ok()
+                };
+                (
+                    side_patch(PatchSide::Staged),
+                    side_patch(PatchSide::Unstaged),
+                )
+            } else {
+                (None, None)
+            };
+            results.push(GitFileDiff {
+                path: normalized_path,
+                diff: content,
+                old_lines,
+                new_lines,
+                is_binary: false,
+                is_image: false,
+                old_image_data: None,
+                new_image_data: None,
+                old_image_mime: None,
+                new_image_mime: None,
+                staged_diff,
+                unstaged_diff,
+            });
+        }
+
+        Ok(results)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub(crate) async fn get_git_log_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    limit: Option<usize>,
+) -> Result<GitLogResponse, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let max_items = limit.unwrap_or(40);
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push_head().map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TIME)
+        .map_err(|e| e.to_string())?;
+
+    let mut total = 0usize;
+    for oid_result in revwalk {
+        oid_result.map_err(|e| e.to_string())?;
+        total += 1;
+    }
+
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push_head().map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TIME)
+        .map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for oid_result in revwalk.take(max_items) {
+        let oid = oid_result.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        entries.push(commit_to_entry(commit));
+    }
+
+    let mut ahead = 0usize;
+    let mut behind = 0usize;
+    let mut ahead_entries = Vec::new();
+    let mut behind_entries = Vec::new();
+    let mut upstream = None;
+
+    if let Ok(head) = repo.head() {
+        if head.is_branch() {
+            if let Some(branch_name) = head.shorthand() {
+                if let Ok(branch) = repo.find_branch(branch_name, BranchType::Local) {
+                    if let Ok(upstream_branch) = branch.upstream() {
+                        let upstream_ref = upstream_branch.get();
+                        upstream = upstream_ref
+                            .shorthand()
+                            .map(|name| name.to_string())
+                            .or_else(|| upstream_ref.name().map(|name| name.to_string()));
+                        if let (Some(head_oid), Some(upstream_oid)) =
+                            (head.target(), upstream_ref.target())
+                        {
+                            let (ahead_count, behind_count) = repo
+                                .graph_ahead_behind(head_oid, upstream_oid)
+                                .map_err(|e| e.to_string())?;
+                            ahead = ahead_count;
+                            behind = behind_count;
+
+                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                            revwalk.push(head_oid).map_err(|e| e.to_string())?;
+                            revwalk.hide(upstream_oid).map_err(|e| e.to_string())?;
+                            revwalk
+                                .set_sorting(Sort::TIME)
+                                .map_err(|e| e.to_string())?;
+                            for oid_result in revwalk.take(max_items) {
+                                let oid = oid_result.map_err(|e| e.to_string())?;
+                                let commit =
+                                    repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                ahead_entries.push(commit_to_entry(commit));
+                            }
+
+                            let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+                            revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
+                            revwalk.hide(head_oid).map_err(|e| e.to_string())?;
+                            revwalk
+                                .set_sorting(Sort::TIME)
+                                .map_err(|e| e.to_string())?;
+                            for oid_result in revwalk.take(max_items) {
+                                let oid = oid_result.map_err(|e| e.to_string())?;
+                                let commit =
+                                    repo.find_commit(oid).map_err(|e| e.to_string())?;
+                                behind_entries.push(commit_to_entry(commit));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(GitLogResponse {
+        total,
+        entries,
+        ahead,
+        behind,
+        ahead_entries,
+        behind_entries,
+        upstream,
+    })
+}
+
+/// Per-file diffs between two trees, with the text/image/binary handling
+/// shared by the commit, stash and ref views. `parent_tree` of `None` diffs
+/// against an empty tree.
+fn diff_trees_to_entries(
+    repo: &Repository,
+    parent_tree: Option<&git2::Tree>,
+    commit_tree: &git2::Tree,
+    ignore_whitespace_changes: bool,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let mut options = DiffOptions::new();
+    options.ignore_whitespace_change(ignore_whitespace_changes);
+    let diff = repo
+        .diff_tree_to_tree(parent_tree, Some(commit_tree), Some(&mut options))
+        .map_err(|e| e.to_string())?;
+
+    let mut results = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let old_path = delta.old_file().path();
+        let new_path = delta.new_file().path();
+        let display_path = new_path.or(old_path);
+        let Some(display_path) = display_path else {
+            continue;
+        };
+        let old_path_str = old_path.map(|path| path.to_string_lossy());
+        let new_path_str = new_path.map(|path| path.to_string_lossy());
+        let display_path_str = display_path.to_string_lossy();
+        let normalized_path = normalize_git_path(&display_path_str);
+        let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+        let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+        let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+        let is_deleted = delta.status() == git2::Delta::Deleted;
+        let is_added = delta.status() == git2::Delta::Added;
+
+        let old_lines = if !is_added {
+            parent_tree
+                .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                .and_then(|entry| repo.find_blob(entry.id()).ok())
+                .and_then(blob_to_lines)
+        } else {
+            None
+        };
+
+        let new_lines = if !is_deleted {
+            new_path
+                .and_then(|path| commit_tree.get_path(path).ok())
+                .and_then(|entry| repo.find_blob(entry.id()).ok())
+                .and_then(blob_to_lines)
+        } else {
+            None
+        };
+
+        if is_image {
+            let old_image_data = if !is_added && old_image_mime.is_some() {
+                parent_tree
+                    .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
+                    .and_then(|entry| repo.find_blob(entry.id()).ok())
+                    .and_then(blob_to_base64)
+            } else {
+                None
+            };
+
+            let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                new_path
+                    .and_then(|path| commit_tree.get_path(path).ok())
+                    .and_then(|entry| repo.find_blob(entry.id()).ok())
+                    .and_then(blob_to_base64)
+            } else {
+                None
+            };
+
+            results.push(GitCommitDiff {
+                path: normalized_path,
+                status: status_for_delta(delta.status()).to_string(),
+                diff: String::new(),
+                old_lines: None,
+                new_lines: None,
+                is_binary: true,
+                is_image: true,
+                old_image_data,
+                new_image_data,
+                old_image_mime: old_image_mime.map(str::to_string),
+                new_image_mime: new_image_mime.map(str::to_string),
+            });
+            continue;
+        }
+
+        let patch = match git2::Patch::from_diff(&diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        results.push(GitCommitDiff {
+            path: normalized_path,
+            status: status_for_delta(delta.status()).to_string(),
+            diff: content,
+            old_lines,
+            new_lines,
+            is_binary: false,
+            is_image: false,
+            old_image_data: None,
+            new_image_data: None,
+            old_image_mime: None,
+            new_image_mime: None,
+        });
+    }
+
+    Ok(results)
+}
+
+pub(crate) async fn get_git_commit_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    sha: String,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = read_ignore_whitespace_changes(app_settings).await;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit
+        .parent(0)
+        .ok()
+        .and_then(|parent| parent.tree().ok());
+    diff_trees_to_entries(
+        &repo,
+        parent_tree.as_ref(),
+        &commit_tree,
+        ignore_whitespace_changes,
+    )
+}
+
+fn resolve_commit<'repo>(
+    repo: &'repo Repository,
+    spec: &str,
+) -> Result<git2::Commit<'repo>, String> {
+    repo.revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Unknown ref `{spec}`: {}", e.message()))
+}
+
+fn ref_diff(
+    repo_root: &Path,
+    base: &str,
+    head: &str,
+    merge_base: bool,
+    ignore_whitespace_changes: bool,
+) -> Result<GitRefDiffResponse, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let base_commit = resolve_commit(&repo, base)?;
+    let head_commit = resolve_commit(&repo, head)?;
+    let merge_base_oid = if merge_base {
+        Some(
+            repo.merge_base(base_commit.id(), head_commit.id())
+                .map_err(|_| format!("`{base}` and `{head}` have no common ancestor."))?,
+        )
+    } else {
+        None
+    };
+    let from_commit = match merge_base_oid {
+        Some(oid) => repo.find_commit(oid).map_err(|e| e.to_string())?,
+        None => base_commit.clone(),
+    };
+    let from_tree = from_commit.tree().map_err(|e| e.to_string())?;
+    let head_tree = head_commit.tree().map_err(|e| e.to_string())?;
+    let files = diff_trees_to_entries(
+        &repo,
+        Some(&from_tree),
+        &head_tree,
+        ignore_whitespace_changes,
+    )?;
+
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push(head_commit.id()).map_err(|e| e.to_string())?;
+    revwalk.hide(base_commit.id()).map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+        .map_err(|e| e.to_string())?;
+    let mut commits = Vec::new();
+    let mut total_commits = 0usize;
+    for oid_result in revwalk {
+        let oid = oid_result.map_err(|e| e.to_string())?;
+        total_commits += 1;
+        if commits.len() < MAX_REF_DIFF_COMMITS {
+            let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+            commits.push(commit_to_entry(commit));
+        }
+    }
+
+    Ok(GitRefDiffResponse {
+        base_sha: base_commit.id().to_string(),
+        head_sha: head_commit.id().to_string(),
+        merge_base_sha: merge_base_oid.map(|oid| oid.to_string()),
+        files,
+        commits,
+        total_commits,
+    })
+}
+
+pub(crate) async fn get_git_ref_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    base: String,
+    head: String,
+    merge_base: bool,
+) -> Result<GitRefDiffResponse, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = read_ignore_whitespace_changes(app_settings).await;
+    ref_diff(
+        &repo_root,
+        &base,
+        &head,
+        merge_base,
+        ignore_whitespace_changes,
+    )
+}
+
+pub(crate) async fn get_git_remote_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Option<String>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin".to_string()
+    } else {
+        remotes
+            .iter()
+            .flatten()
+            .next()
+            .unwrap_or("")
+            .to_string()
+    };
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
+    Ok(remote.url().map(|url| url.to_string()))
+}
+
+pub(crate) async fn get_github_issues_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitHubIssuesResponse, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo_name = github_repo_from_path(&repo_root)?;
+
+    let output = tokio_command("gh")
+        .args([
+            "issue",
+            "list",
+            "--repo",
+            &repo_name,
+            "--limit",
+            "50",
+            "--json",
+            "number,title,url,updatedAt",
+        ])
+        .current_dir(&repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+
+    let issues: Vec<GitHubIssue> =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+
+    let search_query = format!("repo:{repo_name} is:issue is:open");
+    let search_query = search_query.replace(' ', "+");
+    let total = match tokio_command("gh")
+        .args([
+            "api",
+            &format!("/search/issues?q={search_query}"),
+            "--jq",
+            ".total_count",
+        ])
+        .current_dir(&repo_root)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<usize>()
+            .unwrap_or(issues.len()),
+        _ => issues.len(),
+    };
+
+    Ok(GitHubIssuesResponse { total, issues })
+}
+
+pub(crate) async fn get_github_pull_requests_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitHubPullRequestsResponse, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo_name = github_repo_from_path(&repo_root)?;
+
+    let output = tokio_command("gh")
+        .args([
+            "pr",
+            "list",
+            "--repo",
+            &repo_name,
+            "--state",
+            "open",
+            "--limit",
+            "50",
+            "--json",
+            "number,title,url,updatedAt,createdAt,body,headRefName,baseRefName,isDraft,author",
+        ])
+        .current_dir(&repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+
+    let pull_requests: Vec<GitHubPullRequest> =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+
+    let search_query = format!("repo:{repo_name} is:pr is:open");
+    let search_query = search_query.replace(' ', "+");
+    let total = match tokio_command("gh")
+        .args([
+            "api",
+            &format!("/search/issues?q={search_query}"),
+            "--jq",
+            ".total_count",
+        ])
+        .current_dir(&repo_root)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse::<usize>()
+            .unwrap_or(pull_requests.len()),
+        _ => pull_requests.len(),
+    };
+
+    Ok(GitHubPullRequestsResponse {
+        total,
+        pull_requests,
+    })
+}
+
+pub(crate) async fn get_github_pull_request_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    pr_number: u64,
+) -> Result<Vec<GitHubPullRequestDiff>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo_name = github_repo_from_path(&repo_root)?;
+
+    let output = tokio_command("gh")
+        .args([
+            "pr",
+            "diff",
+            &pr_number.to_string(),
+            "--repo",
+            &repo_name,
+            "--color",
+            "never",
+        ])
+        .current_dir(&repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+
+    let diff_text = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_pr_diff(&diff_text))
+}
+
+pub(crate) async fn get_github_pull_request_comments_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    pr_number: u64,
+) -> Result<Vec<GitHubPullRequestComment>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo_name = github_repo_from_path(&repo_root)?;
+
+    let comments_endpoint =
+        format!("/repos/{repo_name}/issues/{pr_number}/comments?per_page=30");
+    let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
+
+    let output = tokio_command("gh")
+        .args(["api", &comments_endpoint, "--jq", jq_filter])
+        .current_dir(&repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+
+    let comments: Vec<GitHubPullRequestComment> =
+        serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+
+    Ok(comments)
+}
+
+pub(crate) async fn list_git_branches_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Value, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut branches = Vec::new();
+    let refs = repo
+        .branches(Some(BranchType::Local))
+        .map_err(|e| e.to_string())?;
+    for branch_result in refs {
+        let (branch, _) = branch_result.map_err(|e| e.to_string())?;
+        let name = branch.name().ok().flatten().unwrap_or("").to_string();
+        if name.is_empty() {
+            continue;
+        }
+        let last_commit = branch
+            .get()
+            .target()
+            .and_then(|oid| repo.find_commit(oid).ok())
+            .map(|commit| commit.time().seconds())
+            .unwrap_or(0);
+        branches.push(BranchInfo { name, last_commit });
+    }
+    branches.sort_by(|a, b| b.last_commit.cmp(&a.last_commit));
+    Ok(json!({ "branches": branches }))
+}
+
+pub(crate) async fn checkout_git_branch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    checkout_branch(&repo, &name).map_err(|e| e.to_string())
+}
+
+pub(crate) async fn create_git_branch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let head = repo.head().map_err(|e| e.to_string())?;
+    let target = head.peel_to_commit().map_err(|e| e.to_string())?;
+    repo.branch(&name, &target, false)
+        .map_err(|e| e.to_string())?;
+    checkout_branch(&repo, &name).map_err(|e| e.to_string())
+}
+
+/// Stash messages read `WIP on <branch>: <sha> <subject>` or
+/// `On <branch>: <message>`; a detached HEAD shows as `(no branch)`.
+fn parse_stash_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(": ")?;
+    (branch != "(no branch)").then(|| branch.to_string())
+}
+
+fn stash_ids(repo: &mut Repository) -> Result<Vec<(String, git2::Oid)>, String> {
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|_, message, oid| {
+        stashes.push((message.to_string(), *oid));
+        true
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(stashes)
+}
+
+fn list_stashes(repo_root: &Path) -> Result<Vec<GitStashEntry>, String> {
+    let mut repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let stashes = stash_ids(&mut repo)?;
+    stashes
+        .into_iter()
+        .enumerate()
+        .map(|(index, (message, oid))| {
+            let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+            Ok(GitStashEntry {
+                index,
+                branch: parse_stash_branch(&message),
+                message,
+                sha: oid.to_string(),
+                timestamp: commit.time().seconds(),
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn list_git_stashes_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Vec<GitStashEntry>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    list_stashes(&repo_root)
+}
+
+pub(crate) async fn create_git_stash_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    message: Option<String>,
+    include_untracked: bool,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let before = list_stashes(&repo_root)?.len();
+    let mut args = vec!["stash", "push"];
+    if include_untracked {
+        args.push("--include-untracked");
+    }
+    let message = message.filter(|message| !message.trim().is_empty());
+    if let Some(message) = message.as_deref() {
+        args.extend(["-m", message]);
+    }
+    run_git_command(&repo_root, &args).await?;
+    // `git stash push` exits 0 without creating a stash when nothing changed.
+    if list_stashes(&repo_root)?.len() == before {
+        return Err("No local changes to stash.".to_string());
+    }
+    Ok(())
+}
+
+/// Finds where the stash with `sha` currently sits in the stash list. Other
+/// clients can push or drop stashes in the meantime, so a list position the
+/// UI saw earlier may now name a different entry.
+fn find_stash(repo: &mut Repository, sha: &str) -> Result<(usize, git2::Oid), String> {
+    let oid = git2::Oid::from_str(sha).map_err(|_| format!("Invalid stash id: {sha}"))?;
+    let index = stash_ids(repo)?
+        .into_iter()
+        .position(|(_, stash_oid)| stash_oid == oid)
+        .ok_or_else(|| "Stash no longer exists. Refresh the stash list.".to_string())?;
+    Ok((index, oid))
+}
+
+async fn run_stash_command(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    command: &str,
+    sha: String,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let mut repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let (index, _) = find_stash(&mut repo, &sha)?;
+    let stash_ref = format!("stash@{{{index}}}");
+    run_git_command(&repo_root, &["stash", command, &stash_ref]).await
+}
+
+pub(crate) async fn apply_git_stash_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    sha: String,
+) -> Result<(), String> {
+    run_stash_command(workspaces, workspace_id, "apply", sha).await
+}
+
+pub(crate) async fn pop_git_stash_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    sha: String,
+) -> Result<(), String> {
+    run_stash_command(workspaces, workspace_id, "pop", sha).await
+}
+
+pub(crate) async fn drop_git_stash_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    sha: String,
+) -> Result<(), String> {
+    run_stash_command(workspaces, workspace_id, "drop", sha).await
+}
+
+fn stash_diff_entries(
+    repo_root: &Path,
+    sha: &str,
+    ignore_whitespace_changes: bool,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let mut repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let (_, oid) = find_stash(&mut repo, sha)?;
+    let stash = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let stash_tree = stash.tree().map_err(|e| e.to_string())?;
+    let base_tree = stash.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let mut entries = diff_trees_to_entries(
+        &repo,
+        base_tree.as_ref(),
+        &stash_tree,
+        ignore_whitespace_changes,
+    )?;
+    // A stash made with --include-untracked keeps those files in a third
+    // parent whose tree holds only them.
+    if let Some(untracked_tree) = stash.parent(2).ok().and_then(|parent| parent.tree().ok()) {
+        entries.extend(diff_trees_to_entries(
+            &repo,
+            None,
+            &untracked_tree,
+            ignore_whitespace_changes,
+        )?);
+    }
+    Ok(entries)
+}
+
+/// The changes a stash would bring back, relative to the commit it was made on.
+pub(crate) async fn get_git_stash_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    sha: String,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = read_ignore_whitespace_changes(app_settings).await;
+    stash_diff_entries(&repo_root, &sha, ignore_whitespace_changes)
+}
+
+fn repository_operation(repo: &Repository) -> Option<&'static str> {
+    match repo.state() {
+        git2::RepositoryState::Clean => None,
+        git2::RepositoryState::Merge => Some("merge"),
+        git2::RepositoryState::Revert | git2::RepositoryState::RevertSequence => Some("revert"),
+        git2::RepositoryState::CherryPick | git2::RepositoryState::CherryPickSequence => {
+            Some("cherryPick")
+        }
+        git2::RepositoryState::Bisect => Some("bisect"),
+        git2::RepositoryState::Rebase
+        | git2::RepositoryState::RebaseInteractive
+        | git2::RepositoryState::RebaseMerge
+        | git2::RepositoryState::ApplyMailboxOrRebase => Some("rebase"),
+        git2::RepositoryState::ApplyMailbox => Some("am"),
+    }
+}
+
+fn conflict_path(conflict: &git2::IndexConflict) -> Option<String> {
+    conflict
+        .our
+        .as_ref()
+        .or(conflict.their.as_ref())
+        .or(conflict.ancestor.as_ref())
+        .map(|entry| normalize_git_path(&String::from_utf8_lossy(&entry.path)))
+}
+
+fn conflict_side_blob(
+    repo: &Repository,
+    entry: Option<&git2::IndexEntry>,
+) -> Result<Option<Vec<u8>>, String> {
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+    let blob = repo.find_blob(entry.id).map_err(|e| e.to_string())?;
+    Ok(Some(blob.content().to_vec()))
+}
+
+/// Returns the side's text, or `(None, true)` when it is binary.
+fn conflict_side_text(
+    repo: &Repository,
+    entry: Option<&git2::IndexEntry>,
+) -> (Option<String>, bool) {
+    let Some(entry) = entry else {
+        return (None, false);
+    };
+    let Ok(blob) = repo.find_blob(entry.id) else {
+        return (None, false);
+    };
+    if blob.is_binary() {
+        return (None, true);
+    }
+    match String::from_utf8(blob.content().to_vec()) {
+        Ok(text) => (Some(text), false),
+        Err(_) => (None, true),
+    }
+}
+
+fn list_conflicts(repo: &Repository) -> Result<Vec<GitConflictFile>, String> {
+    let index = repo.index().map_err(|e| e.to_string())?;
+    if !index.has_conflicts() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for conflict in index.conflicts().map_err(|e| e.to_string())? {
+        let conflict = conflict.map_err(|e| e.to_string())?;
+        let Some(path) = conflict_path(&conflict) else {
+            continue;
+        };
+        let (base, base_binary) = conflict_side_text(repo, conflict.ancestor.as_ref());
+        let (ours, ours_binary) = conflict_side_text(repo, conflict.our.as_ref());
+        let (theirs, theirs_binary) = conflict_side_text(repo, conflict.their.as_ref());
+        let is_binary = base_binary || ours_binary || theirs_binary;
+        files.push(GitConflictFile {
+            path,
+            base: if is_binary { None } else { base },
+            ours: if is_binary { None } else { ours },
+            theirs: if is_binary { None } else { theirs },
+            is_binary,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn resolve_conflict(
+    repo: &Repository,
+    path: &str,
+    strategy: GitConflictStrategy,
+    content: Option<String>,
+) -> Result<(), String> {
+    let workdir = repo
+        .workdir()
+        .ok_or("Repository has no working directory.")?
+        .to_path_buf();
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    let conflict = index
+        .conflicts()
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .find(|conflict| conflict_path(conflict).as_deref() == Some(path))
+        .ok_or_else(|| format!("{path} has no merge conflict."))?;
+    let resolved = match strategy {
+        GitConflictStrategy::Ours => conflict_side_blob(repo, conflict.our.as_ref())?,
+        GitConflictStrategy::Theirs => conflict_side_blob(repo, conflict.their.as_ref())?,
+        GitConflictStrategy::Content => Some(
+            content
+                .ok_or("Resolved content is required for the content strategy.")?
+                .into_bytes(),
+        ),
+    };
+
+    let relative = Path::new(path);
+    let target = workdir.join(relative);
+    // Adding or removing the path drops its conflict stages from the index.
+    match resolved {
+        Some(data) => {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&target, data).map_err(|e| e.to_string())?;
+            index.add_path(relative).map_err(|e| e.to_string())?;
+        }
+        None => {
+            if target.exists() {
+                fs::remove_file(&target).map_err(|e| e.to_string())?;
+            }
+            index.remove_path(relative).map_err(|e| e.to_string())?;
+        }
+    }
+    index.write().map_err(|e| e.to_string())
+}
+
+pub(crate) async fn get_git_conflicts_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Vec<GitConflictFile>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    list_conflicts(&repo)
+}
+
+pub(crate) async fn resolve_git_conflict_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+    strategy: GitConflictStrategy,
+    content: Option<String>,
+) -> Result<(), String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    resolve_conflict(&repo, &normalize_git_path(&path), strategy, content)
+}
+
+fn blame_file(repo_root: &Path, path: &str) -> Result<Vec<GitBlameHunk>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let relative = Path::new(path);
+    let blame = repo.blame_file(relative, None).map_err(|e| e.to_string())?;
+    // Blame the working copy on top of HEAD so edited lines show as uncommitted.
+    let blame = match fs::read(repo_root.join(relative)) {
+        Ok(contents) => blame.blame_buffer(&contents).map_err(|e| e.to_string())?,
+        Err(_) => blame,
+    };
+
+    let mut commits: HashMap<git2::Oid, Option<git2::Commit>> = HashMap::new();
+    let mut hunks = Vec::new();
+    for hunk in blame.iter() {
+        let oid = hunk.final_commit_id();
+        let commit = commits
+            .entry(oid)
+            .or_insert_with(|| {
+                if oid.is_zero() {
+                    None
+                } else {
+                    repo.find_commit(oid).ok()
+                }
+            })
+            .clone();
+        let orig_path = hunk
+            .path()
+            .map(|orig| normalize_git_path(&orig.to_string_lossy()))
+            .filter(|orig| orig != path);
+        let (sha, summary, author, timestamp) = match commit.as_ref() {
+            Some(commit) => (
+                commit.id().to_string(),
+                commit.summary().unwrap_or("").to_string(),
+                commit.author().name().unwrap_or("").to_string(),
+                commit.time().seconds(),
+            ),
+            None => (String::new(), String::new(), String::new(), 0),
+        };
+        hunks.push(GitBlameHunk {
+            start_line: hunk.final_start_line(),
+            line_count: hunk.lines_in_hunk(),
+            sha,
+            summary,
+            author,
+            timestamp,
+            orig_path,
+            uncommitted: commit.is_none(),
+        });
+    }
+    Ok(hunks)
+}
+
+fn tree_blob_id(tree: &git2::Tree, path: &str) -> Option<git2::Oid> {
+    tree.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+/// Finds where `path` in `commit` was renamed from, relative to `parent`.
+fn renamed_from(
+    repo: &Repository,
+    parent: &git2::Tree,
+    tree: &git2::Tree,
+    path: &str,
+) -> Result<Option<String>, String> {
+    let mut diff = repo
+        .diff_tree_to_tree(Some(parent), Some(tree), None)
+        .map_err(|e| e.to_string())?;
+    diff.find_similar(None).map_err(|e| e.to_string())?;
+    Ok(diff.deltas().find_map(|delta| {
+        if delta.status() != git2::Delta::Renamed {
+            return None;
+        }
+        let new_path = delta.new_file().path()?.to_string_lossy();
+        if normalize_git_path(&new_path) != path {
+            return None;
+        }
+        let old_path = delta.old_file().path()?.to_string_lossy();
+        Some(normalize_git_path(&old_path))
+    }))
+}
+
+fn file_history(
+    repo_root: &Path,
+    path: &str,
+    limit: usize,
+) -> Result<Vec<GitFileHistoryEntry>, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push_head().map_err(|e| e.to_string())?;
+    revwalk
+        .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+        .map_err(|e| e.to_string())?;
+
+    let mut current_path = path.to_string();
+    let mut entries = Vec::new();
+    for oid_result in revwalk {
+        if entries.len() >= limit {
+            break;
+        }
+        let oid = oid_result.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let tree = commit.tree().map_err(|e| e.to_string())?;
+        let Some(blob_id) = tree_blob_id(&tree, &current_path) else {
+            continue;
+        };
+        let parent_trees = commit
+            .parents()
+            .map(|parent| parent.tree().map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Like `git log`, skip commits that match any parent for this path.
+        if parent_trees
+            .iter()
+            .any(|parent| tree_blob_id(parent, &current_path) == Some(blob_id))
+        {
+            continue;
+        }
+
+        let entry_path = current_path.clone();
+        let in_parent = parent_trees
+            .iter()
+            .any(|parent| tree_blob_id(parent, &current_path).is_some());
+        let status = if in_parent {
+            "M"
+        } else if let Some(parent) = parent_trees.first() {
+            match renamed_from(&repo, parent, &tree, &current_path)? {
+                Some(old_path) => {
+                    current_path = old_path;
+                    "R"
+                }
+                None => "A",
+            }
+        } else {
+            "A"
+        };
+        let log_entry = commit_to_entry(commit);
+        entries.push(GitFileHistoryEntry {
+            sha: log_entry.sha,
+            summary: log_entry.summary,
+            author: log_entry.author,
+            timestamp: log_entry.timestamp,
+            path: entry_path,
+            status: status.to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+pub(crate) async fn get_git_blame_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+) -> Result<Vec<GitBlameHunk>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let path = normalize_git_path(&path);
+    tokio::task::spawn_blocking(move || blame_file(&repo_root, &path))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub(crate) async fn get_git_file_history_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<GitFileHistoryEntry>, String> {
+    let repo_root = resolve_repo_root(workspaces, &workspace_id).await?;
+    let path = normalize_git_path(&path);
+    let limit = limit.unwrap_or(40);
+    tokio::task::spawn_blocking(move || file_history(&repo_root, &path, limit))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn create_temp_repo() -> (PathBuf, Repository) {
+        let root = std::env::temp_dir().join(format!(
+            "codex-monitor-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&root).expect("create temp repo root");
+        let repo = Repository::init(&root).expect("init repo");
+        (root, repo)
+    }
+
+    #[test]
+    fn collect_workspace_diff_prefers_staged_changes() {
+        let (root, repo) = create_temp_repo();
+        let file_path = root.join("staged.txt");
+        fs::write(&file_path, "staged\n").expect("write staged file");
+        let mut index = repo.index().expect("index");
+        index.add_path(Path::new("staged.txt")).expect("add path");
+        index.write().expect("write index");
+
+        let diff = collect_workspace_diff(&root).expect("collect diff");
+        assert!(diff.contains("staged.txt"));
+        assert!(diff.contains("staged"));
+    }
+
+    #[test]
+    fn collect_workspace_diff_falls_back_to_workdir() {
+        let (root, _repo) = create_temp_repo();
+        let file_path = root.join("unstaged.txt");
+        fs::write(&file_path, "unstaged\n").expect("write unstaged file");
+
+        let diff = collect_workspace_diff(&root).expect("collect diff");
+        assert!(diff.contains("unstaged.txt"));
+        assert!(diff.contains("unstaged"));
+    }
+
+    #[test]
+    fn action_paths_for_file_expands_renames() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "hello\n").expect("write file");
+
+        let mut index = repo.index().expect("repo index");
+        index
+            .add_path(Path::new("a.txt"))
+            .expect("add path");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig =
+            git2::Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .expect("commit");
+
+        fs::rename(root.join("a.txt"), root.join("b.txt")).expect("rename file");
+
+        // Stage the rename so libgit2 reports it as an INDEX_RENAMED entry.
+        let mut index = repo.index().expect("repo index");
+        index
+            .remove_path(Path::new("a.txt"))
+            .expect("remove old path");
+        index
+            .add_path(Path::new("b.txt"))
+            .expect("add new path");
+        index.write().expect("write index");
+
+        let paths = action_paths_for_file(&root, "b.txt");
+        assert_eq!(paths, vec!["a.txt".to_string(), "b.txt".to_string()]);
+    }
+
+    fn commit_all(repo: &Repository, message: &str) -> git2::Oid {
+        let mut index = repo.index().expect("repo index");
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .expect("add all");
+        index.write().expect("write index");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents = parent.iter().collect::<Vec<_>>();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .expect("commit")
+    }
+
+    fn staged_content(root: &Path, path: &str) -> String {
+        let repo = Repository::open(root).expect("open repo");
+        let index = repo.index().expect("repo index");
+        let entry = index.get_path(Path::new(path), 0).expect("index entry");
+        let blob = repo.find_blob(entry.id).expect("blob");
+        String::from_utf8_lossy(blob.content()).to_string()
+    }
+
+    fn stage_paths(root: &Path, paths: &[&str]) {
+        let repo = Repository::open(root).expect("open repo");
+        let mut index = repo.index().expect("repo index");
+        for path in paths {
+            index.add_path(Path::new(path)).expect("stage path");
+        }
+        index.write().expect("write index");
+    }
+
+    fn workspace_for(root: &Path) -> Mutex<HashMap<String, WorkspaceEntry>> {
+        let entry = WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "repo".to_string(),
+            path: root.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: Default::default(),
+            parent_id: None,
+            worktree: None,
+            settings: Default::default(),
+        };
+        Mutex::new(HashMap::from([(entry.id.clone(), entry)]))
+    }
+
+    fn numbered_lines(count: usize) -> Vec<String> {
+        (1..=count).map(|line| format!("line {line}\n")).collect()
+    }
+
+    #[test]
+    fn select_hunk_lines_keeps_only_chosen_changes() {
+        let hunk = PatchHunk {
+            old_start: 4,
+            old_lines: 3,
+            new_start: 4,
+            new_lines: 3,
+            lines: vec![
+                " a\n".to_string(),
+                "-b\n".to_string(),
+                "+B\n".to_string(),
+                "-c\n".to_string(),
+                "\\ No newline at end of file\n".to_string(),
+                "+C".to_string(),
+            ],
+        };
+        assert_eq!(
+            parse_hunk_header("@@ -4,3 +4,3 @@ fn main() {"),
+            Some((4, 3, 4, 3))
+        );
+        assert_eq!(parse_hunk_header("@@ -1 +0,0 @@"), Some((1, 1, 0, 0)));
+
+        let selected = select_hunk_lines(&hunk, Some(&[2]), false).unwrap();
+        assert_eq!(selected.header(), "@@ -4,3 +4,4 @@");
+        assert_eq!(
+            selected.lines,
+            [" a\n", " b\n", "+B\n", " c\n"]
+                .into_iter()
+                .chain(["\\ No newline at end of file\n"])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(selected.reversed().header(), "@@ -4,4 +4,3 @@");
+        assert_eq!(selected.reversed().lines[2], "-B\n");
+        assert!(select_hunk_lines(&hunk, Some(&[0]), false).is_err());
+        assert_eq!(select_hunk_lines(&hunk, None, false).unwrap(), hunk);
+    }
+
+    #[test]
+    fn select_hunk_lines_in_reverse_keeps_the_new_side() {
+        let hunk = PatchHunk {
+            old_start: 1,
+            old_lines: 3,
+            new_start: 1,
+            new_lines: 4,
+            lines: [" a\n", "-b\n", "+B\n", " c\n", "+d\n"]
+                .map(str::to_string)
+                .to_vec(),
+        };
+        // Taking back only `+d` must leave `B` in place, not restore `b`.
+        let selected = select_hunk_lines(&hunk, Some(&[4]), true).unwrap();
+        assert_eq!(selected.lines, [" a\n", " B\n", " c\n", "+d\n"]);
+        let reversed = selected.reversed();
+        assert_eq!(reversed.header(), "@@ -1,4 +1,3 @@");
+        assert_eq!(reversed.lines[3], "-d\n");
+
+        let selected = select_hunk_lines(&hunk, Some(&[1]), true).unwrap();
+        assert_eq!(selected.lines, [" a\n", "-b\n", " B\n", " c\n", " d\n"]);
+        assert_eq!(selected.reversed().header(), "@@ -1,4 +1,5 @@");
+    }
+
+    #[test]
+    fn hunk_actions_stage_unstage_and_revert_single_hunks() {
+        let (root, repo) = create_temp_repo();
+        let original = numbered_lines(20);
+        fs::write(root.join("notes.txt"), original.concat()).expect("write file");
+        commit_all(&repo, "init");
+
+        let mut edited = original.clone();
+        edited[1] = "line 2 edited\n".to_string();
+        edited[17] = "line 18 edited\n".to_string();
+        fs::write(root.join("notes.txt"), edited.concat()).expect("edit file");
+
+        let patch = file_patch(&repo, "notes.txt", PatchSide::Combined, false).expect("file patch");
+        let headers = patch
+            .lines()
+            .filter(|line| line.starts_with("@@"))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(headers.len(), 2);
+        let run = |header: &str, action| {
+            apply_hunk_action(&root, "notes.txt", header, None, false, action)
+        };
+
+        run(&headers[0], HunkAction::Stage).expect("stage first hunk");
+        let mut expected = original.clone();
+        expected[1] = "line 2 edited\n".to_string();
+        assert_eq!(staged_content(&root, "notes.txt"), expected.concat());
+
+        run(&headers[0], HunkAction::Unstage).expect("unstage first hunk");
+        assert_eq!(staged_content(&root, "notes.txt"), original.concat());
+
+        run(&headers[1], HunkAction::Revert).expect("revert second hunk");
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt")).expect("read file"),
+            expected.concat()
+        );
+
+        let stale = run(&headers[1], HunkAction::Stage);
+        assert!(stale.unwrap_err().contains("refresh"));
+    }
+
+    #[test]
+    fn stage_hunk_lines_stages_part_of_a_hunk() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("list.txt"), "a\nb\nc\n").expect("write file");
+        commit_all(&repo, "init");
+        fs::write(root.join("list.txt"), "a\nB\nc\nd\n").expect("edit file");
+
+        let patch = file_patch(&repo, "list.txt", PatchSide::Combined, false).expect("file patch");
+        let header = patch.lines().find(|line| line.starts_with("@@")).unwrap();
+        // Body: " a", "-b", "+B", " c", "+d"; stage only the appended line.
+        let stage = |path: &str, lines: Option<&[usize]>| {
+            apply_hunk_action(&root, path, header, lines, false, HunkAction::Stage)
+        };
+        stage("list.txt", Some(&[4])).expect("stage appended line");
+        assert_eq!(staged_content(&root, "list.txt"), "a\nb\nc\nd\n");
+
+        fs::write(root.join("new.txt"), "new\n").expect("write untracked file");
+        assert!(stage("new.txt", None).is_err());
+    }
+
+    #[test]
+    fn unstage_and_revert_hunk_lines_take_back_only_the_chosen_change() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("list.txt"), "a\nb\nc\n").expect("write file");
+        commit_all(&repo, "init");
+        fs::write(root.join("list.txt"), "a\nB\nc\nd\n").expect("edit file");
+        let header = |side| {
+            let patch = file_patch(&repo, "list.txt", side, false).expect("file patch");
+            patch
+                .lines()
+                .find(|line| line.starts_with("@@"))
+                .unwrap()
+                .to_string()
+        };
+        let run = |header: &str, lines: &[usize], action| {
+            apply_hunk_action(&root, "list.txt", header, Some(lines), false, action)
+        };
+
+        // Body: " a", "-b", "+B", " c", "+d".
+        let revert_header = header(PatchSide::Combined);
+        run(&revert_header, &[4], HunkAction::Revert).expect("revert appended line");
+        assert_eq!(
+            fs::read_to_string(root.join("list.txt")).expect("read file"),
+            "a\nB\nc\n"
+        );
+
+        fs::write(root.join("list.txt"), "a\nB\nc\nd\n").expect("edit file");
+        stage_paths(&root, &["list.txt"]);
+        run(&header(PatchSide::Staged), &[4], HunkAction::Unstage).expect("unstage appended line");
+        assert_eq!(staged_content(&root, "list.txt"), "a\nB\nc\n");
+        assert_eq!(
+            fs::read_to_string(root.join("list.txt")).expect("read file"),
+            "a\nB\nc\nd\n"
+        );
+    }
+
+    #[test]
+    fn hunk_actions_use_the_staged_and_unstaged_halves_of_a_partly_staged_file() {
+        let (root, repo) = create_temp_repo();
+        let original = numbered_lines(20);
+        fs::write(root.join("notes.txt"), original.concat()).expect("write file");
+        commit_all(&repo, "init");
+
+        // Stage an inserted line near the top, then edit further down.
+        let mut staged = original.clone();
+        staged.insert(1, "inserted\n".to_string());
+        fs::write(root.join("notes.txt"), staged.concat()).expect("edit file");
+        stage_paths(&root, &["notes.txt"]);
+        let mut edited = staged.clone();
+        edited[18] = "line 18 edited\n".to_string();
+        fs::write(root.join("notes.txt"), edited.concat()).expect("edit file");
+
+        let workspaces = workspace_for(&root);
+        let app_settings = Mutex::new(AppSettings::default());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        let diffs = runtime
+            .block_on(get_git_diffs_core(
+                &workspaces,
+                &app_settings,
+                "ws-1".to_string(),
+            ))
+            .expect("diffs");
+        let first_header = |patch: &str| {
+            patch
+                .lines()
+                .find(|line| line.starts_with("@@"))
+                .unwrap()
+                .to_string()
+        };
+        let unstaged = first_header(diffs[0].unstaged_diff.as_deref().expect("unstaged diff"));
+        let staged_hunk = first_header(diffs[0].staged_diff.as_deref().expect("staged diff"));
+        assert_ne!(unstaged, first_header(&diffs[0].diff));
+
+        apply_hunk_action(
+            &root,
+            "notes.txt",
+            &unstaged,
+            None,
+            false,
+            HunkAction::Stage,
+        )
+        .expect("stage the unstaged hunk");
+        assert_eq!(staged_content(&root, "notes.txt"), edited.concat());
+
+        let stale = apply_hunk_action(
+            &root,
+            "notes.txt",
+            &staged_hunk,
+            None,
+            false,
+            HunkAction::Stage,
+        );
+        assert!(stale.is_err());
+    }
+
+    #[test]
+    fn conflicts_are_listed_reported_and_resolved() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "base\n").expect("write a");
+        fs::write(root.join("b.txt"), "base\n").expect("write b");
+        let base = commit_all(&repo, "base");
+        fs::write(root.join("a.txt"), "theirs\n").expect("write a");
+        fs::write(root.join("b.txt"), "theirs\n").expect("write b");
+        let theirs = commit_all(&repo, "theirs");
+        let base_commit = repo.find_commit(base).expect("base commit");
+        repo.reset(base_commit.as_object(), git2::ResetType::Hard, None)
+            .expect("reset");
+        fs::write(root.join("a.txt"), "ours\n").expect("write a");
+        fs::write(root.join("b.txt"), "ours\n").expect("write b");
+        commit_all(&repo, "ours");
+        let theirs = repo.find_annotated_commit(theirs).expect("annotated");
+        repo.merge(&[&theirs], None, None).expect("merge");
+
+        let workspaces = workspace_for(&root);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let id = || "ws-1".to_string();
+            let status = get_git_status_core(&workspaces, id())
+                .await
+                .expect("status");
+            assert_eq!(status["operation"], "merge");
+            assert_eq!(status["conflictedFiles"].as_array().map(Vec::len), Some(2));
+            assert_eq!(status["conflictedFiles"][0]["status"], "U");
+
+            let conflicts = get_git_conflicts_core(&workspaces, id())
+                .await
+                .expect("conflicts");
+            assert_eq!(conflicts.len(), 2);
+            assert_eq!(conflicts[0].path, "a.txt");
+            assert_eq!(conflicts[0].base.as_deref(), Some("base\n"));
+            assert_eq!(conflicts[0].ours.as_deref(), Some("ours\n"));
+            assert_eq!(conflicts[0].theirs.as_deref(), Some("theirs\n"));
+
+            let resolve = |path: &str, strategy, content: Option<&str>| {
+                resolve_git_conflict_core(
+                    &workspaces,
+                    id(),
+                    path.to_string(),
+                    strategy,
+                    content.map(str::to_string),
+                )
+            };
+            assert!(resolve("b.txt", GitConflictStrategy::Content, None)
+                .await
+                .is_err());
+            resolve("a.txt", GitConflictStrategy::Theirs, None)
+                .await
+                .expect("resolve theirs");
+            resolve("b.txt", GitConflictStrategy::Content, Some("merged\n"))
+                .await
+                .expect("resolve content");
+            assert!(resolve("a.txt", GitConflictStrategy::Ours, None)
+                .await
+                .is_err());
+
+            assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "theirs\n");
+            assert_eq!(staged_content(&root, "b.txt"), "merged\n");
+            assert!(get_git_conflicts_core(&workspaces, id())
+                .await
+                .expect("conflicts")
+                .is_empty());
+            let status = get_git_status_core(&workspaces, id())
+                .await
+                .expect("status");
+            assert_eq!(status["conflictedFiles"].as_array().map(Vec::len), Some(0));
+            assert_eq!(status["stagedFiles"].as_array().map(Vec::len), Some(2));
+        });
+    }
+
+    #[test]
+    fn blame_and_file_history_follow_renames() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "one\ntwo\n").expect("write a");
+        let added = commit_all(&repo, "add a");
+        fs::write(root.join("a.txt"), "one\ntwo\nthree\n").expect("edit a");
+        let edited = commit_all(&repo, "edit a");
+        fs::rename(root.join("a.txt"), root.join("b.txt")).expect("rename");
+        let mut index = repo.index().expect("repo index");
+        index.remove_path(Path::new("a.txt")).expect("remove a");
+        index.write().expect("write index");
+        let renamed = commit_all(&repo, "rename to b");
+        fs::write(root.join("b.txt"), "one\ntwo\nthree\nfour\n").expect("edit b");
+        let appended = commit_all(&repo, "edit b");
+        fs::write(root.join("b.txt"), "one\ntwo\nthree\nfour\nfive\n").expect("edit b");
+
+        let history = file_history(&root, "b.txt", 40).expect("history");
+        let shas = history
+            .iter()
+            .map(|entry| entry.sha.clone())
+            .collect::<Vec<_>>();
+        let expected = [appended, renamed, edited, added].map(|oid| oid.to_string());
+        assert_eq!(shas, expected);
+        let changes = history
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.status.as_str()))
+            .collect::<Vec<_>>();
+        let expected_changes = [
+            ("b.txt", "M"),
+            ("b.txt", "R"),
+            ("a.txt", "M"),
+            ("a.txt", "A"),
+        ];
+        assert_eq!(changes, expected_changes);
+        assert_eq!(file_history(&root, "b.txt", 2).expect("history").len(), 2);
+
+        let blame = blame_file(&root, "b.txt").expect("blame");
+        let line_owner = |line: usize| {
+            blame
+                .iter()
+                .find(|hunk| line >= hunk.start_line && line < hunk.start_line + hunk.line_count)
+                .expect("hunk for line")
+        };
+        assert_eq!(line_owner(1).sha, added.to_string());
+        assert_eq!(line_owner(1).orig_path.as_deref(), Some("a.txt"));
+        assert_eq!(line_owner(3).sha, edited.to_string());
+        assert_eq!(line_owner(4).sha, appended.to_string());
+        assert_eq!(line_owner(4).orig_path, None);
+        assert!(line_owner(5).uncommitted);
+        assert!(line_owner(5).sha.is_empty());
+    }
+
+    #[test]
+    fn ref_diff_compares_branches_with_and_without_merge_base() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("shared.txt"), "base\n").expect("write shared");
+        let base = commit_all(&repo, "base");
+        let main = repo
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(str::to_string))
+            .expect("main branch");
+        let base_commit = repo.find_commit(base).expect("base commit");
+        repo.branch("feature", &base_commit, false).expect("branch");
+        fs::write(root.join("main.txt"), "main\n").expect("write main");
+        commit_all(&repo, "main work");
+
+        repo.set_head("refs/heads/feature").expect("set head");
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .expect("checkout feature");
+        fs::write(root.join("feature.txt"), "one\n").expect("write feature");
+        let first = commit_all(&repo, "feature one");
+        fs::write(root.join("shared.txt"), "base\nfeature\n").expect("edit shared");
+        let second = commit_all(&repo, "feature two");
+
+        let diff = ref_diff(&root, &main, "feature", true, false).expect("merge-base diff");
+        assert_eq!(diff.merge_base_sha, Some(base.to_string()));
+        assert_eq!(diff.head_sha, second.to_string());
+        let files = diff
+            .files
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.status.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![("feature.txt", "A"), ("shared.txt", "M")]);
+        let shas = diff
+            .commits
+            .iter()
+            .map(|entry| entry.sha.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(shas, vec![second.to_string(), first.to_string()]);
+        assert_eq!(diff.total_commits, 2);
+
+        let direct = ref_diff(&root, &main, "feature", false, false).expect("direct diff");
+        assert_eq!(direct.merge_base_sha, None);
+        let files = direct
+            .files
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.status.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![("feature.txt", "A"), ("main.txt", "D"), ("shared.txt", "M")]
+        );
+        assert_eq!(direct.total_commits, 2);
+
+        let missing = ref_diff(&root, "nope", "feature", true, false).unwrap_err();
+        assert!(missing.starts_with("Unknown ref `nope`"));
+    }
+
+    #[test]
+    fn parse_stash_branch_reads_both_message_forms() {
+        assert_eq!(
+            parse_stash_branch("WIP on main: 1a2b3c4 Fix build"),
+            Some("main".to_string())
+        );
+        assert_eq!(
+            parse_stash_branch("On feature/x: before rebase"),
+            Some("feature/x".to_string())
+        );
+        assert_eq!(parse_stash_branch("WIP on (no branch): 1a2b3c4 x"), None);
+        assert_eq!(parse_stash_branch("custom"), None);
+    }
+
+    #[test]
+    fn stash_create_list_diff_and_pop() {
+        let (root, repo) = create_temp_repo();
+        let mut config = repo.config().expect("repo config");
+        config.set_str("user.name", "Test").expect("user.name");
+        config
+            .set_str("user.email", "test@example.com")
+            .expect("user.email");
+        fs::write(root.join("tracked.txt"), "one\n").expect("write file");
+        commit_all(&repo, "init");
+        fs::write(root.join("tracked.txt"), "one\ntwo\n").expect("edit file");
+        fs::write(root.join("scratch.txt"), "scratch\n").expect("write untracked");
+
+        let workspaces = workspace_for(&root);
+        let app_settings = Mutex::new(AppSettings::default());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+        runtime.block_on(async {
+            let id = || "ws-1".to_string();
+            create_git_stash_core(&workspaces, id(), Some("wip".to_string()), true)
+                .await
+                .expect("create stash");
+            assert!(!root.join("scratch.txt").exists());
+
+            let stashes = list_git_stashes_core(&workspaces, id())
+                .await
+                .expect("list");
+            assert_eq!(stashes.len(), 1);
+            assert_eq!(stashes[0].index, 0);
+            assert!(stashes[0].message.ends_with("wip"));
+            assert!(stashes[0].branch.is_some());
+
+            let sha = stashes[0].sha.clone();
+            let diff = get_git_stash_diff_core(&workspaces, &app_settings, id(), sha.clone())
+                .await
+                .expect("stash diff");
+            let paths = diff
+                .iter()
+                .map(|entry| (entry.path.as_str(), entry.status.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(paths, vec![("tracked.txt", "M"), ("scratch.txt", "A")]);
+            assert!(diff[0].diff.contains("+two"));
+
+            let empty = create_git_stash_core(&workspaces, id(), None, false).await;
+            assert_eq!(empty.unwrap_err(), "No local changes to stash.");
+
+            pop_git_stash_core(&workspaces, id(), sha.clone())
+                .await
+                .expect("pop");
+            assert!(root.join("scratch.txt").exists());
+            assert!(list_git_stashes_core(&workspaces, id())
+                .await
+                .expect("list")
+                .is_empty());
+            assert!(
+                get_git_stash_diff_core(&workspaces, &app_settings, id(), sha.clone())
+                    .await
+                    .is_err()
+            );
+            assert_eq!(
+                drop_git_stash_core(&workspaces, id(), sha)
+                    .await
+                    .unwrap_err(),
+                "Stash no longer exists. Refresh the stash list."
+            );
+            assert_eq!(
+                apply_git_stash_core(&workspaces, id(), "not-a-sha".to_string())
+                    .await
+                    .unwrap_err(),
+                "Invalid stash id: not-a-sha"
+            );
+        });
+    }
+}
@@ -0,0 +1,588 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Instant};
+use uuid::Uuid;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::protocol::{
+    decode_response, CommandExecParams, CommandExecResponse, Request, SandboxPolicy,
+};
+use crate::shared::process_core::{kill_child_process_tree, tokio_command};
+use crate::shared::workspaces_core::{normalize_setup_script, worktree_setup_mark_ran_core};
+use crate::types::{ScriptRunSource, WorkspaceEntry, WorkspaceScript, WorkspaceScriptRun};
+
+const DEFAULT_SCRIPT_TIMEOUT: Duration = Duration::from_secs(600);
+/// Codex reports a command it killed for running past `timeoutMs` with this exit code.
+const COMMAND_EXEC_TIMEOUT_EXIT_CODE: i32 = 124;
+/// Extra time `command/exec` gets to report its own timeout before the request is abandoned.
+const COMMAND_EXEC_GRACE: Duration = Duration::from_secs(10);
+/// How long to keep reading output after the script exited; background
+/// processes it started may hold the pipes open.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+/// Output kept per stream in the returned run; the events carry all of it.
+const MAX_CAPTURED_OUTPUT: usize = 256 * 1024;
+
+fn resolve_script(entry: &WorkspaceEntry, script: &WorkspaceScript) -> Result<String, String> {
+    let settings = &entry.settings;
+    let text = match script {
+        WorkspaceScript::Launch { id: Some(id) } => settings
+            .launch_scripts
+            .as_ref()
+            .and_then(|scripts| scripts.iter().find(|candidate| &candidate.id == id))
+            .map(|candidate| candidate.script.clone())
+            .ok_or_else(|| format!("launch script not found: {id}"))?,
+        WorkspaceScript::Launch { id: None } => settings.launch_script.clone().unwrap_or_default(),
+        WorkspaceScript::WorktreeSetup => {
+            settings.worktree_setup_script.clone().unwrap_or_default()
+        }
+    };
+    normalize_setup_script(Some(text)).ok_or_else(|| "No script is configured.".to_string())
+}
+
+#[cfg(windows)]
+fn shell_command(script: &str) -> Vec<String> {
+    vec!["cmd".to_string(), "/C".to_string(), script.to_string()]
+}
+
+#[cfg(not(windows))]
+fn shell_command(script: &str) -> Vec<String> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    vec![shell, "-lc".to_string(), script.to_string()]
+}
+
+fn push_capped(buffer: &mut String, chunk: &str) {
+    let room = MAX_CAPTURED_OUTPUT.saturating_sub(buffer.len());
+    if chunk.len() <= room {
+        buffer.push_str(chunk);
+        return;
+    }
+    let mut end = room;
+    while !chunk.is_char_boundary(end) {
+        end -= 1;
+    }
+    buffer.push_str(&chunk[..end]);
+}
+
+fn emit_started<E: EventSink>(
+    event_sink: &E,
+    workspace_id: &str,
+    run_id: &str,
+    source: ScriptRunSource,
+) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "codex/script/started",
+            "params": {
+                "runId": run_id,
+                "source": source,
+                "streaming": source == ScriptRunSource::Local,
+            }
+        }),
+    });
+}
+
+fn emit_output<E: EventSink>(
+    event_sink: &E,
+    workspace_id: &str,
+    run_id: &str,
+    stream: &str,
+    data: &str,
+) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "codex/script/output",
+            "params": { "runId": run_id, "stream": stream, "data": data }
+        }),
+    });
+}
+
+fn emit_exited<E: EventSink>(event_sink: &E, workspace_id: &str, run: &WorkspaceScriptRun) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "codex/script/exited",
+            "params": {
+                "runId": run.run_id,
+                "exitCode": run.exit_code,
+                "timedOut": run.timed_out,
+                "source": run.source,
+                "error": null,
+            }
+        }),
+    });
+}
+
+/// Closes a run that failed before it produced an exit status, e.g. the
+/// process could not be spawned or `command/exec` stopped answering.
+fn emit_failed<E: EventSink>(
+    event_sink: &E,
+    workspace_id: &str,
+    run_id: &str,
+    source: ScriptRunSource,
+    error: &str,
+) {
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: json!({
+            "method": "codex/script/exited",
+            "params": {
+                "runId": run_id,
+                "exitCode": null,
+                "timedOut": false,
+                "source": source,
+                "error": error,
+            }
+        }),
+    });
+}
+
+/// Runs one of the workspace's stored scripts in its directory without a
+/// terminal. It runs as a local process and output is emitted line by line as
+/// `codex/script/output`. With `use_command_exec` and a connected app-server it
+/// goes through `command/exec` instead, which buffers output until the command
+/// exits, so the output arrives all at once. The run opens with `codex/script/started` (carrying `streaming`) and
+/// always ends with `codex/script/exited`, which carries `error` if the script
+/// could not be run. A successful worktree setup run is marked as done so it
+/// is not offered again.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_workspace_script_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: &E,
+    data_dir: &PathBuf,
+    workspace_id: String,
+    script: WorkspaceScript,
+    run_id: Option<String>,
+    timeout_secs: Option<u64>,
+    use_command_exec: bool,
+) -> Result<WorkspaceScriptRun, String> {
+    let entry = workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let command = shell_command(&resolve_script(&entry, &script)?);
+    let run_id = run_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let limit = timeout_secs
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SCRIPT_TIMEOUT);
+    let session = if use_command_exec {
+        sessions
+            .lock()
+            .await
+            .get(&workspace_id)
+            .filter(|session| session.is_connected())
+            .cloned()
+    } else {
+        None
+    };
+
+    let source = if session.is_some() {
+        ScriptRunSource::CommandExec
+    } else {
+        ScriptRunSource::Local
+    };
+    emit_started(event_sink, &workspace_id, &run_id, source);
+
+    let result = match session {
+        Some(session) => {
+            run_with_command_exec(&session, event_sink, run_id.clone(), command, limit).await
+        }
+        None => run_locally(event_sink, &entry, run_id.clone(), command, limit).await,
+    };
+    let run = match result {
+        Ok(run) => run,
+        Err(error) => {
+            emit_failed(event_sink, &workspace_id, &run_id, source, &error);
+            return Err(error);
+        }
+    };
+    emit_exited(event_sink, &workspace_id, &run);
+
+    if script == WorkspaceScript::WorktreeSetup
+        && run.exit_code == Some(0)
+        && entry.kind.is_worktree()
+    {
+        worktree_setup_mark_ran_core(workspaces, &workspace_id, data_dir).await?;
+    }
+    Ok(run)
+}
+
+async fn run_with_command_exec<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    run_id: String,
+    command: Vec<String>,
+    limit: Duration,
+) -> Result<WorkspaceScriptRun, String> {
+    let request = CommandExecParams {
+        command,
+        timeout_ms: Some(limit.as_millis() as u64),
+        cwd: Some(session.entry.path.clone()),
+        // Scripts are written by the user for their own machine (installs,
+        // caches outside the workspace), so they run unsandboxed as they
+        // would in a terminal.
+        sandbox_policy: Some(SandboxPolicy::DangerFullAccess),
+    };
+    let started = Instant::now();
+    let params = serde_json::to_value(&request).map_err(|err| err.to_string())?;
+    let response = session
+        .send_request_with_timeout(
+            CommandExecParams::METHOD,
+            params,
+            Some(limit + COMMAND_EXEC_GRACE),
+        )
+        .await?;
+    let result: CommandExecResponse = decode_response(CommandExecParams::METHOD, response)?;
+
+    let mut run = WorkspaceScriptRun {
+        run_id,
+        exit_code: Some(result.exit_code),
+        stdout: String::new(),
+        stderr: String::new(),
+        timed_out: result.exit_code == COMMAND_EXEC_TIMEOUT_EXIT_CODE && started.elapsed() >= limit,
+        source: ScriptRunSource::CommandExec,
+    };
+    for (stream, data, captured) in [
+        ("stdout", &result.stdout, &mut run.stdout),
+        ("stderr", &result.stderr, &mut run.stderr),
+    ] {
+        if !data.is_empty() {
+            emit_output(event_sink, &session.entry.id, &run.run_id, stream, data);
+            push_capped(captured, data);
+        }
+    }
+    Ok(run)
+}
+
+async fn run_locally<E: EventSink>(
+    event_sink: &E,
+    entry: &WorkspaceEntry,
+    run_id: String,
+    command: Vec<String>,
+    limit: Duration,
+) -> Result<WorkspaceScriptRun, String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| "No script is configured.".to_string())?;
+    let mut process = tokio_command(program);
+    process
+        .args(args)
+        .current_dir(&entry.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = process
+        .spawn()
+        .map_err(|err| format!("Failed to start script: {err}"))?;
+
+    let stdout = Arc::new(std::sync::Mutex::new(String::new()));
+    let stderr = Arc::new(std::sync::Mutex::new(String::new()));
+    let readers = [
+        child.stdout.take().map(|pipe| {
+            spawn_output_reader(pipe, event_sink, &entry.id, &run_id, "stdout", &stdout)
+        }),
+        child.stderr.take().map(|pipe| {
+            spawn_output_reader(pipe, event_sink, &entry.id, &run_id, "stderr", &stderr)
+        }),
+    ];
+
+    let (exit_code, timed_out) = match timeout(limit, child.wait()).await {
+        Ok(status) => (status.map_err(|err| err.to_string())?.code(), false),
+        Err(_) => {
+            kill_child_process_tree(&mut child).await;
+            let _ = child.wait().await;
+            (None, true)
+        }
+    };
+    for mut reader in readers.into_iter().flatten() {
+        if timeout(OUTPUT_DRAIN_TIMEOUT, &mut reader).await.is_err() {
+            reader.abort();
+        }
+    }
+
+    let take = |buffer: &Arc<std::sync::Mutex<String>>| {
+        buffer
+            .lock()
+            .map(|mut buffer| std::mem::take(&mut *buffer))
+            .unwrap_or_default()
+    };
+    Ok(WorkspaceScriptRun {
+        run_id,
+        exit_code,
+        stdout: take(&stdout),
+        stderr: take(&stderr),
+        timed_out,
+        source: ScriptRunSource::Local,
+    })
+}
+
+fn spawn_output_reader<R, E>(
+    pipe: R,
+    event_sink: &E,
+    workspace_id: &str,
+    run_id: &str,
+    stream: &'static str,
+    captured: &Arc<std::sync::Mutex<String>>,
+) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    E: EventSink,
+{
+    let event_sink = event_sink.clone();
+    let workspace_id = workspace_id.to_string();
+    let run_id = run_id.to_string();
+    let captured = Arc::clone(captured);
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let chunk = String::from_utf8_lossy(&line);
+                    emit_output(&event_sink, &workspace_id, &run_id, stream, &chunk);
+                    if let Ok(mut captured) = captured.lock() {
+                        push_capped(&mut captured, &chunk);
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::events::{TerminalExit, TerminalOutput};
+    use crate::shared::workspaces_core::worktree_setup_marker_path;
+    use crate::types::{LaunchScriptEntry, WorkspaceKind, WorkspaceSettings, WorktreeInfo};
+    use serde_json::Value;
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        messages: Arc<std::sync::Mutex<Vec<Value>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, event: AppServerEvent) {
+            self.messages.lock().unwrap().push(event.message);
+        }
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+        fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    }
+
+    fn entry(path: &str, settings: WorkspaceSettings) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "repo".to_string(),
+            path: path.to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some("parent".to_string()),
+            worktree: Some(WorktreeInfo {
+                branch: "feature".to_string(),
+            }),
+            settings,
+        }
+    }
+
+    #[test]
+    fn resolve_script_picks_launch_entry_or_setup_script() {
+        let entry = entry(
+            "/tmp",
+            WorkspaceSettings {
+                launch_script: Some("npm run dev".to_string()),
+                launch_scripts: Some(vec![LaunchScriptEntry {
+                    id: "test".to_string(),
+                    script: "cargo test".to_string(),
+                    icon: "play".to_string(),
+                    label: None,
+                }]),
+                worktree_setup_script: Some("   ".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        );
+        let launch = |id: Option<&str>| WorkspaceScript::Launch {
+            id: id.map(str::to_string),
+        };
+        assert_eq!(
+            resolve_script(&entry, &launch(None)).unwrap(),
+            "npm run dev"
+        );
+        assert_eq!(
+            resolve_script(&entry, &launch(Some("test"))).unwrap(),
+            "cargo test"
+        );
+        assert!(resolve_script(&entry, &launch(Some("missing")))
+            .unwrap_err()
+            .contains("missing"));
+        assert!(resolve_script(&entry, &WorkspaceScript::WorktreeSetup).is_err());
+    }
+
+    #[test]
+    fn push_capped_stops_at_a_char_boundary() {
+        let mut buffer = "a".repeat(MAX_CAPTURED_OUTPUT - 1);
+        push_capped(&mut buffer, "é!");
+        assert_eq!(buffer.len(), MAX_CAPTURED_OUTPUT - 1);
+        push_capped(&mut buffer, "b");
+        push_capped(&mut buffer, "c");
+        assert!(buffer.ends_with('b'));
+        assert_eq!(buffer.len(), MAX_CAPTURED_OUTPUT);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn setup_script_runs_locally_streams_output_and_marks_worktree() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-script-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data_dir = dir.join("data");
+        let workspace = entry(
+            dir.to_str().unwrap(),
+            WorkspaceSettings {
+                worktree_setup_script: Some("echo ready; echo oops >&2; pwd".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        );
+        let workspaces = Mutex::new(HashMap::from([(workspace.id.clone(), workspace)]));
+        let sessions = Mutex::new(HashMap::new());
+        let sink = RecordingSink::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let run = runtime
+            .block_on(run_workspace_script_core(
+                &workspaces,
+                &sessions,
+                &sink,
+                &data_dir,
+                "ws-1".to_string(),
+                WorkspaceScript::WorktreeSetup,
+                Some("run-1".to_string()),
+                None,
+                false,
+            ))
+            .unwrap();
+
+        assert_eq!(run.exit_code, Some(0));
+        assert_eq!(run.source, ScriptRunSource::Local);
+        assert!(run.stdout.starts_with("ready\n"));
+        assert!(run.stderr.ends_with("oops\n"));
+        let messages = sink.messages.lock().unwrap().clone();
+        let started = messages.first().unwrap();
+        assert_eq!(started["method"], "codex/script/started");
+        assert_eq!(started["params"]["runId"], "run-1");
+        assert_eq!(started["params"]["streaming"], true);
+        assert!(messages.iter().any(|message| {
+            message["method"] == "codex/script/output" && message["params"]["data"] == "ready\n"
+        }));
+        let exited = messages.last().unwrap();
+        assert_eq!(exited["method"], "codex/script/exited");
+        assert_eq!(exited["params"]["runId"], "run-1");
+        assert_eq!(exited["params"]["source"], "local");
+        assert!(worktree_setup_marker_path(&data_dir, "ws-1").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn script_that_fails_to_start_still_reports_exited() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-script-{}", Uuid::new_v4()));
+        let workspace = entry(
+            dir.to_str().unwrap(),
+            WorkspaceSettings {
+                launch_script: Some("echo never".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        );
+        let workspaces = Mutex::new(HashMap::from([(workspace.id.clone(), workspace)]));
+        let sessions = Mutex::new(HashMap::new());
+        let sink = RecordingSink::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // The workspace directory doesn't exist, so the process can't spawn.
+        let error = runtime
+            .block_on(run_workspace_script_core(
+                &workspaces,
+                &sessions,
+                &sink,
+                &dir,
+                "ws-1".to_string(),
+                WorkspaceScript::Launch { id: None },
+                Some("run-2".to_string()),
+                None,
+                false,
+            ))
+            .unwrap_err();
+
+        assert!(error.starts_with("Failed to start script"));
+        let messages = sink.messages.lock().unwrap().clone();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["method"], "codex/script/started");
+        let exited = &messages[1];
+        assert_eq!(exited["method"], "codex/script/exited");
+        assert_eq!(exited["params"]["runId"], "run-2");
+        assert_eq!(exited["params"]["exitCode"], Value::Null);
+        assert_eq!(exited["params"]["error"], error.as_str());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_script_is_killed_after_timeout() {
+        let dir = std::env::temp_dir();
+        let workspace = entry(
+            dir.to_str().unwrap(),
+            WorkspaceSettings {
+                launch_script: Some("sleep 5".to_string()),
+                ..WorkspaceSettings::default()
+            },
+        );
+        let workspaces = Mutex::new(HashMap::from([(workspace.id.clone(), workspace)]));
+        let sessions = Mutex::new(HashMap::new());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let run = runtime
+            .block_on(run_workspace_script_core(
+                &workspaces,
+                &sessions,
+                &RecordingSink::default(),
+                &dir,
+                "ws-1".to_string(),
+                WorkspaceScript::Launch { id: None },
+                None,
+                Some(1),
+                // Without a connected app-server this falls back to a local run.
+                true,
+            ))
+            .unwrap();
+
+        assert!(run.timed_out);
+        assert_eq!(run.exit_code, None);
+        assert!(!run.run_id.is_empty());
+    }
+}
@@ -1,8 +1,14 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use tokio::sync::Mutex;
 
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::protocol::{ConfigEdit, MergeStrategy};
 use crate::codex::config as codex_config;
+use crate::shared::codex_core;
 use crate::storage::write_settings;
 use crate::types::AppSettings;
 
@@ -42,19 +48,46 @@ pub(crate) async fn get_app_settings_core(app_settings: &Mutex<AppSettings>) ->
     settings
 }
 
+/// The `config.toml` keys mirrored from the app settings.
+fn codex_config_edits(settings: &AppSettings) -> Vec<ConfigEdit> {
+    let personality = normalize_personality(&settings.personality)
+        .map(|value| json!(value))
+        .unwrap_or(Value::Null);
+    [
+        (
+            "features.collab",
+            json!(settings.experimental_collab_enabled),
+        ),
+        (
+            "features.collaboration_modes",
+            json!(settings.collaboration_modes_enabled),
+        ),
+        ("features.steer", json!(settings.steer_enabled)),
+        (
+            "features.unified_exec",
+            json!(settings.unified_exec_enabled),
+        ),
+        ("features.apps", json!(settings.experimental_apps_enabled)),
+        ("personality", personality),
+    ]
+    .into_iter()
+    .map(|(key_path, value)| ConfigEdit {
+        key_path: key_path.to_string(),
+        value,
+        merge_strategy: MergeStrategy::Replace,
+    })
+    .collect()
+}
+
 pub(crate) async fn update_app_settings_core(
     settings: AppSettings,
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
 ) -> Result<AppSettings, String> {
-    let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
-    let _ = codex_config::write_collaboration_modes_enabled(
-        settings.collaboration_modes_enabled,
-    );
-    let _ = codex_config::write_steer_enabled(settings.steer_enabled);
-    let _ = codex_config::write_unified_exec_enabled(settings.unified_exec_enabled);
-    let _ = codex_config::write_apps_enabled(settings.experimental_apps_enabled);
-    let _ = codex_config::write_personality(settings.personality.as_str());
+    // Codex validates and writes its own config when an app-server is running;
+    // a value it rejects fails the save instead of landing in the file.
+    codex_core::write_default_codex_config_core(sessions, codex_config_edits(&settings)).await?;
     write_settings(settings_path, &settings)?;
     let mut current = app_settings.lock().await;
     *current = settings.clone();
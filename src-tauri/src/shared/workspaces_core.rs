@@ -8,7 +8,6 @@ use tokio::sync::Mutex;
 use crate::backend::app_server::WorkspaceSession;
 use crate::codex::args::resolve_workspace_codex_args;
 use crate::codex::home::resolve_workspace_codex_home;
-use crate::shared::process_core::kill_child_process_tree;
 use crate::storage::write_workspaces;
 use crate::types::{
     AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
@@ -88,7 +87,9 @@ pub(crate) async fn list_workspaces_core(
             name: entry.name.clone(),
             path: entry.path.clone(),
             codex_bin: entry.codex_bin.clone(),
-            connected: sessions.contains_key(&entry.id),
+            connected: sessions
+                .get(&entry.id)
+                .is_some_and(|session| session.is_connected()),
             kind: entry.kind.clone(),
             parent_id: entry.parent_id.clone(),
             worktree: entry.worktree.clone(),
@@ -234,8 +235,7 @@ where
             let mut workspaces = workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.kill().await;
         return Err(error);
     }
 
@@ -457,7 +457,9 @@ where
     };
     let codex_home = resolve_workspace_codex_home(&entry, parent_entry.as_ref());
     let session = spawn_session(entry.clone(), default_bin, codex_args, codex_home).await?;
-    sessions.lock().await.insert(entry.id, session);
+    if let Some(old_session) = sessions.lock().await.insert(entry.id, session) {
+        old_session.kill().await;
+    }
     Ok(())
 }
 
@@ -466,8 +468,7 @@ async fn kill_session_by_id(
     id: &str,
 ) {
     if let Some(session) = sessions.lock().await.remove(id) {
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.kill().await;
     }
 }
 
@@ -1016,8 +1017,7 @@ where
             .await
             .insert(entry_snapshot.id.clone(), new_session)
         {
-            let mut child = old_session.child.lock().await;
-            kill_child_process_tree(&mut child).await;
+            old_session.kill().await;
         }
     }
     if codex_home_changed || codex_args_changed {
@@ -1059,8 +1059,7 @@ where
                 .await
                 .insert(child.id.clone(), new_session)
             {
-                let mut child = old_session.child.lock().await;
-                kill_child_process_tree(&mut child).await;
+                old_session.kill().await;
             }
         }
     }
@@ -1097,6 +1096,62 @@ where
     })
 }
 
+/// Points a workspace at an account profile's CODEX_HOME, or clears the
+/// override when `profile_id` is `None`, restarting its app-server if it is
+/// connected.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn switch_workspace_account_core<FApplySettings, FSpawn, FutSpawn>(
+    id: String,
+    profile_id: Option<String>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    apply_settings_update: FApplySettings,
+    spawn_session: FSpawn,
+) -> Result<WorkspaceInfo, String>
+where
+    FApplySettings: Fn(&mut HashMap<String, WorkspaceEntry>, &str, WorkspaceSettings)
+        -> Result<WorkspaceEntry, String>,
+    FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
+    FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let codex_home = match profile_id.as_deref() {
+        Some(profile_id) => {
+            let settings = app_settings.lock().await;
+            let profile = settings
+                .account_profiles
+                .iter()
+                .find(|profile| profile.id == profile_id)
+                .ok_or_else(|| format!("account profile not found: {profile_id}"))?;
+            Some(profile.codex_home.clone())
+        }
+        None => None,
+    };
+
+    let (mut entry, parent_entry) = resolve_entry_and_parent(workspaces, &id).await?;
+    entry.settings.codex_home = codex_home;
+    // Codex refuses to start with a CODEX_HOME that does not exist yet.
+    if entry.settings.codex_home.is_some() {
+        if let Some(home) = resolve_workspace_codex_home(&entry, parent_entry.as_ref()) {
+            std::fs::create_dir_all(&home)
+                .map_err(|err| format!("Failed to create {}: {err}", home.display()))?;
+        }
+    }
+
+    update_workspace_settings_core(
+        id,
+        entry.settings,
+        workspaces,
+        sessions,
+        app_settings,
+        storage_path,
+        apply_settings_update,
+        spawn_session,
+    )
+    .await
+}
+
 pub(crate) async fn update_workspace_codex_bin_core(
     id: String,
     codex_bin: Option<String>,
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitFileStatus {
@@ -28,6 +29,12 @@ pub(crate) struct GitFileDiff {
     pub(crate) old_image_mime: Option<String>,
     #[serde(rename = "newImageMime")]
     pub(crate) new_image_mime: Option<String>,
+    /// HEAD-to-index patch, only set when the file also has unstaged changes.
+    #[serde(default, rename = "stagedDiff")]
+    pub(crate) staged_diff: Option<String>,
+    /// Index-to-worktree patch, only set when the file also has staged changes.
+    #[serde(default, rename = "unstagedDiff")]
+    pub(crate) unstaged_diff: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -61,6 +68,70 @@ pub(crate) struct GitLogEntry {
     pub(crate) timestamp: i64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitBlameHunk {
+    /// 1-based first line of the range in the current file.
+    #[serde(rename = "startLine")]
+    pub(crate) start_line: usize,
+    #[serde(rename = "lineCount")]
+    pub(crate) line_count: usize,
+    pub(crate) sha: String,
+    pub(crate) summary: String,
+    pub(crate) author: String,
+    pub(crate) timestamp: i64,
+    /// Path in the blamed commit when it differs from the current path.
+    #[serde(default, rename = "origPath")]
+    pub(crate) orig_path: Option<String>,
+    /// Lines changed in the working tree but not yet committed.
+    #[serde(default)]
+    pub(crate) uncommitted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitFileHistoryEntry {
+    pub(crate) sha: String,
+    pub(crate) summary: String,
+    pub(crate) author: String,
+    pub(crate) timestamp: i64,
+    /// The file's path as of this commit.
+    pub(crate) path: String,
+    pub(crate) status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitStashEntry {
+    /// Position in the stash list, as in `stash@{index}`.
+    pub(crate) index: usize,
+    pub(crate) message: String,
+    pub(crate) sha: String,
+    pub(crate) timestamp: i64,
+    /// The branch the stash was made on, parsed from the message.
+    pub(crate) branch: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitConflictFile {
+    pub(crate) path: String,
+    /// Common ancestor contents; `None` when the file was added on both sides.
+    pub(crate) base: Option<String>,
+    /// Our side; `None` when we deleted the file.
+    pub(crate) ours: Option<String>,
+    /// Their side; `None` when they deleted the file.
+    pub(crate) theirs: Option<String>,
+    /// Contents are omitted for binary files.
+    #[serde(default, rename = "isBinary")]
+    pub(crate) is_binary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GitConflictStrategy {
+    Ours,
+    Theirs,
+    /// Use caller-supplied merged contents.
+    Content,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitLogResponse {
     pub(crate) total: usize,
@@ -77,6 +148,22 @@ pub(crate) struct GitLogResponse {
     pub(crate) upstream: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitRefDiffResponse {
+    #[serde(rename = "baseSha")]
+    pub(crate) base_sha: String,
+    #[serde(rename = "headSha")]
+    pub(crate) head_sha: String,
+    /// The commit the diff starts from when comparing against the merge base.
+    #[serde(default, rename = "mergeBaseSha")]
+    pub(crate) merge_base_sha: Option<String>,
+    pub(crate) files: Vec<GitCommitDiff>,
+    /// Commits reachable from head but not from base, newest first.
+    pub(crate) commits: Vec<GitLogEntry>,
+    #[serde(rename = "totalCommits")]
+    pub(crate) total_commits: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubIssue {
     pub(crate) number: u64,
@@ -298,6 +385,52 @@ pub(crate) struct WorktreeSetupStatus {
     pub(crate) script: Option<String>,
 }
 
+/// A stored workspace script to run headless.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum WorkspaceScript {
+    /// An entry from `launchScripts`, or the single `launchScript` when `id` is omitted.
+    Launch {
+        #[serde(default)]
+        id: Option<String>,
+    },
+    WorktreeSetup,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScriptRunSource {
+    /// Ran through the workspace app-server's `command/exec`.
+    CommandExec,
+    /// Ran as a local process, the default and the fallback when no
+    /// app-server is connected.
+    Local,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct WorkspaceScriptRun {
+    #[serde(rename = "runId")]
+    pub(crate) run_id: String,
+    /// `None` when the script was killed before it exited.
+    #[serde(rename = "exitCode")]
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    #[serde(rename = "timedOut")]
+    pub(crate) timed_out: bool,
+    pub(crate) source: ScriptRunSource,
+}
+
+/// A named Codex login, kept in its own CODEX_HOME so workspaces can switch
+/// between accounts without logging out.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct AccountProfile {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(rename = "codexHome")]
+    pub(crate) codex_home: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct OpenAppTarget {
     pub(crate) id: String,
@@ -317,12 +450,20 @@ pub(crate) struct AppSettings {
     pub(crate) codex_bin: Option<String>,
     #[serde(default, rename = "codexArgs")]
     pub(crate) codex_args: Option<String>,
+    #[serde(default = "default_codex_auto_restart", rename = "codexAutoRestart")]
+    pub(crate) codex_auto_restart: bool,
+    #[serde(default, rename = "codexRequestTimeouts")]
+    pub(crate) codex_request_timeouts: HashMap<String, u64>,
+    #[serde(default, rename = "accountProfiles")]
+    pub(crate) account_profiles: Vec<AccountProfile>,
     #[serde(default, rename = "backendMode")]
     pub(crate) backend_mode: BackendMode,
     #[serde(default = "default_remote_backend_host", rename = "remoteBackendHost")]
     pub(crate) remote_backend_host: String,
     #[serde(default, rename = "remoteBackendToken")]
     pub(crate) remote_backend_token: Option<String>,
+    #[serde(default, rename = "remoteBackendTlsFingerprint")]
+    pub(crate) remote_backend_tls_fingerprint: Option<String>,
     #[serde(default = "default_access_mode", rename = "defaultAccessMode")]
     pub(crate) default_access_mode: String,
     #[serde(default = "default_review_delivery_mode", rename = "reviewDeliveryMode")]
@@ -727,6 +868,10 @@ fn default_experimental_collab_enabled() -> bool {
     false
 }
 
+fn default_codex_auto_restart() -> bool {
+    true
+}
+
 fn default_collaboration_modes_enabled() -> bool {
     true
 }
@@ -924,9 +1069,13 @@ impl Default for AppSettings {
         Self {
             codex_bin: None,
             codex_args: None,
+            codex_auto_restart: default_codex_auto_restart(),
+            codex_request_timeouts: HashMap::new(),
+            account_profiles: Vec::new(),
             backend_mode: BackendMode::Local,
             remote_backend_host: default_remote_backend_host(),
             remote_backend_token: None,
+            remote_backend_tls_fingerprint: None,
             default_access_mode: "current".to_string(),
             review_delivery_mode: default_review_delivery_mode(),
             composer_model_shortcut: default_composer_model_shortcut(),
@@ -994,9 +1143,13 @@ mod tests {
     fn app_settings_defaults_from_empty_json() {
         let settings: AppSettings = serde_json::from_str("{}").expect("settings deserialize");
         assert!(settings.codex_bin.is_none());
+        assert!(settings.codex_auto_restart);
+        assert!(settings.codex_request_timeouts.is_empty());
+        assert!(settings.account_profiles.is_empty());
         assert!(matches!(settings.backend_mode, BackendMode::Local));
         assert_eq!(settings.remote_backend_host, "127.0.0.1:4732");
         assert!(settings.remote_backend_token.is_none());
+        assert!(settings.remote_backend_tls_fingerprint.is_none());
         assert_eq!(settings.default_access_mode, "current");
         assert_eq!(settings.review_delivery_mode, "inline");
         let expected_primary = if cfg!(target_os = "macos") { "cmd" } else { "ctrl" };
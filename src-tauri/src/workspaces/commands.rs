@@ -30,16 +30,18 @@ use crate::backend::app_server::WorkspaceSession;
 use crate::codex::spawn_workspace_session;
 use crate::codex::args::resolve_workspace_codex_args;
 use crate::codex::home::resolve_workspace_codex_home;
+use crate::event_sink::TauriEventSink;
 use crate::git_utils::resolve_git_root;
 use crate::remote_backend;
-use crate::shared::process_core::{kill_child_process_tree, tokio_command};
+use crate::shared::process_core::tokio_command;
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
-use crate::shared::workspaces_core;
+use crate::shared::{script_core, workspaces_core};
 use crate::state::AppState;
 use crate::storage::write_workspaces;
 use crate::types::{
-    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeSetupStatus,
+    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceScript, WorkspaceScriptRun,
+    WorkspaceSettings, WorktreeSetupStatus,
 };
 use crate::utils::{git_env_path, resolve_git_binary};
 
@@ -261,8 +263,7 @@ pub(crate) async fn add_clone(
             let mut workspaces = state.workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.kill().await;
         let _ = tokio::fs::remove_dir_all(&destination_path).await;
         return Err(error);
     }
@@ -397,6 +398,55 @@ pub(crate) async fn worktree_setup_mark_ran(
         .await
 }
 
+/// Runs a stored launch or worktree setup script headless. Output streams as
+/// `codex/script/output` events; with `use_command_exec` the script goes
+/// through the app-server and its output only arrives once it exits, which
+/// `codex/script/started` reports as `streaming: false`.
+#[tauri::command]
+pub(crate) async fn run_workspace_script(
+    workspace_id: String,
+    script: WorkspaceScript,
+    run_id: Option<String>,
+    timeout_secs: Option<u64>,
+    use_command_exec: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceScriptRun, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "run_workspace_script",
+            json!({
+                "workspaceId": workspace_id,
+                "script": script,
+                "runId": run_id,
+                "timeoutSecs": timeout_secs,
+                "useCommandExec": use_command_exec,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
+    script_core::run_workspace_script_core(
+        &state.workspaces,
+        &state.sessions,
+        &TauriEventSink::new(app),
+        &data_dir,
+        workspace_id,
+        script,
+        run_id,
+        timeout_secs,
+        use_command_exec.unwrap_or(false),
+    )
+    .await
+}
+
 
 #[tauri::command]
 pub(crate) async fn remove_workspace(
@@ -739,6 +789,41 @@ pub(crate) async fn update_workspace_settings(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn switch_workspace_account(
+    workspace_id: String,
+    profile_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "switch_workspace_account",
+            json!({ "workspaceId": workspace_id, "profileId": profile_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::switch_workspace_account_core(
+        workspace_id,
+        profile_id,
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &state.storage_path,
+        |workspaces, workspace_id, next_settings| {
+            apply_workspace_settings_update(workspaces, workspace_id, next_settings)
+        },
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await
+}
+
 
 #[tauri::command]
 pub(crate) async fn update_workspace_codex_bin(
@@ -8,10 +8,10 @@ use super::worktree::{
     build_clone_destination_path, sanitize_clone_dir_name, sanitize_worktree_name,
 };
 use crate::backend::app_server::WorkspaceSession;
-use crate::shared::workspaces_core::rename_worktree_core;
+use crate::shared::workspaces_core::{rename_worktree_core, switch_workspace_account_core};
 use crate::storage::{read_workspaces, write_workspaces};
 use crate::types::{
-    AppSettings, WorktreeInfo, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+    AccountProfile, AppSettings, WorktreeInfo, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
 };
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex;
@@ -244,6 +244,67 @@ fn update_workspace_settings_persists_sort_and_group() {
     );
 }
 
+#[test]
+fn switch_workspace_account_sets_profile_codex_home() {
+    run_async(async {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).expect("create temp dir");
+        let company_home = temp_dir.join("codex-company");
+        let entry = WorkspaceEntry {
+            id: "workspace-1".to_string(),
+            name: "Workspace".to_string(),
+            path: temp_dir.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        let workspaces = Mutex::new(HashMap::from([(entry.id.clone(), entry.clone())]));
+        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let mut settings = AppSettings::default();
+        settings.account_profiles = vec![AccountProfile {
+            id: "company".to_string(),
+            name: "Company".to_string(),
+            codex_home: company_home.to_string_lossy().to_string(),
+        }];
+        let app_settings = Mutex::new(settings);
+        let storage_path = temp_dir.join("workspaces.json");
+
+        let switch = |profile_id: Option<&str>| {
+            switch_workspace_account_core(
+                entry.id.clone(),
+                profile_id.map(str::to_string),
+                &workspaces,
+                &sessions,
+                &app_settings,
+                &storage_path,
+                |workspaces, workspace_id, next_settings| {
+                    apply_workspace_settings_update(workspaces, workspace_id, next_settings)
+                },
+                |_entry, _default_bin, _codex_args, _codex_home| async move {
+                    Err("spawn not expected".to_string())
+                },
+            )
+        };
+
+        let updated = switch(Some("company")).await.expect("switch to company");
+        assert_eq!(
+            updated.settings.codex_home.as_deref(),
+            Some(company_home.to_string_lossy().as_ref())
+        );
+        assert!(company_home.is_dir());
+        let stored = read_workspaces(&storage_path).expect("read workspaces");
+        assert!(stored["workspace-1"].settings.codex_home.is_some());
+
+        let updated = switch(None).await.expect("switch back to default");
+        assert!(updated.settings.codex_home.is_none());
+
+        let error = switch(Some("missing")).await.expect_err("unknown profile");
+        assert!(error.contains("missing"));
+    });
+}
+
 #[test]
 fn rename_worktree_preserves_custom_name() {
     run_async(async {
@@ -73,6 +73,7 @@ import {
 } from "./features/layout/components/SidebarToggleControls";
 import { useAppSettingsController } from "./features/app/hooks/useAppSettingsController";
 import { useUpdaterController } from "./features/app/hooks/useUpdaterController";
+import { useRemoteBackendStatus } from "./features/app/hooks/useRemoteBackendStatus";
 import { useResponseRequiredNotificationsController } from "./features/app/hooks/useResponseRequiredNotificationsController";
 import { useErrorToasts } from "./features/notifications/hooks/useErrorToasts";
 import { useComposerShortcuts } from "./features/composer/hooks/useComposerShortcuts";
@@ -212,6 +213,7 @@ function MainApp() {
     addWorktreeAgent,
     connectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceDisconnected,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
@@ -313,6 +315,11 @@ function MainApp() {
 
   const { errorToasts, dismissErrorToast } = useErrorToasts();
 
+  useRemoteBackendStatus({
+    enabled: appSettings.backendMode === "remote",
+    onDebug: addDebugEntry,
+  });
+
   useEffect(() => {
     setAccessMode((prev) =>
       prev === "current" ? appSettings.defaultAccessMode : prev
@@ -725,6 +732,7 @@ function MainApp() {
   } = useThreads({
     activeWorkspace,
     onWorkspaceConnected: markWorkspaceConnected,
+    onWorkspaceDisconnected: markWorkspaceDisconnected,
     onDebug: addDebugEntry,
     model: resolvedModel,
     effort: resolvedEffort,
@@ -47,6 +47,7 @@ describe("useAppServerEvents", () => {
     const handlers: Handlers = {
       onAppServerEvent: vi.fn(),
       onWorkspaceConnected: vi.fn(),
+      onWorkspaceDisconnected: vi.fn(),
       onThreadStarted: vi.fn(),
       onThreadNameUpdated: vi.fn(),
       onBackgroundThreadAction: vi.fn(),
@@ -59,6 +60,10 @@ describe("useAppServerEvents", () => {
       onAgentMessageCompleted: vi.fn(),
       onAccountUpdated: vi.fn(),
       onAccountLoginCompleted: vi.fn(),
+      onMcpServerOauthLoginCompleted: vi.fn(),
+      onScriptStarted: vi.fn(),
+      onScriptOutput: vi.fn(),
+      onScriptExited: vi.fn(),
     };
     const { root } = await mount(handlers);
 
@@ -69,6 +74,23 @@ describe("useAppServerEvents", () => {
     });
     expect(handlers.onWorkspaceConnected).toHaveBeenCalledWith("ws-1");
 
+    act(() => {
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "codex/disconnected",
+          params: { exitCode: 1, signal: null, stderr: ["boom"], restarting: false },
+        },
+      });
+    });
+    expect(handlers.onWorkspaceDisconnected).toHaveBeenCalledWith("ws-1", {
+      restarting: false,
+      exitCode: 1,
+      signal: null,
+      stderr: ["boom"],
+      error: null,
+    });
+
     act(() => {
       listener?.({
         workspace_id: "ws-1",
@@ -273,6 +295,68 @@ describe("useAppServerEvents", () => {
       error: null,
     });
 
+    act(() => {
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "mcpServer/oauthLogin/completed",
+          params: { name: "linear", success: false, error: "denied" },
+        },
+      });
+    });
+    expect(handlers.onMcpServerOauthLoginCompleted).toHaveBeenCalledWith("ws-1", {
+      name: "linear",
+      success: false,
+      error: "denied",
+    });
+
+    act(() => {
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "codex/script/started",
+          params: { runId: "run-1", source: "commandExec", streaming: false },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "codex/script/output",
+          params: { runId: "run-1", stream: "stderr", data: "warn\n" },
+        },
+      });
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "codex/script/exited",
+          params: {
+            runId: "run-1",
+            exitCode: null,
+            timedOut: false,
+            source: "local",
+            error: "Failed to start script: not found",
+          },
+        },
+      });
+    });
+    expect(handlers.onScriptStarted).toHaveBeenCalledWith("ws-1", {
+      runId: "run-1",
+      source: "commandExec",
+      streaming: false,
+    });
+    expect(handlers.onScriptOutput).toHaveBeenCalledWith("ws-1", {
+      runId: "run-1",
+      stream: "stderr",
+      data: "warn\n",
+    });
+    expect(handlers.onScriptExited).toHaveBeenCalledWith("ws-1", {
+      runId: "run-1",
+      exitCode: null,
+      timedOut: false,
+      source: "local",
+      error: "Failed to start script: not found",
+    });
+
     await act(async () => {
       root.unmount();
     });
@@ -30,6 +30,16 @@ type AgentCompleted = {
 
 type AppServerEventHandlers = {
   onWorkspaceConnected?: (workspaceId: string) => void;
+  onWorkspaceDisconnected?: (
+    workspaceId: string,
+    payload: {
+      restarting: boolean;
+      exitCode: number | null;
+      signal: number | null;
+      stderr: string[];
+      error: string | null;
+    },
+  ) => void;
   onThreadStarted?: (workspaceId: string, thread: Record<string, unknown>) => void;
   onThreadNameUpdated?: (
     workspaceId: string,
@@ -88,6 +98,28 @@ type AppServerEventHandlers = {
     workspaceId: string,
     payload: { loginId: string | null; success: boolean; error: string | null },
   ) => void;
+  onMcpServerOauthLoginCompleted?: (
+    workspaceId: string,
+    payload: { name: string; success: boolean; error: string | null },
+  ) => void;
+  onScriptStarted?: (
+    workspaceId: string,
+    payload: { runId: string; source: string; streaming: boolean },
+  ) => void;
+  onScriptOutput?: (
+    workspaceId: string,
+    payload: { runId: string; stream: "stdout" | "stderr"; data: string },
+  ) => void;
+  onScriptExited?: (
+    workspaceId: string,
+    payload: {
+      runId: string;
+      exitCode: number | null;
+      timedOut: boolean;
+      source: string;
+      error: string | null;
+    },
+  ) => void;
 };
 
 export const METHODS_ROUTED_IN_USE_APP_SERVER_EVENTS = [
@@ -96,6 +128,10 @@ export const METHODS_ROUTED_IN_USE_APP_SERVER_EVENTS = [
   "account/updated",
   "codex/backgroundThread",
   "codex/connected",
+  "codex/disconnected",
+  "codex/script/exited",
+  "codex/script/output",
+  "codex/script/started",
   "error",
   "item/agentMessage/delta",
   "item/commandExecution/outputDelta",
@@ -108,6 +144,7 @@ export const METHODS_ROUTED_IN_USE_APP_SERVER_EVENTS = [
   "item/reasoning/textDelta",
   "item/started",
   "item/tool/requestUserInput",
+  "mcpServer/oauthLogin/completed",
   "thread/name/updated",
   "thread/started",
   "thread/tokenUsage/updated",
@@ -134,6 +171,19 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "codex/disconnected") {
+        handlers.onWorkspaceDisconnected?.(workspace_id, {
+          restarting: Boolean(params.restarting),
+          exitCode: typeof params.exitCode === "number" ? params.exitCode : null,
+          signal: typeof params.signal === "number" ? params.signal : null,
+          stderr: Array.isArray(params.stderr)
+            ? params.stderr.filter((line): line is string => typeof line === "string")
+            : [],
+          error: typeof params.error === "string" ? params.error : null,
+        });
+        return;
+      }
+
       const requestId = getAppServerRequestId(payload);
       const hasRequestId = requestId !== null;
 
@@ -345,6 +395,63 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "mcpServer/oauthLogin/completed") {
+        const name = String(params.name ?? "").trim();
+        if (!name) {
+          return;
+        }
+        const errorRaw = params.error ?? null;
+        const error =
+          typeof errorRaw === "string" && errorRaw.trim().length > 0 ? errorRaw : null;
+        handlers.onMcpServerOauthLoginCompleted?.(workspace_id, {
+          name,
+          success: Boolean(params.success),
+          error,
+        });
+        return;
+      }
+
+      if (method === "codex/script/started") {
+        const runId = String(params.runId ?? "");
+        if (!runId) {
+          return;
+        }
+        handlers.onScriptStarted?.(workspace_id, {
+          runId,
+          source: String(params.source ?? ""),
+          streaming: Boolean(params.streaming),
+        });
+        return;
+      }
+
+      if (method === "codex/script/output") {
+        const runId = String(params.runId ?? "");
+        if (!runId) {
+          return;
+        }
+        handlers.onScriptOutput?.(workspace_id, {
+          runId,
+          stream: params.stream === "stderr" ? "stderr" : "stdout",
+          data: String(params.data ?? ""),
+        });
+        return;
+      }
+
+      if (method === "codex/script/exited") {
+        const runId = String(params.runId ?? "");
+        if (!runId) {
+          return;
+        }
+        handlers.onScriptExited?.(workspace_id, {
+          runId,
+          exitCode: typeof params.exitCode === "number" ? params.exitCode : null,
+          timedOut: Boolean(params.timedOut),
+          source: String(params.source ?? ""),
+          error: typeof params.error === "string" ? params.error : null,
+        });
+        return;
+      }
+
       if (method === "item/completed") {
         const threadId = String(params.threadId ?? params.thread_id ?? "");
         const item = params.item as Record<string, unknown> | undefined;
@@ -0,0 +1,52 @@
+import { useCallback } from "react";
+import { useTauriEvent } from "./useTauriEvent";
+import {
+  subscribeRemoteBackendStatus,
+  type RemoteBackendStatusEvent,
+} from "../../../services/events";
+import { pushErrorToast } from "../../../services/toasts";
+import type { DebugEntry } from "../../../types";
+
+type Params = {
+  enabled: boolean;
+  onDebug: (entry: DebugEntry) => void;
+};
+
+export function useRemoteBackendStatus({ enabled, onDebug }: Params) {
+  const subscribe = useCallback(
+    (handler: (event: RemoteBackendStatusEvent) => void) =>
+      subscribeRemoteBackendStatus(handler, {
+        onError: (error) => {
+          onDebug({
+            id: `${Date.now()}-client-remote-backend-status-error`,
+            timestamp: Date.now(),
+            source: "error",
+            label: "remote-backend/status-error",
+            payload: error instanceof Error ? error.message : String(error),
+          });
+        },
+      }),
+    [onDebug],
+  );
+
+  useTauriEvent(
+    subscribe,
+    (event) => {
+      onDebug({
+        id: `${Date.now()}-server-remote-backend-status`,
+        timestamp: Date.now(),
+        source: event.state === "connected" ? "event" : "error",
+        label: `remote-backend/${event.state}`,
+        payload: event,
+      });
+      // One toast per outage; later attempts only show up in the debug panel.
+      if (event.state === "reconnecting" && event.attempt === 1) {
+        pushErrorToast({
+          title: "Remote backend disconnected",
+          message: `Lost connection to ${event.host}. Reconnecting…`,
+        });
+      }
+    },
+    { enabled },
+  );
+}
@@ -21,9 +21,11 @@ vi.mock("@tauri-apps/plugin-dialog", () => ({
 const baseSettings: AppSettings = {
   codexBin: null,
   codexArgs: null,
+  codexAutoRestart: true,
   backendMode: "local",
   remoteBackendHost: "127.0.0.1:4732",
   remoteBackendToken: null,
+  remoteBackendTlsFingerprint: null,
   defaultAccessMode: "current",
   reviewDeliveryMode: "inline",
   composerModelShortcut: null,
@@ -346,6 +346,9 @@ export function SettingsView({
   const [codexArgsDraft, setCodexArgsDraft] = useState(appSettings.codexArgs ?? "");
   const [remoteHostDraft, setRemoteHostDraft] = useState(appSettings.remoteBackendHost);
   const [remoteTokenDraft, setRemoteTokenDraft] = useState(appSettings.remoteBackendToken ?? "");
+  const [remoteFingerprintDraft, setRemoteFingerprintDraft] = useState(
+    appSettings.remoteBackendTlsFingerprint ?? "",
+  );
   const [scaleDraft, setScaleDraft] = useState(
     `${Math.round(clampUiScale(appSettings.uiScale) * 100)}%`,
   );
@@ -547,6 +550,10 @@ export function SettingsView({
     setRemoteTokenDraft(appSettings.remoteBackendToken ?? "");
   }, [appSettings.remoteBackendToken]);
 
+  useEffect(() => {
+    setRemoteFingerprintDraft(appSettings.remoteBackendTlsFingerprint ?? "");
+  }, [appSettings.remoteBackendTlsFingerprint]);
+
   useEffect(() => {
     setScaleDraft(`${Math.round(clampUiScale(appSettings.uiScale) * 100)}%`);
   }, [appSettings.uiScale]);
@@ -753,6 +760,20 @@ export function SettingsView({
     });
   };
 
+  const handleCommitRemoteFingerprint = async () => {
+    const nextFingerprint = remoteFingerprintDraft.trim()
+      ? remoteFingerprintDraft.trim()
+      : null;
+    setRemoteFingerprintDraft(nextFingerprint ?? "");
+    if (nextFingerprint === appSettings.remoteBackendTlsFingerprint) {
+      return;
+    }
+    await onUpdateAppSettings({
+      ...appSettings,
+      remoteBackendTlsFingerprint: nextFingerprint,
+    });
+  };
+
   const handleCommitScale = async () => {
     if (parsedScale === null) {
       setScaleDraft(`${Math.round(clampUiScale(appSettings.uiScale) * 100)}%`);
@@ -3052,6 +3073,27 @@ export function SettingsView({
                     Extra flags passed before <code>app-server</code>. Use quotes for values with
                     spaces.
                   </div>
+                  <div className="settings-toggle-row">
+                    <div>
+                      <div className="settings-toggle-title">Restart Codex if it exits</div>
+                      <div className="settings-toggle-subtitle">
+                        Restart a crashed app-server with backoff and resume its open threads.
+                      </div>
+                    </div>
+                    <button
+                      type="button"
+                      className={`settings-toggle ${appSettings.codexAutoRestart ? "on" : ""}`}
+                      onClick={() =>
+                        void onUpdateAppSettings({
+                          ...appSettings,
+                          codexAutoRestart: !appSettings.codexAutoRestart,
+                        })
+                      }
+                      aria-pressed={appSettings.codexAutoRestart}
+                    >
+                      <span className="settings-toggle-knob" />
+                    </button>
+                  </div>
                 <div className="settings-field-actions">
                   {codexDirty && (
                     <button
@@ -3216,8 +3258,30 @@ export function SettingsView({
                         aria-label="Remote backend token"
                       />
                     </div>
+                    <div className="settings-field-row">
+                      <input
+                        className="settings-input settings-input--compact"
+                        value={remoteFingerprintDraft}
+                        placeholder="TLS certificate SHA-256 fingerprint (optional)"
+                        onChange={(event) => setRemoteFingerprintDraft(event.target.value)}
+                        onBlur={() => {
+                          void handleCommitRemoteFingerprint();
+                        }}
+                        onKeyDown={(event) => {
+                          if (event.key === "Enter") {
+                            event.preventDefault();
+                            void handleCommitRemoteFingerprint();
+                          }
+                        }}
+                        aria-label="Remote backend TLS fingerprint"
+                      />
+                    </div>
                     <div className="settings-help">
                       Start the daemon separately and point CodexMonitor to it (host:port + token).
+                      Use <code>tls://host:port</code> or <code>wss://host:port</code> for a daemon
+                      started with <code>--tls-cert</code>; the fingerprint pins its certificate.
+                      A daemon on this machine can listen on <code>unix:/path/to.sock</code>
+                      instead, which needs no token.
                     </div>
                   </div>
                 )}
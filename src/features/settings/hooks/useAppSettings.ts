@@ -25,9 +25,11 @@ function buildDefaultSettings(): AppSettings {
   return {
     codexBin: null,
     codexArgs: null,
+    codexAutoRestart: true,
     backendMode: "local",
     remoteBackendHost: "127.0.0.1:4732",
     remoteBackendToken: null,
+    remoteBackendTlsFingerprint: null,
     defaultAccessMode: "current",
     reviewDeliveryMode: "inline",
     composerModelShortcut: isMac ? "cmd+shift+m" : "ctrl+shift+m",
@@ -26,6 +26,10 @@ type ThreadEventHandlersOptions = {
   pushThreadErrorMessage: (threadId: string, message: string) => void;
   onDebug?: (entry: DebugEntry) => void;
   onWorkspaceConnected: (workspaceId: string) => void;
+  onWorkspaceDisconnected?: (
+    workspaceId: string,
+    payload: { restarting: boolean },
+  ) => void;
   applyCollabThreadLinks: (
     threadId: string,
     item: Record<string, unknown>,
@@ -49,6 +53,7 @@ export function useThreadEventHandlers({
   pushThreadErrorMessage,
   onDebug,
   onWorkspaceConnected,
+  onWorkspaceDisconnected,
   applyCollabThreadLinks,
   onReviewExited,
   approvalAllowlistRef,
@@ -135,6 +140,7 @@ export function useThreadEventHandlers({
   const handlers = useMemo(
     () => ({
       onWorkspaceConnected,
+      onWorkspaceDisconnected,
       onApprovalRequest,
       onRequestUserInput,
       onBackgroundThreadAction,
@@ -161,6 +167,7 @@ export function useThreadEventHandlers({
     }),
     [
       onWorkspaceConnected,
+      onWorkspaceDisconnected,
       onApprovalRequest,
       onRequestUserInput,
       onBackgroundThreadAction,
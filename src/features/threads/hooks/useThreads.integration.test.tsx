@@ -11,6 +11,7 @@ import {
   setThreadName,
   startReview,
 } from "../../../services/tauri";
+import { pushErrorToast } from "../../../services/toasts";
 import { useThreads } from "./useThreads";
 
 type AppServerHandlers = Parameters<typeof useAppServerEvents>[0];
@@ -39,6 +40,10 @@ vi.mock("../../../services/tauri", () => ({
   interruptTurn: vi.fn(),
 }));
 
+vi.mock("../../../services/toasts", () => ({
+  pushErrorToast: vi.fn(),
+}));
+
 const workspace: WorkspaceInfo = {
   id: "ws-1",
   name: "CodexMonitor",
@@ -712,4 +717,34 @@ describe("useThreads UX integration", () => {
     ]);
     expect(unpinnedRows.map((row) => row.thread.id)).toEqual(["thread-b"]);
   });
+
+  it("shows a toast when an MCP server OAuth login fails", () => {
+    renderHook(() =>
+      useThreads({
+        activeWorkspace: workspace,
+        onWorkspaceConnected: vi.fn(),
+      }),
+    );
+
+    act(() => {
+      handlers?.onMcpServerOauthLoginCompleted?.("ws-1", {
+        name: "linear",
+        success: true,
+        error: null,
+      });
+    });
+    expect(vi.mocked(pushErrorToast)).not.toHaveBeenCalled();
+
+    act(() => {
+      handlers?.onMcpServerOauthLoginCompleted?.("ws-1", {
+        name: "linear",
+        success: false,
+        error: "access_denied",
+      });
+    });
+    expect(vi.mocked(pushErrorToast)).toHaveBeenCalledWith({
+      title: "MCP login failed: linear",
+      message: "access_denied",
+    });
+  });
 });
@@ -20,11 +20,13 @@ import { useThreadSelectors } from "./useThreadSelectors";
 import { useThreadStatus } from "./useThreadStatus";
 import { useThreadUserInput } from "./useThreadUserInput";
 import { setThreadName as setThreadNameService } from "../../../services/tauri";
+import { pushErrorToast } from "../../../services/toasts";
 import { makeCustomNameKey, saveCustomName } from "../utils/threadStorage";
 
 type UseThreadsOptions = {
   activeWorkspace: WorkspaceInfo | null;
   onWorkspaceConnected: (id: string) => void;
+  onWorkspaceDisconnected?: (id: string) => void;
   onDebug?: (entry: DebugEntry) => void;
   model?: string | null;
   effort?: string | null;
@@ -40,6 +42,7 @@ type UseThreadsOptions = {
 export function useThreads({
   activeWorkspace,
   onWorkspaceConnected,
+  onWorkspaceDisconnected,
   onDebug,
   model,
   effort,
@@ -134,6 +137,16 @@ export function useThreads({
     [onWorkspaceConnected, refreshAccountRateLimits, refreshAccountInfo],
   );
 
+  const handleWorkspaceDisconnected = useCallback(
+    (workspaceId: string, { restarting }: { restarting: boolean }) => {
+      // While a restart is pending the workspace comes back on its own.
+      if (!restarting) {
+        onWorkspaceDisconnected?.(workspaceId);
+      }
+    },
+    [onWorkspaceDisconnected],
+  );
+
   const handleAccountUpdated = useCallback(
     (workspaceId: string) => {
       void refreshAccountRateLimits(workspaceId);
@@ -213,6 +226,7 @@ export function useThreads({
     pushThreadErrorMessage,
     onDebug,
     onWorkspaceConnected: handleWorkspaceConnected,
+    onWorkspaceDisconnected: handleWorkspaceDisconnected,
     applyCollabThreadLinks,
     onReviewExited: handleReviewExited,
     approvalAllowlistRef,
@@ -226,13 +240,44 @@ export function useThreads({
     [handleAccountUpdated],
   );
 
+  const handleMcpServerOauthLoginCompleted = useCallback(
+    (
+      workspaceId: string,
+      payload: { name: string; success: boolean; error: string | null },
+    ) => {
+      if (!payload.success) {
+        pushErrorToast({
+          title: `MCP login failed: ${payload.name}`,
+          message: payload.error ?? "The OAuth login did not complete.",
+        });
+        return;
+      }
+      const threadId = state.activeThreadIdByWorkspace[workspaceId];
+      if (!threadId) {
+        return;
+      }
+      dispatch({
+        type: "addAssistantMessage",
+        threadId,
+        text: `Logged in to MCP server \`${payload.name}\`. Run /mcp to see its tools.`,
+      });
+    },
+    [dispatch, state.activeThreadIdByWorkspace],
+  );
+
   const handlers = useMemo(
     () => ({
       ...threadHandlers,
       onAccountUpdated: handleAccountUpdated,
       onAccountLoginCompleted: handleAccountLoginCompleted,
+      onMcpServerOauthLoginCompleted: handleMcpServerOauthLoginCompleted,
     }),
-    [threadHandlers, handleAccountUpdated, handleAccountLoginCompleted],
+    [
+      threadHandlers,
+      handleAccountUpdated,
+      handleAccountLoginCompleted,
+      handleMcpServerOauthLoginCompleted,
+    ],
   );
 
   useAppServerEvents(handlers);
@@ -411,6 +411,12 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     );
   }
 
+  function markWorkspaceDisconnected(id: string) {
+    setWorkspaces((prev) =>
+      prev.map((entry) => (entry.id === id ? { ...entry, connected: false } : entry)),
+    );
+  }
+
   const updateWorkspaceSettings = useCallback(
     async (workspaceId: string, patch: Partial<WorkspaceSettings>) => {
       onDebug?.({
@@ -872,6 +878,7 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
     addWorktreeAgent,
     connectWorkspace,
     markWorkspaceConnected,
+    markWorkspaceDisconnected,
     updateWorkspaceSettings,
     updateWorkspaceCodexBin,
     createWorkspaceGroup,
@@ -14,6 +14,14 @@ export type TerminalExitEvent = {
   terminalId: string;
 };
 
+export type RemoteBackendStatusEvent = {
+  state: "connected" | "disconnected" | "reconnecting";
+  host: string;
+  attempt: number;
+  retryInMs: number | null;
+  error: string | null;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -86,6 +94,9 @@ const dictationDownloadHub = createEventHub<DictationModelStatus>("dictation-dow
 const dictationEventHub = createEventHub<DictationEvent>("dictation-event");
 const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output");
 const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const remoteBackendStatusHub = createEventHub<RemoteBackendStatusEvent>(
+  "remote-backend-status",
+);
 const updaterCheckHub = createEventHub<void>("updater-check");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
 const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
@@ -146,6 +157,13 @@ export function subscribeTerminalExit(
   return terminalExitHub.subscribe(onEvent, options);
 }
 
+export function subscribeRemoteBackendStatus(
+  onEvent: (event: RemoteBackendStatusEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return remoteBackendStatusHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
@@ -3,26 +3,51 @@ import { invoke } from "@tauri-apps/api/core";
 import * as notification from "@tauri-apps/plugin-notification";
 import {
   addWorkspace,
+  applyGitStash,
+  cancelAppServerRequest,
+  codexLogout,
   compactThread,
+  createGitStash,
+  dropGitStash,
   fetchGit,
   forkThread,
+  getGitBlame,
+  getGitConflicts,
+  getGitFileHistory,
   getGitHubIssues,
   getGitLog,
+  getGitRefDiff,
+  getGitStashDiff,
   getGitStatus,
   getOpenAppIcon,
+  listGitStashes,
   listMcpServerStatus,
+  listMcpServers,
+  mcpServerOauthLogin,
   readGlobalAgentsMd,
   readGlobalCodexConfigToml,
   listWorkspaces,
   openWorkspaceIn,
+  popGitStash,
   readAgentMd,
+  readThread,
+  removeMcpServer,
+  resolveGitConflict,
+  rollbackThread,
   stageGitAll,
+  stageGitHunk,
   respondToServerRequest,
+  revertGitHunk,
   respondToUserInputRequest,
+  runBackgroundPrompt,
+  runWorkspaceScript,
   sendUserMessage,
   sendNotification,
   startReview,
   setThreadName,
+  switchWorkspaceAccount,
+  unstageGitHunk,
+  upsertMcpServer,
   writeGlobalAgentsMd,
   writeGlobalCodexConfigToml,
   writeAgentMd,
@@ -145,6 +170,20 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("maps workspaceId and method for cancel_app_server_request", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ cancelled: [7] });
+
+    await expect(cancelAppServerRequest("ws-10", "thread/list")).resolves.toEqual({
+      cancelled: [7],
+    });
+
+    expect(invokeMock).toHaveBeenCalledWith("cancel_app_server_request", {
+      workspaceId: "ws-10",
+      method: "thread/list",
+    });
+  });
+
   it("maps workspaceId/threadId/name for set_thread_name", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -158,6 +197,25 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("defaults includeTurns for read_thread and maps numTurns for rollback", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({}).mockResolvedValueOnce({});
+
+    await readThread("ws-10", "thread-4");
+    await rollbackThread("ws-10", "thread-4", 2);
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "read_thread", {
+      workspaceId: "ws-10",
+      threadId: "thread-4",
+      includeTurns: true,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "rollback_thread", {
+      workspaceId: "ws-10",
+      threadId: "thread-4",
+      numTurns: 2,
+    });
+  });
+
   it("maps workspaceId/cursor/limit for list_mcp_server_status", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -171,6 +229,92 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("maps MCP server editor and OAuth login commands", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock
+      .mockResolvedValueOnce({ servers: [] })
+      .mockResolvedValueOnce({ ok: true, reloaded: 1 })
+      .mockResolvedValueOnce({ ok: true, reloaded: 0 })
+      .mockResolvedValueOnce({ authorizationUrl: "https://example.com/auth" });
+
+    await listMcpServers("ws-10");
+    await upsertMcpServer("ws-10", "docs", { command: "npx", args: ["docs-mcp"] });
+    await removeMcpServer("ws-10", "old");
+    const login = await mcpServerOauthLogin("ws-10", "linear");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "list_mcp_servers", {
+      workspaceId: "ws-10",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "upsert_mcp_server", {
+      workspaceId: "ws-10",
+      name: "docs",
+      config: { command: "npx", args: ["docs-mcp"] },
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "remove_mcp_server", {
+      workspaceId: "ws-10",
+      name: "old",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(4, "mcp_server_oauth_login", {
+      workspaceId: "ws-10",
+      name: "linear",
+      scopes: null,
+      timeoutSecs: null,
+    });
+    expect(login.authorizationUrl).toBe("https://example.com/auth");
+  });
+
+  it("maps workspace account switching and logout", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock
+      .mockResolvedValueOnce({ id: "ws-3", settings: { codexHome: "~/.codex-work" } })
+      .mockResolvedValueOnce({ loggedOut: true });
+
+    await switchWorkspaceAccount("ws-3", "work");
+    await codexLogout("ws-3");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "switch_workspace_account", {
+      workspaceId: "ws-3",
+      profileId: "work",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "codex_logout", {
+      workspaceId: "ws-3",
+    });
+  });
+
+  it("runs workspace scripts with optional run id and timeout", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue({
+      runId: "run-1",
+      exitCode: 0,
+      stdout: "",
+      stderr: "",
+      timedOut: false,
+      source: "local",
+    });
+
+    await runWorkspaceScript("ws-4", { kind: "worktreeSetup" });
+    await runWorkspaceScript(
+      "ws-4",
+      { kind: "launch", id: "dev" },
+      { runId: "run-1", timeoutSecs: 30, useCommandExec: true },
+    );
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "run_workspace_script", {
+      workspaceId: "ws-4",
+      script: { kind: "worktreeSetup" },
+      runId: null,
+      timeoutSecs: null,
+      useCommandExec: false,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "run_workspace_script", {
+      workspaceId: "ws-4",
+      script: { kind: "launch", id: "dev" },
+      runId: "run-1",
+      timeoutSecs: 30,
+      useCommandExec: true,
+    });
+  });
+
   it("invokes stage_git_all", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -182,6 +326,147 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("maps hunk staging commands", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue(undefined);
+
+    await stageGitHunk("ws-6", "src/main.ts", "@@ -1,3 +1,4 @@", [2]);
+    await unstageGitHunk("ws-6", "src/main.ts", "@@ -1,3 +1,4 @@");
+    await revertGitHunk("ws-6", "src/main.ts", "@@ -8,2 +9,2 @@");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "stage_git_hunk", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      hunk: "@@ -1,3 +1,4 @@",
+      lines: [2],
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "unstage_git_hunk", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      hunk: "@@ -1,3 +1,4 @@",
+      lines: null,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "revert_git_hunk", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      hunk: "@@ -8,2 +9,2 @@",
+      lines: null,
+    });
+  });
+
+  it("maps get_git_ref_diff with merge base by default", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue({});
+
+    await getGitRefDiff("ws-6", "main", "codex/feature");
+    await getGitRefDiff("ws-6", "main", "codex/feature", { mergeBase: false });
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "get_git_ref_diff", {
+      workspaceId: "ws-6",
+      base: "main",
+      head: "codex/feature",
+      mergeBase: true,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "get_git_ref_diff", {
+      workspaceId: "ws-6",
+      base: "main",
+      head: "codex/feature",
+      mergeBase: false,
+    });
+  });
+
+  it("maps blame and file history commands", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue([]);
+
+    await getGitBlame("ws-6", "src/main.ts");
+    await getGitFileHistory("ws-6", "src/main.ts", 20);
+    await getGitFileHistory("ws-6", "src/main.ts");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "get_git_blame", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "get_git_file_history", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      limit: 20,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "get_git_file_history", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      limit: null,
+    });
+  });
+
+  it("maps conflict commands", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue(undefined);
+
+    await getGitConflicts("ws-6");
+    await resolveGitConflict("ws-6", "src/main.ts", "theirs");
+    await resolveGitConflict("ws-6", "src/lib.ts", "content", "merged\n");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "get_git_conflicts", {
+      workspaceId: "ws-6",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "resolve_git_conflict", {
+      workspaceId: "ws-6",
+      path: "src/main.ts",
+      strategy: "theirs",
+      content: null,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "resolve_git_conflict", {
+      workspaceId: "ws-6",
+      path: "src/lib.ts",
+      strategy: "content",
+      content: "merged\n",
+    });
+  });
+
+  it("maps stash commands", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValue(undefined);
+
+    await listGitStashes("ws-6");
+    await createGitStash("ws-6", { message: "wip", includeUntracked: true });
+    await createGitStash("ws-6");
+    await applyGitStash("ws-6", "abc123");
+    await popGitStash("ws-6", "def456");
+    await dropGitStash("ws-6", "0a1b2c");
+    await getGitStashDiff("ws-6", "def456");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "list_git_stashes", {
+      workspaceId: "ws-6",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "create_git_stash", {
+      workspaceId: "ws-6",
+      message: "wip",
+      includeUntracked: true,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(3, "create_git_stash", {
+      workspaceId: "ws-6",
+      message: null,
+      includeUntracked: false,
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(4, "apply_git_stash", {
+      workspaceId: "ws-6",
+      sha: "abc123",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(5, "pop_git_stash", {
+      workspaceId: "ws-6",
+      sha: "def456",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(6, "drop_git_stash", {
+      workspaceId: "ws-6",
+      sha: "0a1b2c",
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(7, "get_git_stash_diff", {
+      workspaceId: "ws-6",
+      sha: "def456",
+    });
+  });
+
   it("invokes fetch_git", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -335,6 +620,25 @@ describe("tauri invoke wrappers", () => {
     });
   });
 
+  it("passes background prompt options through", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({ text: "ok" });
+
+    await runBackgroundPrompt("ws-5", "Summarize", { expectJson: true });
+    await runBackgroundPrompt("ws-5", "Summarize");
+
+    expect(invokeMock).toHaveBeenNthCalledWith(1, "run_background_prompt", {
+      workspaceId: "ws-5",
+      prompt: "Summarize",
+      options: { expectJson: true },
+    });
+    expect(invokeMock).toHaveBeenNthCalledWith(2, "run_background_prompt", {
+      workspaceId: "ws-5",
+      prompt: "Summarize",
+      options: null,
+    });
+  });
+
   it("nests decisions for server request responses", async () => {
     const invokeMock = vi.mocked(invoke);
     invokeMock.mockResolvedValueOnce({});
@@ -11,14 +11,20 @@ import type {
   WorkspaceSettings,
 } from "../types";
 import type {
+  GitBlameHunk,
   GitFileDiff,
+  GitFileHistoryEntry,
   GitFileStatus,
   GitCommitDiff,
+  GitConflictFile,
+  GitConflictStrategy,
   GitHubIssuesResponse,
   GitHubPullRequestComment,
   GitHubPullRequestDiff,
   GitHubPullRequestsResponse,
   GitLogResponse,
+  GitRefDiffResponse,
+  GitStashEntry,
   ReviewTarget,
 } from "../types";
 
@@ -118,6 +124,46 @@ export async function writeGlobalCodexConfigToml(content: string): Promise<void>
   return fileWrite("global", "config", content);
 }
 
+export type CodexConfigEdit = {
+  keyPath: string;
+  value: unknown;
+  mergeStrategy?: "replace" | "upsert";
+};
+
+export async function readCodexConfig(
+  workspaceId?: string | null,
+  includeLayers = false,
+) {
+  return invoke<any>("read_codex_config", {
+    workspaceId: workspaceId ?? null,
+    includeLayers,
+  });
+}
+
+export async function writeCodexConfigValue(
+  edit: CodexConfigEdit,
+  options?: { workspaceId?: string | null; expectedVersion?: string | null },
+) {
+  return invoke<any>("write_codex_config_value", {
+    workspaceId: options?.workspaceId ?? null,
+    keyPath: edit.keyPath,
+    value: edit.value ?? null,
+    mergeStrategy: edit.mergeStrategy ?? null,
+    expectedVersion: options?.expectedVersion ?? null,
+  });
+}
+
+export async function batchWriteCodexConfig(
+  edits: CodexConfigEdit[],
+  options?: { workspaceId?: string | null; expectedVersion?: string | null },
+) {
+  return invoke<any>("batch_write_codex_config", {
+    workspaceId: options?.workspaceId ?? null,
+    edits,
+    expectedVersion: options?.expectedVersion ?? null,
+  });
+}
+
 export async function getConfigModel(workspaceId: string): Promise<string | null> {
   const response = await invoke<{ model?: string | null }>("get_config_model", {
     workspaceId,
@@ -177,6 +223,37 @@ export async function markWorktreeSetupRan(workspaceId: string): Promise<void> {
   return invoke("worktree_setup_mark_ran", { workspaceId });
 }
 
+export type WorkspaceScript =
+  | { kind: "launch"; id?: string | null }
+  | { kind: "worktreeSetup" };
+
+export type WorkspaceScriptRun = {
+  runId: string;
+  exitCode: number | null;
+  stdout: string;
+  stderr: string;
+  timedOut: boolean;
+  source: "commandExec" | "local";
+};
+
+// Output streams as `codex/script/output` events tagged with `runId`; pass a
+// runId to match them before the call resolves. `useCommandExec` runs the
+// script through the app-server instead, which only emits output once the
+// script exits (`codex/script/started` reports `streaming: false`).
+export async function runWorkspaceScript(
+  workspaceId: string,
+  script: WorkspaceScript,
+  options: { runId?: string; timeoutSecs?: number; useCommandExec?: boolean } = {},
+): Promise<WorkspaceScriptRun> {
+  return invoke<WorkspaceScriptRun>("run_workspace_script", {
+    workspaceId,
+    script,
+    runId: options.runId ?? null,
+    timeoutSecs: options.timeoutSecs ?? null,
+    useCommandExec: options.useCommandExec ?? false,
+  });
+}
+
 export async function updateWorkspaceSettings(
   id: string,
   settings: WorkspaceSettings,
@@ -184,6 +261,14 @@ export async function updateWorkspaceSettings(
   return invoke<WorkspaceInfo>("update_workspace_settings", { id, settings });
 }
 
+/** Points the workspace at an account profile's CODEX_HOME; `null` restores the default. */
+export async function switchWorkspaceAccount(
+  workspaceId: string,
+  profileId: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("switch_workspace_account", { workspaceId, profileId });
+}
+
 export async function updateWorkspaceCodexBin(
   id: string,
   codex_bin: string | null,
@@ -289,6 +374,14 @@ export async function interruptTurn(
   return invoke("turn_interrupt", { workspaceId, threadId, turnId });
 }
 
+/** Cancels the workspace's in-flight app-server requests for `method`, e.g. a hung `thread/list`. */
+export async function cancelAppServerRequest(
+  workspaceId: string,
+  method: string,
+): Promise<{ cancelled: number[] }> {
+  return invoke("cancel_app_server_request", { workspaceId, method });
+}
+
 export async function startReview(
   workspaceId: string,
   threadId: string,
@@ -338,6 +431,8 @@ export async function getGitStatus(workspace_id: string): Promise<{
   files: GitFileStatus[];
   stagedFiles: GitFileStatus[];
   unstagedFiles: GitFileStatus[];
+  conflictedFiles?: GitFileStatus[];
+  operation?: "merge" | "rebase" | "cherryPick" | "revert" | "bisect" | "am" | null;
   totalAdditions: number;
   totalDeletions: number;
 }> {
@@ -391,6 +486,122 @@ export async function revertGitFile(workspaceId: string, path: string) {
   return invoke("revert_git_file", { workspaceId, path });
 }
 
+// `hunk` is the `@@ -a,b +c,d @@` header from the `getGitDiffs` patch; for a
+// partly staged file take it from `unstagedDiff` to stage and `stagedDiff` to
+// unstage. `lines` picks body lines of that hunk (0-based, `\` markers not
+// counted).
+export async function stageGitHunk(
+  workspaceId: string,
+  path: string,
+  hunk: string,
+  lines?: number[],
+) {
+  return invoke("stage_git_hunk", { workspaceId, path, hunk, lines: lines ?? null });
+}
+
+export async function unstageGitHunk(
+  workspaceId: string,
+  path: string,
+  hunk: string,
+  lines?: number[],
+) {
+  return invoke("unstage_git_hunk", { workspaceId, path, hunk, lines: lines ?? null });
+}
+
+export async function revertGitHunk(
+  workspaceId: string,
+  path: string,
+  hunk: string,
+  lines?: number[],
+) {
+  return invoke("revert_git_hunk", { workspaceId, path, hunk, lines: lines ?? null });
+}
+
+export async function getGitRefDiff(
+  workspaceId: string,
+  base: string,
+  head: string,
+  options?: { mergeBase?: boolean },
+): Promise<GitRefDiffResponse> {
+  return invoke("get_git_ref_diff", {
+    workspaceId,
+    base,
+    head,
+    mergeBase: options?.mergeBase ?? true,
+  });
+}
+
+export async function getGitBlame(
+  workspaceId: string,
+  path: string,
+): Promise<GitBlameHunk[]> {
+  return invoke("get_git_blame", { workspaceId, path });
+}
+
+export async function getGitFileHistory(
+  workspaceId: string,
+  path: string,
+  limit?: number,
+): Promise<GitFileHistoryEntry[]> {
+  return invoke("get_git_file_history", { workspaceId, path, limit: limit ?? null });
+}
+
+export async function getGitConflicts(
+  workspaceId: string,
+): Promise<GitConflictFile[]> {
+  return invoke("get_git_conflicts", { workspaceId });
+}
+
+export async function resolveGitConflict(
+  workspaceId: string,
+  path: string,
+  strategy: GitConflictStrategy,
+  content?: string,
+): Promise<void> {
+  return invoke("resolve_git_conflict", {
+    workspaceId,
+    path,
+    strategy,
+    content: content ?? null,
+  });
+}
+
+export async function listGitStashes(
+  workspaceId: string,
+): Promise<GitStashEntry[]> {
+  return invoke("list_git_stashes", { workspaceId });
+}
+
+export async function createGitStash(
+  workspaceId: string,
+  options?: { message?: string | null; includeUntracked?: boolean },
+): Promise<void> {
+  return invoke("create_git_stash", {
+    workspaceId,
+    message: options?.message ?? null,
+    includeUntracked: options?.includeUntracked ?? false,
+  });
+}
+
+export async function applyGitStash(workspaceId: string, sha: string): Promise<void> {
+  return invoke("apply_git_stash", { workspaceId, sha });
+}
+
+export async function popGitStash(workspaceId: string, sha: string): Promise<void> {
+  return invoke("pop_git_stash", { workspaceId, sha });
+}
+
+export async function dropGitStash(workspaceId: string, sha: string): Promise<void> {
+  return invoke("drop_git_stash", { workspaceId, sha });
+}
+
+export async function getGitStashDiff(
+  workspaceId: string,
+  sha: string,
+): Promise<GitCommitDiff[]> {
+  return invoke("get_git_stash_diff", { workspaceId, sha });
+}
+
 export async function revertGitAll(workspaceId: string) {
   return invoke("revert_git_all", { workspaceId });
 }
@@ -472,6 +683,26 @@ export async function generateRunMetadata(workspaceId: string, prompt: string) {
   });
 }
 
+export type BackgroundPromptOptions = {
+  model?: string | null;
+  effort?: string | null;
+  accessMode?: "read-only" | "current" | "full-access";
+  timeoutSecs?: number;
+  expectJson?: boolean;
+};
+
+export async function runBackgroundPrompt(
+  workspaceId: string,
+  prompt: string,
+  options?: BackgroundPromptOptions,
+) {
+  return invoke<{ text: string; json?: unknown }>("run_background_prompt", {
+    workspaceId,
+    prompt,
+    options: options ?? null,
+  });
+}
+
 export async function getCollaborationModes(workspaceId: string) {
   return invoke<any>("collaboration_mode_list", { workspaceId });
 }
@@ -497,6 +728,10 @@ export async function cancelCodexLogin(workspaceId: string) {
   );
 }
 
+export async function codexLogout(workspaceId: string) {
+  return invoke<{ loggedOut: boolean; source?: string }>("codex_logout", { workspaceId });
+}
+
 export async function getSkillsList(workspaceId: string) {
   return invoke<any>("skills_list", { workspaceId });
 }
@@ -744,6 +979,66 @@ export async function listMcpServerStatus(
   return invoke<any>("list_mcp_server_status", { workspaceId, cursor, limit });
 }
 
+/** Mirrors a `[mcp_servers.<name>]` table in config.toml, so keys are snake_case. */
+export type McpServerConfig = {
+  command?: string;
+  args?: string[];
+  env?: Record<string, string>;
+  cwd?: string;
+  url?: string;
+  bearer_token_env_var?: string;
+  http_headers?: Record<string, string>;
+  enabled?: boolean;
+  startup_timeout_sec?: number;
+  tool_timeout_sec?: number;
+  enabled_tools?: string[];
+  disabled_tools?: string[];
+  [key: string]: unknown;
+};
+
+export type McpServerEntry = {
+  name: string;
+  config: McpServerConfig;
+};
+
+export async function listMcpServers(
+  workspaceId: string,
+): Promise<{ servers: McpServerEntry[] }> {
+  return invoke("list_mcp_servers", { workspaceId });
+}
+
+export async function upsertMcpServer(
+  workspaceId: string,
+  name: string,
+  config: McpServerConfig,
+): Promise<{ ok: boolean; reloaded: number }> {
+  return invoke("upsert_mcp_server", { workspaceId, name, config });
+}
+
+export async function removeMcpServer(
+  workspaceId: string,
+  name: string,
+): Promise<{ ok: boolean; reloaded: number }> {
+  return invoke("remove_mcp_server", { workspaceId, name });
+}
+
+export async function reloadMcpServers(workspaceId: string) {
+  return invoke<any>("reload_mcp_servers", { workspaceId });
+}
+
+export async function mcpServerOauthLogin(
+  workspaceId: string,
+  name: string,
+  options?: { scopes?: string[] | null; timeoutSecs?: number | null },
+): Promise<{ authorizationUrl: string }> {
+  return invoke("mcp_server_oauth_login", {
+    workspaceId,
+    name,
+    scopes: options?.scopes ?? null,
+    timeoutSecs: options?.timeoutSecs ?? null,
+  });
+}
+
 export async function resumeThread(workspaceId: string, threadId: string) {
   return invoke<any>("resume_thread", { workspaceId, threadId });
 }
@@ -752,6 +1047,34 @@ export async function archiveThread(workspaceId: string, threadId: string) {
   return invoke<any>("archive_thread", { workspaceId, threadId });
 }
 
+export async function unarchiveThread(workspaceId: string, threadId: string) {
+  return invoke<any>("unarchive_thread", { workspaceId, threadId });
+}
+
+export async function readThread(
+  workspaceId: string,
+  threadId: string,
+  includeTurns = true,
+) {
+  return invoke<any>("read_thread", { workspaceId, threadId, includeTurns });
+}
+
+export async function rollbackThread(
+  workspaceId: string,
+  threadId: string,
+  numTurns: number,
+) {
+  return invoke<any>("rollback_thread", { workspaceId, threadId, numTurns });
+}
+
+export async function listLoadedThreads(
+  workspaceId: string,
+  cursor?: string | null,
+  limit?: number | null,
+) {
+  return invoke<any>("list_loaded_threads", { workspaceId, cursor, limit });
+}
+
 export async function setThreadName(
   workspaceId: string,
   threadId: string,
@@ -141,12 +141,23 @@ export type OpenAppTarget = {
   args: string[];
 };
 
+/** A named Codex login kept in its own CODEX_HOME directory. */
+export type AccountProfile = {
+  id: string;
+  name: string;
+  codexHome: string;
+};
+
 export type AppSettings = {
   codexBin: string | null;
   codexArgs: string | null;
+  codexAutoRestart: boolean;
+  codexRequestTimeouts?: Record<string, number>;
+  accountProfiles?: AccountProfile[];
   backendMode: BackendMode;
   remoteBackendHost: string;
   remoteBackendToken: string | null;
+  remoteBackendTlsFingerprint: string | null;
   defaultAccessMode: AccessMode;
   reviewDeliveryMode: "inline" | "detached";
   composerModelShortcut: string | null;
@@ -274,6 +285,9 @@ export type GitFileDiff = {
   newImageData?: string | null;
   oldImageMime?: string | null;
   newImageMime?: string | null;
+  // Set only when the file has both staged and unstaged changes.
+  stagedDiff?: string | null;
+  unstagedDiff?: string | null;
 };
 
 export type GitCommitDiff = {
@@ -297,6 +311,40 @@ export type GitLogEntry = {
   timestamp: number;
 };
 
+export type GitBlameHunk = {
+  startLine: number;
+  lineCount: number;
+  sha: string;
+  summary: string;
+  author: string;
+  timestamp: number;
+  origPath?: string | null;
+  uncommitted?: boolean;
+};
+
+export type GitFileHistoryEntry = GitLogEntry & {
+  path: string;
+  status: string;
+};
+
+export type GitConflictFile = {
+  path: string;
+  base: string | null;
+  ours: string | null;
+  theirs: string | null;
+  isBinary?: boolean;
+};
+
+export type GitConflictStrategy = "ours" | "theirs" | "content";
+
+export type GitStashEntry = {
+  index: number;
+  message: string;
+  sha: string;
+  timestamp: number;
+  branch: string | null;
+};
+
 export type GitLogResponse = {
   total: number;
   entries: GitLogEntry[];
@@ -307,6 +355,15 @@ export type GitLogResponse = {
   upstream: string | null;
 };
 
+export type GitRefDiffResponse = {
+  baseSha: string;
+  headSha: string;
+  mergeBaseSha: string | null;
+  files: GitCommitDiff[];
+  commits: GitLogEntry[];
+  totalCommits: number;
+};
+
 export type GitHubIssue = {
   number: number;
   title: string;
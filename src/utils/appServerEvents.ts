@@ -6,7 +6,11 @@ export const SUPPORTED_APP_SERVER_METHODS = [
   "account/updated",
   "codex/backgroundThread",
   "codex/connected",
+  "codex/disconnected",
   "codex/event/skills_update_available",
+  "codex/script/exited",
+  "codex/script/output",
+  "codex/script/started",
   "error",
   "item/agentMessage/delta",
   "item/commandExecution/outputDelta",
@@ -19,6 +23,7 @@ export const SUPPORTED_APP_SERVER_METHODS = [
   "item/reasoning/textDelta",
   "item/started",
   "item/tool/requestUserInput",
+  "mcpServer/oauthLogin/completed",
   "thread/name/updated",
   "thread/started",
   "thread/tokenUsage/updated",